@@ -22,14 +22,44 @@ from typing import Dict, List, Mapping, Optional, Sequence, Tuple, TypedDict, Un
 from . import _native
 
 
+class RunResult(TypedDict):
+    """Outcome of a single LKH run. Times are in seconds."""
+
+    run: int
+    cost: int
+    penalty: int
+    trials: int
+    time: float
+
+
+class Route(TypedDict):
+    """One vehicle's route, starting at the depot."""
+
+    vehicle: int
+    nodes: List[int]
+    cost: int
+    load: int
+    duration: float
+
+
 class SolveReport(TypedDict):
     """Summary returned by the native LKH solver."""
 
     best_cost: int
+    best_cost_unscaled: float
     best_penalty: int
     runs: int
     dimension: int
     tour: List[int]
+    termination: str
+    lower_bound: float
+    lower_bound_gap: Optional[float]
+    optimum: Optional[int]
+    gap: Optional[float]
+    preprocessing_time: float
+    wall_time: float
+    run_results: List[RunResult]
+    routes: List[Route]
 
 
 class ProblemType(str, Enum):
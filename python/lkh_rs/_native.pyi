@@ -1,13 +1,37 @@
 from __future__ import annotations
 
-from typing import Dict, List, TypedDict, Union
+from typing import Dict, List, Optional, TypedDict, Union
+
+class RunResult(TypedDict):
+    run: int
+    cost: int
+    penalty: int
+    trials: int
+    time: float
+
+class Route(TypedDict):
+    vehicle: int
+    nodes: List[int]
+    cost: int
+    load: int
+    duration: float
 
 class SolveReport(TypedDict):
     best_cost: int
+    best_cost_unscaled: float
     best_penalty: int
     runs: int
     dimension: int
     tour: List[int]
+    termination: str
+    lower_bound: float
+    lower_bound_gap: Optional[float]
+    optimum: Optional[int]
+    gap: Optional[float]
+    preprocessing_time: float
+    wall_time: float
+    run_results: List[RunResult]
+    routes: List[Route]
 
 class ProblemData(TypedDict):
     kind: str
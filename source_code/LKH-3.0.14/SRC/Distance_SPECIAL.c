@@ -1,9 +1,12 @@
 #include "LKH.h"
+#include "LKH_rs.h"
 
 /*
  * The Distance_SPECIAL function may be used to specify a user defined
  * distance fuction. The function is used when the EDGE_WEIGHT_TYPE is
  * SPECIAL. 
+ *
+ * LKH-rs: if LKH_rs_DistanceHook is set, the distance is taken from it.
  * 
  * Example:
  *  
@@ -18,8 +21,17 @@
 int Distance_SPECIAL(Node * Na, Node * Nb)
 {
     const double GridSize = 1000000.0;
-    double dx = Na->X - Nb->X;
-    double dy = Na->Y - Nb->Y;
+    double dx, dy;
+    int W;
+
+    if (LKH_rs_DistanceHook) {
+        if (LKH_rs_DistanceHook(Na->Id, Nb->Id, &W))
+            eprintf("SPECIAL: no distance from node %d to node %d",
+                    Na->Id, Nb->Id);
+        return W;
+    }
+    dx = Na->X - Nb->X;
+    dy = Na->Y - Nb->Y;
     if (dx < 0)
         dx = -dx;
     if (dy < 0)
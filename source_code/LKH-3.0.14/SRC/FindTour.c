@@ -1,4 +1,5 @@
 #include "LKH.h"
+#include "LKH_rs.h"
 
 /*
  * After the candidate set has been created the FindTour function is called
@@ -61,6 +62,10 @@ GainType FindTour(void)
             Trial--;
             break;
         }
+        if (LKH_rs_StopRequested()) {
+            Trial--;
+            break;
+        }
         /* Choose FirstNode at random */
         if (Dimension == DimensionSaved)
             FirstNode = &NodeSet[1 + Random() % Dimension];
@@ -1,26 +1,33 @@
 #include "LKH.h"
 #include "Sequence.h"
 #include "Genetic.h"
+#include "LKH_rs.h"
 
 /*      
  * The FreeStructures function frees all allocated structures.
+ *
+ * LKH-rs: the candidate sets are freed by walking NodeSet rather than by
+ * FreeCandidateSets, which follows the Suc links. A search interrupted by
+ * eprintf may leave those links without a cycle through FirstNode.
  */
 
 #define Free(s) { free(s); s = 0; }
 
 void FreeStructures(void)
 {
-    FreeCandidateSets();
     FreeSegments();
     if (NodeSet) {
         int i;
         for (i = 1; i <= Dimension; i++) {
             Node *N = &NodeSet[i];
+            Free(N->CandidateSet);
+            Free(N->BackboneCandidateSet);
             Free(N->MergeSuc);
             Free(N->ColorAllowed);
             N->C = 0;
         }
         Free(NodeSet);
+        LKH_rs_NodeSetGeneration++;
     }
     Free(CostMatrix);
     Free(BestTour);
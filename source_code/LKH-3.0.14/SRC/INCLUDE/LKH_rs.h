@@ -0,0 +1,45 @@
+#ifndef _LKH_RS_H
+#define _LKH_RS_H
+
+/*
+ * This header specifies the interface used by the LKH-rs bindings to turn
+ * fatal eprintf errors into error codes that the caller can recover from,
+ * to stop a search between trials, to capture what LKH prints, and to
+ * compute SPECIAL distances with a caller-supplied function.
+ */
+
+#include "GainType.h"
+
+typedef void (*LKH_rs_VoidFunction) (void);
+typedef GainType (*LKH_rs_GainFunction) (void);
+typedef int (*LKH_rs_StopFunction) (void);
+typedef int (*LKH_rs_DistanceFunction) (int From, int To, int *Distance);
+
+extern LKH_rs_StopFunction LKH_rs_StopHook; /* Polled between trials */
+extern unsigned LKH_rs_NodeSetGeneration;   /* Bumped when NodeSet is freed */
+extern LKH_rs_DistanceFunction LKH_rs_DistanceHook; /* Used by Distance_SPECIAL */
+
+int LKH_rs_Guard(LKH_rs_VoidFunction Function);
+int LKH_rs_GuardGain(LKH_rs_GainFunction Function, GainType * Result);
+const char *LKH_rs_ErrorMessage(void);
+int LKH_rs_StopRequested(void);
+int LKH_rs_RedirectStdout(int Fd);
+void LKH_rs_RestoreStdout(void);
+void LKH_rs_Print(const char *Text);
+
+/*
+ * Some penalty functions remember a node of the current tour in a static
+ * pointer. LKH_RS_FORGET_STALE_NODE clears such a pointer if NodeSet has
+ * been freed since the previous call, so that a later solve in the same
+ * process never follows it into released memory.
+ */
+#define LKH_RS_FORGET_STALE_NODE(N)                                 \
+    do {                                                            \
+        static unsigned Generation = 0;                             \
+        if (Generation != LKH_rs_NodeSetGeneration) {               \
+            (N) = 0;                                                \
+            Generation = LKH_rs_NodeSetGeneration;                  \
+        }                                                           \
+    } while (0)
+
+#endif
@@ -0,0 +1,174 @@
+#include "LKH.h"
+#include "LKH_rs.h"
+#include <setjmp.h>
+#include <stdarg.h>
+
+/*
+ * LKH-rs replacement for eprintf.c, plus the stop hook polled by FindTour
+ * and the stdout redirection used to capture LKH's output.
+ *
+ * Upstream eprintf prints an error message and calls exit(EXIT_FAILURE).
+ * While a call made through LKH_rs_Guard or LKH_rs_GuardGain is active, this
+ * version stores the formatted message and jumps back to the guard, which
+ * then returns a nonzero value. Outside a guard the upstream behavior is
+ * kept unchanged.
+ *
+ * Guards wrap plain C calls, or Rust trampolines that own nothing needing
+ * to be dropped, so the jump never skips a Rust destructor. Memory
+ * allocated by the interrupted call is not released; the caller is
+ * expected to reset LKH's global state before the next solve, and
+ * ReadProblem.c clears each FILE pointer it closes so that the caller can
+ * tell which input streams are still open.
+ */
+
+#define MessageSize 1024
+
+static jmp_buf *GuardTarget = 0;
+static char Message[MessageSize];
+
+void eprintf(const char *fmt, ...)
+{
+    va_list args;
+    size_t Length;
+
+    if (!GuardTarget) {
+        if (LastLine && *LastLine)
+            fprintf(stderr, "\n%s\n", LastLine);
+        fprintf(stderr, "\n*** Error ***\n");
+        va_start(args, fmt);
+        vfprintf(stderr, fmt, args);
+        va_end(args);
+        fprintf(stderr, "\n");
+        exit(EXIT_FAILURE);
+    }
+    va_start(args, fmt);
+    vsnprintf(Message, MessageSize, fmt, args);
+    va_end(args);
+    Length = strlen(Message);
+    while (Length > 0 && Message[Length - 1] == '\n')
+        Message[--Length] = '\0';
+    if (LastLine && *LastLine && Length + 1 < MessageSize)
+        snprintf(Message + Length, MessageSize - Length,
+                 " (last line read: \"%s\")", LastLine);
+    longjmp(*GuardTarget, 1);
+}
+
+int LKH_rs_Guard(LKH_rs_VoidFunction Function)
+{
+    jmp_buf Target;
+    jmp_buf *Saved = GuardTarget;
+
+    if (setjmp(Target)) {
+        GuardTarget = Saved;
+        return 1;
+    }
+    GuardTarget = &Target;
+    Message[0] = '\0';
+    Function();
+    GuardTarget = Saved;
+    return 0;
+}
+
+int LKH_rs_GuardGain(LKH_rs_GainFunction Function, GainType * Result)
+{
+    jmp_buf Target;
+    jmp_buf *Saved = GuardTarget;
+
+    if (setjmp(Target)) {
+        GuardTarget = Saved;
+        return 1;
+    }
+    GuardTarget = &Target;
+    Message[0] = '\0';
+    *Result = Function();
+    GuardTarget = Saved;
+    return 0;
+}
+
+const char *LKH_rs_ErrorMessage(void)
+{
+    return Message;
+}
+
+/*
+ * LKH_rs_NodeSetGeneration is incremented by FreeStructures each time it
+ * frees NodeSet. LKH_RS_FORGET_STALE_NODE compares it with the value seen
+ * on its previous call to tell whether a remembered node belongs to a node
+ * set that no longer exists.
+ */
+
+unsigned LKH_rs_NodeSetGeneration = 0;
+
+/*
+ * The LKH_rs_StopRequested function is called by FindTour before each trial.
+ * It returns a nonzero value if the caller has installed a stop hook and
+ * the hook asks the search to end. FindTour then keeps the best tour of the
+ * trials completed so far, exactly as when the time limit is exceeded.
+ */
+
+LKH_rs_StopFunction LKH_rs_StopHook = 0;
+
+int LKH_rs_StopRequested(void)
+{
+    return LKH_rs_StopHook && LKH_rs_StopHook();
+}
+
+/*
+ * While LKH_rs_DistanceHook is set, Distance_SPECIAL asks it for the
+ * distance between two node ids instead of computing one from coordinates.
+ * The hook stores the distance and returns 0, or returns nonzero if it cannot
+ * provide one, in which case Distance_SPECIAL reports an error through
+ * eprintf.
+ */
+
+LKH_rs_DistanceFunction LKH_rs_DistanceHook = 0;
+
+/*
+ * The LKH_rs_RedirectStdout function makes the C stream stdout write to the
+ * file descriptor Fd, which it takes ownership of. Only the stream is
+ * replaced; file descriptor 1 of the process is left alone, so output that
+ * does not go through C's stdio is not affected. It returns 0 on success.
+ *
+ * LKH_rs_RestoreStdout flushes and closes the stream, which closes Fd, and
+ * puts the original stdout back.
+ *
+ * LKH_rs_Print writes Text to the current stdout stream.
+ */
+
+static FILE *SavedStdout = 0;
+
+int LKH_rs_RedirectStdout(int Fd)
+{
+#ifdef _WIN32
+    (void) Fd;
+    return -1;
+#else
+    FILE *Stream;
+
+    if (SavedStdout)
+        return -1;
+    fflush(stdout);
+    if (!(Stream = fdopen(Fd, "w")))
+        return -1;
+    setvbuf(Stream, 0, _IOLBF, BUFSIZ);
+    SavedStdout = stdout;
+    stdout = Stream;
+    return 0;
+#endif
+}
+
+void LKH_rs_RestoreStdout(void)
+{
+#ifndef _WIN32
+    if (!SavedStdout)
+        return;
+    fclose(stdout);
+    stdout = SavedStdout;
+    SavedStdout = 0;
+#endif
+}
+
+void LKH_rs_Print(const char *Text)
+{
+    fputs(Text, stdout);
+}
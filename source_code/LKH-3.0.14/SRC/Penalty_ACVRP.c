@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_ACVRP(void)
 {
@@ -8,6 +9,7 @@ GainType Penalty_ACVRP(void)
     GainType DemandSum, DistanceSum, P = 0;
     int Forward = SUCC(Depot)->Id != Depot->Id + DimensionSaved;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
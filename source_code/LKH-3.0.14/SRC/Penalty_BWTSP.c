@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_BWTSP(void)
 {
@@ -9,6 +10,7 @@ GainType Penalty_BWTSP(void)
     int WhiteCount;
     static Node *StartNode = 0;
 
+    LKH_RS_FORGET_STALE_NODE(StartNode);
     if (!StartNode)
         StartNode = N;
     if (StartNode->Id > DimensionSaved)
@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_CVRP(void)
 {
@@ -7,6 +8,7 @@ GainType Penalty_CVRP(void)
     Node *N, *CurrentRoute;
     GainType DemandSum, DistanceSum, P = 0;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
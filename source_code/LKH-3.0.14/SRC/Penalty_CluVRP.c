@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_CluVRP(void)
 {
@@ -8,6 +9,7 @@ GainType Penalty_CluVRP(void)
     GainType DemandSum, DistanceSum, P = 0;
     int *ColorUsed, i;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_GCTSP(void)
 {
@@ -14,6 +15,7 @@ GainType Penalty_GCTSP(void)
     while ((N2 = PREDD(N2))->DepotId == 0);
     Forward = N1 != N2 ? N1->DepotId < N2->DepotId : !Reversed;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     N = StartRoute;
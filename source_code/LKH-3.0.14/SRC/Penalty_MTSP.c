@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_MTSP_MINSUM(void)
 {
@@ -47,6 +48,7 @@ GainType Penalty_MTSP_MINMAX(void)
     Node *N, *NextN, *CurrentRoute;
     GainType Cost, MaxCost = MINUS_INFINITY;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
@@ -81,6 +83,7 @@ GainType Penalty_MTSP_MINMAX_SIZE(void)
     Node *N, *NextN, *CurrentRoute;
     int Size, MaxSize = INT_MIN;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
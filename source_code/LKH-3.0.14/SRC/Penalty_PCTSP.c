@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 static GainType Penalty_PCTSP_GCTSP(void);
 static GainType Penalty_PCTSP_SOP(void);
@@ -25,6 +26,7 @@ GainType Penalty_PCTSP_GCTSP(void)
     N2 = Depot;
     while ((N2 = PREDD(N2))->DepotId == 0);
     Forward = N1 != N2 ? N1->DepotId < N2->DepotId : !Reversed;
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     N = StartRoute;
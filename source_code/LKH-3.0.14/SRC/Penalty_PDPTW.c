@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_PDPTW(void)
 {
@@ -9,6 +10,7 @@ GainType Penalty_PDPTW(void)
     int Forward = SUCC(Depot)->Id != Depot->Id + DimensionSaved;
     int Pickups, Deliveries;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
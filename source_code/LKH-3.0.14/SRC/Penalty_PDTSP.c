@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_PDTSP(void)
 {
@@ -9,6 +10,7 @@ GainType Penalty_PDTSP(void)
     int Forward = SUCC(Depot)->Id != Depot->Id + DimensionSaved;
     int Pickups = 0, Deliveries = 0;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 static int *Queue;
 
@@ -13,6 +14,7 @@ GainType Penalty_PDTSPF(void)
 
     if (!Queue)
         Queue = (int *) malloc(Capacity * sizeof(int));
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 static int *Stack;
 
@@ -13,6 +14,7 @@ GainType Penalty_PDTSPL(void)
 
     if (!Stack)
         Stack = (int *) malloc(Dim * sizeof(int));
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
@@ -1,5 +1,6 @@
 #include "LKH.h"
 #include "Segment.h"
+#include "LKH_rs.h"
 
 GainType Penalty_RCTVRP(void)
 {
@@ -8,6 +9,7 @@ GainType Penalty_RCTVRP(void)
     GainType RiskSum, CostSum, DemandSum, P = 0;
     int Forward = SUCC(Depot)->Id != Depot->Id + DimensionSaved, d;
 
+    LKH_RS_FORGET_STALE_NODE(StartRoute);
     if (!StartRoute)
         StartRoute = Depot;
     if (StartRoute->Id > DimensionSaved)
@@ -983,6 +983,7 @@ void ReadProblem(void)
         ProblemType == PTP)
         Optimum *= -1;
     fclose(ProblemFile);
+    ProblemFile = 0;
     if (InitialTourFileName)
         ReadTour(InitialTourFileName, &InitialTourFile);
     if (InputTourFileName)
@@ -991,7 +992,7 @@ void ReadProblem(void)
         ReadTour(SubproblemTourFileName, &SubproblemTourFile);
     if (MergeTourFiles >= 1) {
         free(MergeTourFile);
-        MergeTourFile = (FILE **) malloc(MergeTourFiles * sizeof(FILE *));
+        MergeTourFile = (FILE **) calloc(MergeTourFiles, sizeof(FILE *));
         for (i = 0; i < MergeTourFiles; i++)
             ReadTour(MergeTourFileName[i], &MergeTourFile[i]);
     }
@@ -2718,6 +2719,7 @@ void ReadTour(char *FileName, FILE ** File)
     if (!Done)
         eprintf("Missing TOUR_SECTION in tour file: \"%s\"", FileName);
     fclose(*File);
+    *File = 0;
 }
 
 static void Read_RISK_THRESHOLD(void)
@@ -1,3 +1,4 @@
 #include "LKH-3.0.14/SRC/INCLUDE/LKH.h"
 #include "LKH-3.0.14/SRC/INCLUDE/Genetic.h"
-#include "LKH-3.0.14/SRC/INCLUDE/BIT.h"
\ No newline at end of file
+#include "LKH-3.0.14/SRC/INCLUDE/BIT.h"
+#include "LKH-3.0.14/SRC/INCLUDE/LKH_rs.h"
@@ -0,0 +1,332 @@
+//! The `lkh bench` subcommand: repeated solves over a directory of instances.
+
+use crate::output::OutputFormat;
+use clap::Args;
+use lkh_rs::solver::tour_cost;
+use lkh_rs::{solve_problem, LkhError, RoutingProblem, SearchParameters};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Args, Debug)]
+pub(crate) struct BenchArgs {
+    /// Directory of TSPLIB/LKH problem files. Files that are not problems,
+    /// such as tours and parameter files, are skipped.
+    dir: PathBuf,
+
+    /// Solves per instance and seed.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    repeat: u64,
+
+    /// Seeds to solve with, as a list (`1,2,3`) or an inclusive range
+    /// (`1..5`). Without it the seed of `--params` is used.
+    #[arg(long, value_parser = parse_seeds)]
+    seeds: Option<Seeds>,
+
+    /// Parameter file with the search settings shared by every solve. File
+    /// names in it are ignored.
+    #[arg(long, value_name = "PAR")]
+    params: Option<PathBuf>,
+}
+
+/// The seeds of one `--seeds` value.
+#[derive(Debug, Clone)]
+struct Seeds(Vec<u32>);
+
+/// Results of all solves of one instance.
+///
+/// The statistics are `None` when every solve of the instance failed.
+#[derive(Debug)]
+pub(crate) struct InstanceSummary {
+    name: String,
+    dimension: usize,
+    costs: Vec<i64>,
+    times: Vec<Duration>,
+    /// Cost of the instance's `.opt.tour`, or the `OPTIMUM` of `--params`.
+    optimum: Option<i64>,
+    /// What went wrong with the instance without stopping the bench, such as
+    /// an `.opt.tour` that could not be measured or a failed solve.
+    errors: Vec<String>,
+}
+
+impl InstanceSummary {
+    fn best(&self) -> Option<i64> {
+        self.costs.iter().copied().min()
+    }
+
+    fn average(&self) -> Option<f64> {
+        (!self.costs.is_empty())
+            .then(|| self.costs.iter().sum::<i64>() as f64 / self.costs.len() as f64)
+    }
+
+    /// Percentage by which the best cost exceeds the optimum, as LKH reports
+    /// it.
+    fn gap(&self) -> Option<f64> {
+        let best = self.best()?;
+        self.optimum
+            .filter(|&optimum| optimum != 0)
+            .map(|optimum| 100.0 * (best - optimum) as f64 / optimum as f64)
+    }
+
+    fn average_time(&self) -> Option<Duration> {
+        (!self.times.is_empty())
+            .then(|| self.times.iter().sum::<Duration>() / self.times.len() as u32)
+    }
+}
+
+/// One instance of the JSON report. `time` is the average solve time, as a
+/// `Duration` in its serde form like the times of a [`lkh_rs::SolveReport`].
+#[derive(Serialize)]
+struct JsonSummary<'a> {
+    instance: &'a str,
+    dimension: usize,
+    costs: &'a [i64],
+    best: Option<i64>,
+    average: Option<f64>,
+    optimum: Option<i64>,
+    gap: Option<f64>,
+    time: Option<Duration>,
+    errors: &'a [String],
+}
+
+pub(crate) fn run(args: &BenchArgs) -> Result<Vec<InstanceSummary>, LkhError> {
+    let base = match &args.params {
+        Some(path) => SearchParameters::from_path(path)?.parameters,
+        None => SearchParameters::new(),
+    };
+    let seeds: Vec<Option<u32>> = match &args.seeds {
+        Some(Seeds(seeds)) => seeds.iter().copied().map(Some).collect(),
+        None => vec![base.seed],
+    };
+
+    let mut summaries = Vec::new();
+    for path in problem_files(&args.dir)? {
+        let problem = match RoutingProblem::from_tsplib_path(&path) {
+            Ok(problem) => problem,
+            Err(err) => {
+                log::info!("skipping {}: {err}", path.display());
+                continue;
+            }
+        };
+        let mut errors = Vec::new();
+        let measured = optimal_tour(&path).and_then(|tour| {
+            tour.map(|tour| tour_cost(&problem, &base, &tour))
+                .transpose()
+        });
+        let optimum = match measured {
+            Ok(Some(optimum)) => Some(optimum),
+            Ok(None) => base.optimum,
+            Err(err) => {
+                log::warn!("{}: cannot measure the .opt.tour: {err}", path.display());
+                errors.push(format!("cannot measure the .opt.tour: {err}"));
+                base.optimum
+            }
+        };
+        let mut summary = InstanceSummary {
+            name: problem.name().to_owned(),
+            dimension: problem.dimension(),
+            costs: Vec::new(),
+            times: Vec::new(),
+            optimum,
+            errors,
+        };
+        for &seed in &seeds {
+            let mut parameters = base.clone();
+            parameters.seed = seed;
+            for _ in 0..args.repeat {
+                match solve_problem(&problem, &parameters) {
+                    Ok(report) => {
+                        summary.costs.push(report.best_cost);
+                        summary.times.push(report.wall_time);
+                    }
+                    Err(err) => {
+                        log::warn!("{}: solve failed: {err}", path.display());
+                        let seed =
+                            seed.map_or_else(|| "default".to_owned(), |seed| seed.to_string());
+                        summary.errors.push(format!("seed {seed}: {err}"));
+                    }
+                }
+            }
+        }
+        summaries.push(summary);
+    }
+    Ok(summaries)
+}
+
+/// Render `summaries` in `format`, which must not be [`OutputFormat::Tour`].
+pub(crate) fn render(summaries: &[InstanceSummary], format: OutputFormat) -> String {
+    let mut output = String::new();
+    match format {
+        OutputFormat::Text => {
+            writeln!(
+                &mut output,
+                "{:<20} {:>8} {:>6} {:>12} {:>14} {:>12} {:>9} {:>9}",
+                "Instance", "Nodes", "Solves", "Best", "Average", "Optimum", "Gap %", "Time s"
+            )
+            .unwrap();
+            for summary in summaries {
+                let missing = || "-".to_owned();
+                writeln!(
+                    &mut output,
+                    "{:<20} {:>8} {:>6} {:>12} {:>14} {:>12} {:>9} {:>9}",
+                    summary.name,
+                    summary.dimension,
+                    summary.costs.len(),
+                    summary.best().map_or_else(missing, |best| best.to_string()),
+                    summary
+                        .average()
+                        .map_or_else(missing, |average| format!("{average:.2}")),
+                    summary
+                        .optimum
+                        .map_or_else(missing, |optimum| optimum.to_string()),
+                    summary
+                        .gap()
+                        .map_or_else(missing, |gap| format!("{gap:.4}")),
+                    summary
+                        .average_time()
+                        .map_or_else(missing, |time| format!("{:.3}", time.as_secs_f64()))
+                )
+                .unwrap();
+            }
+            for summary in summaries {
+                for error in &summary.errors {
+                    writeln!(&mut output, "{}: {error}", summary.name).unwrap();
+                }
+            }
+        }
+        OutputFormat::Csv => {
+            output.push_str("instance,dimension,solves,best,average,optimum,gap,time,errors\n");
+            for summary in summaries {
+                let field = |value: Option<String>| value.unwrap_or_default();
+                writeln!(
+                    &mut output,
+                    "{},{},{},{},{},{},{},{},{}",
+                    csv_field(&summary.name),
+                    summary.dimension,
+                    summary.costs.len(),
+                    field(summary.best().map(|best| best.to_string())),
+                    field(summary.average().map(|average| average.to_string())),
+                    field(summary.optimum.map(|optimum| optimum.to_string())),
+                    field(summary.gap().map(|gap| gap.to_string())),
+                    field(
+                        summary
+                            .average_time()
+                            .map(|time| time.as_secs_f64().to_string())
+                    ),
+                    csv_field(&summary.errors.join("; "))
+                )
+                .unwrap();
+            }
+        }
+        OutputFormat::Json => {
+            let rows: Vec<JsonSummary> = summaries
+                .iter()
+                .map(|summary| JsonSummary {
+                    instance: &summary.name,
+                    dimension: summary.dimension,
+                    costs: &summary.costs,
+                    best: summary.best(),
+                    average: summary.average(),
+                    optimum: summary.optimum,
+                    gap: summary.gap(),
+                    time: summary.average_time(),
+                    errors: &summary.errors,
+                })
+                .collect();
+            output = serde_json::to_string(&rows).expect("bench summaries serialize to JSON");
+            output.push('\n');
+        }
+        OutputFormat::Tour => unreachable!("bench rejects the tour format before solving"),
+    }
+    output
+}
+
+/// Regular files in `dir`, sorted by name, without tours and parameter files.
+fn problem_files(dir: &Path) -> Result<Vec<PathBuf>, LkhError> {
+    let read_error = |source| LkhError::ReadFile {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        let skipped = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("tour" | "par")
+        );
+        if path.is_file() && !skipped {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read `<stem>.opt.tour` next to a problem file, if there is one.
+fn optimal_tour(problem_path: &Path) -> Result<Option<Vec<usize>>, LkhError> {
+    let Some(stem) = problem_path.file_stem() else {
+        return Ok(None);
+    };
+    let mut name = stem.to_os_string();
+    name.push(".opt.tour");
+    let path = problem_path.with_file_name(name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(&path).map_err(|source| LkhError::ReadFile {
+        path: path.clone(),
+        source,
+    })?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .skip_while(|(_, line)| line.trim() != "TOUR_SECTION");
+    if lines.next().is_none() {
+        return Err(LkhError::TsplibParse {
+            line: text.lines().count(),
+            message: format!("{} has no TOUR_SECTION", path.display()),
+        });
+    }
+    let mut tour = Vec::new();
+    for (index, line) in lines {
+        for token in line.split_whitespace() {
+            if token == "-1" || token == "EOF" {
+                return Ok(Some(tour));
+            }
+            let node = token.parse().map_err(|_| LkhError::TsplibParse {
+                line: index + 1,
+                message: format!("{}: invalid tour node {token:?}", path.display()),
+            })?;
+            tour.push(node);
+        }
+    }
+    Ok(Some(tour))
+}
+
+fn parse_seeds(value: &str) -> Result<Seeds, String> {
+    let invalid = || format!("expected seeds like 1,2,3 or 1..5, got {value:?}");
+    if let Some((first, last)) = value.split_once("..") {
+        let first: u32 = first.trim().parse().map_err(|_| invalid())?;
+        let last = last.trim_start_matches('=');
+        let last: u32 = last.trim().parse().map_err(|_| invalid())?;
+        if first > last {
+            return Err(invalid());
+        }
+        return Ok(Seeds((first..=last).collect()));
+    }
+    value
+        .split(',')
+        .map(|seed| seed.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()
+        .map(Seeds)
+}
+
+/// `value` as a CSV field, quoted when it holds a comma, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-/// Errors returned by the safe Rust layer before control reaches LKH's C core.
+/// Errors returned by the safe Rust layer and by LKH's C core.
 #[derive(Debug, thiserror::Error)]
 pub enum LkhError {
     #[error("parameter file does not exist: {0}")]
@@ -23,6 +23,13 @@ pub enum LkhError {
         source: std::io::Error,
     },
 
+    #[error("failed to read {path}: {source}")]
+    ReadFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     #[error("failed to build C string for {context}: {source}")]
     CString {
         context: &'static str,
@@ -33,6 +40,12 @@ pub enum LkhError {
     #[error("invalid routing problem: {0}")]
     InvalidProblem(String),
 
+    #[error("TSPLIB parse error on line {line}: {message}")]
+    TsplibParse { line: usize, message: String },
+
+    #[error("LKH parameter file parse error on line {line}: {message}")]
+    ParameterParse { line: usize, message: String },
+
     #[error("invalid search parameters: {0}")]
     InvalidSearchParameters(String),
 
@@ -47,4 +60,43 @@ pub enum LkhError {
 
     #[error("LKH returned no best tour")]
     MissingBestTour,
+
+    #[error("the solve was cancelled before LKH found a tour")]
+    Cancelled,
+
+    /// LKH rejected its input or state through `eprintf`, which upstream
+    /// reports by terminating the process.
+    #[error("LKH reported an error: {0}")]
+    Native(String),
+
+    #[error("failed to start LKH worker {program}: {source}")]
+    WorkerSpawn {
+        program: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("failed to communicate with LKH worker: {0}")]
+    WorkerIo(#[source] std::io::Error),
+
+    #[error("malformed LKH worker message: {0}")]
+    WorkerProtocol(String),
+
+    #[error("LKH worker reported an error: {0}")]
+    WorkerFailed(String),
+
+    #[error("LKH worker did not finish within {timeout:?}")]
+    WorkerTimeout { timeout: std::time::Duration },
+
+    #[error("the solver pool has shut down")]
+    PoolClosed,
+
+    #[error("LKH worker terminated abnormally (exit code {exit_code:?}): {stderr}")]
+    NativeAbort {
+        /// Exit code of the worker, or `None` when it was killed by a signal.
+        exit_code: Option<i32>,
+        /// Everything the worker wrote to stderr, including LKH's `eprintf`
+        /// diagnostics.
+        stderr: String,
+    },
 }
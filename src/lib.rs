@@ -57,18 +57,32 @@ pub mod ffi {
 }
 
 pub mod error;
+pub mod observer;
+pub mod pool;
 pub mod problem;
 pub mod solver;
+pub mod subprocess;
 
 #[cfg(feature = "python")]
 mod python;
 
 pub use error::LkhError;
-pub use problem::{Point2d, ProblemEntry, ProblemKind, RoutingProblem, SearchParameters};
+pub use observer::{SolveEvent, SolveObserver};
+pub use pool::{PendingSolve, PoolOptions, SolverPool};
+pub use problem::{
+    BwtspParameters, CandidateSetType, Coordinates, CvrpSpec, CvrptwSpec, Distances,
+    EdgeWeightFormat, EdgeWeightType, ExtraCandidateSetType, GeoPoint, InitialTourAlgorithm,
+    LkhParameterFile, MtspObjective, MtspSettings, Partitioning, PatchingMode, PdptwSpec,
+    PickupDelivery, Point2d, Point3d, ProblemEntry, ProblemKind, Recombination, RoutingProblem,
+    Scaling, SearchParameters, SubproblemSettings, TimeWindow, TsptwSpec,
+};
 pub use solver::{
-    solve_parameter_file, solve_problem, solve_problem_with_options, solve_with_options,
-    ProgrammaticSolveOptions, SolveOptions, SolveReport,
+    solve_parameter_file, solve_problem, solve_problem_with_distance, solve_problem_with_observer,
+    solve_problem_with_observer_and_options, solve_problem_with_options, solve_with_options,
+    CancellationToken, MtspSolution, NativeOutput, ProgrammaticSolveOptions, Route, RunResult,
+    RunStatistics, SalesmanTour, SolveOptions, SolveReport, TerminationReason,
 };
+pub use subprocess::SubprocessBackend;
 
 const PLUS_INFINITY: sys::GainType = i64::MAX;
 
@@ -1,25 +1,225 @@
-use clap::Parser;
-use lkh_rs::{solve_parameter_file, LkhError};
+use clap::{Args as ClapArgs, Parser, Subcommand};
+use lkh_rs::{solve_parameter_file, solve_problem, LkhError, RoutingProblem, SearchParameters};
+use output::OutputFormat;
+use std::path::{Path, PathBuf};
+
+mod bench;
+mod output;
 
 /// The Rust binding created for LKH3.
 #[derive(Parser, Debug)]
 #[command(author, bin_name = "lkh", version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
     /// Path of the parameter file. Example: ./source_code/LKH-3.0.14/pr2392.par
-    #[arg(short, long)]
-    par: String,
+    #[arg(short, long, required = true)]
+    par: Option<String>,
+
+    /// Format of the solve report.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    /// Write the report to this file instead of stdout.
+    #[arg(long, global = true, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Solve a TSPLIB problem file with search settings given as flags.
+    Solve(SolveArgs),
+    /// Solve every problem file in a directory and summarize the results.
+    Bench(bench::BenchArgs),
+    /// Serve solve requests from a parent process over stdin/stdout.
+    #[command(hide = true)]
+    Worker,
+}
+
+#[derive(ClapArgs, Debug)]
+struct SolveArgs {
+    /// TSPLIB/LKH problem file. Example: tests/fixtures/tiny.tsp
+    problem: PathBuf,
+
+    /// Number of independent runs.
+    #[arg(long)]
+    runs: Option<i32>,
+
+    /// Random seed.
+    #[arg(long)]
+    seed: Option<u32>,
+
+    /// Time limit in seconds for each run.
+    #[arg(long)]
+    time_limit: Option<f64>,
+
+    /// Maximum number of trials in each run.
+    #[arg(long)]
+    max_trials: Option<i32>,
+
+    /// Any other LKH parameter, as in a `.par` file. Repeatable.
+    #[arg(long = "lkh", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    lkh: Vec<(String, String)>,
+}
+
+impl SolveArgs {
+    /// Read `--lkh` settings like a `.par` file, then apply the typed flags.
+    fn search_parameters(&self) -> Result<SearchParameters, LkhError> {
+        let text: String = self
+            .lkh
+            .iter()
+            .map(|(key, value)| format!("{key} = {value}\n"))
+            .collect();
+        let file = SearchParameters::from_lkh_parameter_str(&text)?;
+        let names_file = file.problem_file.is_some()
+            || file.initial_tour_file.is_some()
+            || file.input_tour_file.is_some()
+            || !file.merge_tour_files.is_empty()
+            || file.tour_file.is_some()
+            || file.output_tour_file.is_some()
+            || file.subproblem_tour_file.is_some();
+        if names_file {
+            return Err(LkhError::InvalidSearchParameters(
+                "--lkh does not accept file keywords; use --par for parameter files".to_owned(),
+            ));
+        }
+        let mut parameters = file.parameters;
+        if let Some(runs) = self.runs {
+            parameters.runs = runs;
+        }
+        if let Some(seed) = self.seed {
+            parameters.seed = Some(seed);
+        }
+        if let Some(time_limit) = self.time_limit {
+            parameters.time_limit = Some(time_limit);
+        }
+        if let Some(max_trials) = self.max_trials {
+            parameters.max_trials = Some(max_trials);
+        }
+        parameters.validate()?;
+        Ok(parameters)
+    }
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    let (key, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got {value:?}"))?;
+    Ok((key.trim().to_owned(), value.trim().to_owned()))
 }
 
 fn main() -> Result<(), LkhError> {
     env_logger::init();
     let args = Args::parse();
-    let report = solve_parameter_file(args.par)?;
+    // LKH prints its progress to stdout, which must only carry the report
+    // when a machine-readable format is piped.
+    let redirect = (args.output_format != OutputFormat::Text && args.output.is_none())
+        .then(StdoutToStderr::new);
+    let rendered = match args.command {
+        Some(Command::Worker) => return lkh_rs::subprocess::run_worker(),
+        Some(Command::Solve(solve)) => {
+            let problem = RoutingProblem::from_tsplib_path(&solve.problem)?;
+            let report = solve_problem(&problem, &solve.search_parameters()?)?;
+            StdoutToStderr::restore(redirect);
+            output::render(&report, args.output_format, Some(problem.name()))
+        }
+        Some(Command::Bench(bench)) => {
+            if args.output_format == OutputFormat::Tour {
+                return Err(LkhError::InvalidSearchParameters(
+                    "bench has no single tour to write; use text, json or csv".to_owned(),
+                ));
+            }
+            let summaries = bench::run(&bench)?;
+            StdoutToStderr::restore(redirect);
+            bench::render(&summaries, args.output_format)
+        }
+        None => {
+            let par = args.par.expect("clap requires --par without a subcommand");
+            let name = Path::new(&par)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned());
+            let report = solve_parameter_file(par)?;
+            StdoutToStderr::restore(redirect);
+            output::render(&report, args.output_format, name.as_deref())
+        }
+    };
+    match args.output {
+        Some(path) => std::fs::write(&path, rendered).map_err(|source| LkhError::WriteFile {
+            path: path.clone(),
+            source,
+        })?,
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
 
-    println!("Best cost: {}", report.best_cost);
-    println!("Best penalty: {}", report.best_penalty);
-    println!("Runs: {}", report.runs);
-    println!("Dimension: {}", report.dimension);
-    println!("Tour length: {}", report.tour.len());
+/// Points the process's stdout at stderr until dropped.
+#[cfg(unix)]
+struct StdoutToStderr {
+    saved_fd: Option<libc::c_int>,
+}
 
-    Ok(())
+#[cfg(unix)]
+impl StdoutToStderr {
+    fn new() -> Self {
+        flush_stdout();
+        // SAFETY: Only duplicates and replaces the process's standard file
+        // descriptors. The original stdout is restored on drop.
+        let saved_fd = unsafe {
+            let saved_fd = libc::dup(libc::STDOUT_FILENO);
+            if saved_fd >= 0 && libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+                libc::close(saved_fd);
+                -1
+            } else {
+                saved_fd
+            }
+        };
+        Self {
+            saved_fd: (saved_fd >= 0).then_some(saved_fd),
+        }
+    }
+
+    /// Point stdout back at where it was before `redirect`, if any.
+    fn restore(redirect: Option<Self>) {
+        drop(redirect);
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StdoutToStderr {
+    fn drop(&mut self) {
+        if let Some(saved_fd) = self.saved_fd.take() {
+            flush_stdout();
+            // SAFETY: `saved_fd` was returned by `dup(STDOUT_FILENO)`.
+            unsafe {
+                libc::dup2(saved_fd, libc::STDOUT_FILENO);
+                libc::close(saved_fd);
+            }
+        }
+    }
+}
+
+/// Leaves stdout alone where file descriptors cannot be redirected.
+#[cfg(not(unix))]
+struct StdoutToStderr;
+
+#[cfg(not(unix))]
+impl StdoutToStderr {
+    fn new() -> Self {
+        Self
+    }
+
+    fn restore(_redirect: Option<Self>) {}
+}
+
+/// Flush both Rust's and C's stdout buffers.
+#[cfg(unix)]
+fn flush_stdout() {
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+    // SAFETY: `fflush(NULL)` flushes every open C output stream.
+    unsafe {
+        libc::fflush(std::ptr::null_mut());
+    }
 }
@@ -0,0 +1,89 @@
+//! Structured progress events from the in-memory search loop.
+//!
+//! With `TRACE_LEVEL >= 1`, LKH reports its progress by printing to stdout.
+//! [`crate::solve_problem_with_observer`] reports the same milestones as
+//! [`SolveEvent`] values instead, whatever the trace level.
+//!
+//! Durations are measured with LKH's `GetTime`, which counts process CPU time
+//! where `getrusage` is available.
+
+use std::time::Duration;
+
+/// A milestone reached while solving.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveEvent {
+    /// The subgradient ascent finished and produced a lower bound.
+    LowerBoundComputed {
+        /// Lower bound on the optimal tour cost.
+        lower_bound: f64,
+    },
+    /// Candidate edges have been generated and the search is about to start.
+    CandidateSetCreated {
+        /// Time spent in the ascent and candidate set generation.
+        elapsed: Duration,
+    },
+    /// A run found a tour that improved on the population.
+    MergedWithIndividual {
+        /// Run that produced the tour.
+        run: i32,
+        /// One-based index of the population individual merged with.
+        individual: i32,
+        /// Cost after merging.
+        cost: i64,
+        /// Penalty after merging.
+        penalty: i64,
+    },
+    /// The genetic population gained or replaced an individual.
+    PopulationUpdated {
+        /// Run that produced the new individual.
+        run: i32,
+        /// Population size after the update.
+        size: i32,
+    },
+    /// A run produced a better tour than any earlier run.
+    NewBest {
+        /// Run that produced the tour.
+        run: i32,
+        /// Cost of the new best tour.
+        cost: i64,
+        /// Penalty of the new best tour.
+        penalty: i64,
+    },
+    /// A run completed.
+    RunFinished {
+        /// One-based run number.
+        run: i32,
+        /// Cost of the run's tour.
+        cost: i64,
+        /// Penalty of the run's tour.
+        penalty: i64,
+        /// Time spent in the run.
+        elapsed: Duration,
+    },
+}
+
+/// Receives [`SolveEvent`]s while a solve is running.
+///
+/// Events are delivered on the solving thread, between calls into LKH, so a
+/// slow observer slows the search down. Closures taking `&SolveEvent`
+/// implement this trait.
+pub trait SolveObserver {
+    /// Handle one event.
+    fn on_event(&mut self, event: &SolveEvent);
+}
+
+impl<F> SolveObserver for F
+where
+    F: FnMut(&SolveEvent),
+{
+    fn on_event(&mut self, event: &SolveEvent) {
+        self(event)
+    }
+}
+
+/// Observer used when the caller did not ask for events.
+pub(crate) struct IgnoreEvents;
+
+impl SolveObserver for IgnoreEvents {
+    fn on_event(&mut self, _event: &SolveEvent) {}
+}
@@ -0,0 +1,95 @@
+//! Rendering of solve reports for the `lkh` binary.
+
+use clap::ValueEnum;
+use lkh_rs::{MtspSolution, Route, SolveReport};
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// Format of the report written by the CLI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// A short human-readable summary.
+    Text,
+    /// The full report, tour and routes as a JSON object.
+    Json,
+    /// The best tour as a TSPLIB `.tour` file.
+    Tour,
+    /// One `route,position,node` row per visited node.
+    Csv,
+}
+
+/// Render `report` in `format`. `name` is the problem name, when known.
+pub(crate) fn render(report: &SolveReport, format: OutputFormat, name: Option<&str>) -> String {
+    match format {
+        OutputFormat::Text => render_text(report),
+        OutputFormat::Json => render_json(report),
+        OutputFormat::Tour => render_tour(report, name),
+        OutputFormat::Csv => render_csv(report),
+    }
+}
+
+fn render_text(report: &SolveReport) -> String {
+    let mut output = String::new();
+    writeln!(&mut output, "Best cost: {}", report.best_cost).unwrap();
+    writeln!(&mut output, "Best penalty: {}", report.best_penalty).unwrap();
+    writeln!(&mut output, "Runs: {}", report.runs).unwrap();
+    writeln!(&mut output, "Dimension: {}", report.dimension).unwrap();
+    writeln!(&mut output, "Tour length: {}", report.tour.len()).unwrap();
+    output
+}
+
+/// The JSON report: the serde form of [`SolveReport`], with
+/// [`SolveReport::mtsp_solution`] alongside it.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    #[serde(flatten)]
+    report: &'a SolveReport,
+    mtsp_solution: Option<MtspSolution>,
+}
+
+fn render_json(report: &SolveReport) -> String {
+    let mut output = serde_json::to_string(&JsonReport {
+        report,
+        mtsp_solution: report.mtsp_solution(),
+    })
+    .expect("solve reports serialize to JSON");
+    output.push('\n');
+    output
+}
+
+fn render_tour(report: &SolveReport, name: Option<&str>) -> String {
+    let mut output = String::new();
+    if let Some(name) = name {
+        writeln!(&mut output, "NAME : {name}.tour").unwrap();
+    }
+    writeln!(&mut output, "COMMENT : Length = {}", report.best_cost).unwrap();
+    writeln!(&mut output, "TYPE : TOUR").unwrap();
+    writeln!(&mut output, "DIMENSION : {}", report.tour.len()).unwrap();
+    writeln!(&mut output, "TOUR_SECTION").unwrap();
+    for node in &report.tour {
+        writeln!(&mut output, "{node}").unwrap();
+    }
+    output.push_str("-1\nEOF\n");
+    output
+}
+
+/// Multi-vehicle solutions get one route per vehicle. Other solutions are a
+/// single route holding the whole tour.
+fn render_csv(report: &SolveReport) -> String {
+    let mut output = String::from("route,position,node\n");
+    let routes = report.routes();
+    let routes: Vec<(usize, &[i32])> = if routes.is_empty() {
+        vec![(1, report.tour.as_slice())]
+    } else {
+        routes
+            .iter()
+            .map(|Route { vehicle, nodes, .. }| (*vehicle, nodes.as_slice()))
+            .collect()
+    };
+    for (route, nodes) in routes {
+        for (position, node) in nodes.iter().enumerate() {
+            writeln!(&mut output, "{route},{},{node}", position + 1).unwrap();
+        }
+    }
+    output
+}
@@ -0,0 +1,229 @@
+//! A pool of worker processes for solving many independent problems.
+//!
+//! The in-process solver is serialized by a global lock because LKH keeps its
+//! state in C globals. `SolverPool` gets around that by keeping several
+//! [`SubprocessBackend`] workers alive and feeding them jobs from a bounded
+//! queue. Each worker has its own copy of LKH's globals, so jobs run in
+//! parallel.
+//!
+//! Workers are long-lived to avoid a process spawn per job. A worker that
+//! exceeds the job timeout is killed and replaced, and every worker is
+//! replaced after a configurable number of jobs so that memory leaked by the
+//! C core does not accumulate.
+
+use crate::error::LkhError;
+use crate::problem::{RoutingProblem, SearchParameters};
+use crate::solver::{ProgrammaticSolveOptions, SolveReport};
+use crate::subprocess::{SubprocessBackend, WorkerProcess, WorkerRequest};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Options for [`SolverPool::new`].
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    /// Number of worker processes. At least one worker is always started.
+    pub workers: usize,
+    /// Number of submitted jobs that may wait for a free worker before
+    /// [`SolverPool::submit`] blocks.
+    pub queue_capacity: usize,
+    /// Kill and replace a worker whose job runs longer than this.
+    pub job_timeout: Option<Duration>,
+    /// Replace a worker after it has served this many jobs.
+    pub max_jobs_per_worker: Option<usize>,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        let workers = thread::available_parallelism().map_or(1, usize::from);
+        Self {
+            workers,
+            queue_capacity: 2 * workers,
+            job_timeout: None,
+            max_jobs_per_worker: None,
+        }
+    }
+}
+
+/// A fixed-size pool of LKH worker processes.
+///
+/// Dropping the pool waits for queued jobs to finish and then shuts the
+/// workers down.
+pub struct SolverPool {
+    jobs: Option<SyncSender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+struct Job {
+    request: WorkerRequest,
+    reply: Sender<Result<SolveReport, LkhError>>,
+}
+
+/// The eventual result of a job submitted to a [`SolverPool`].
+pub struct PendingSolve {
+    reply: Receiver<Result<SolveReport, LkhError>>,
+}
+
+impl PendingSolve {
+    /// Block until the job has finished.
+    pub fn wait(self) -> Result<SolveReport, LkhError> {
+        self.reply.recv().unwrap_or(Err(LkhError::PoolClosed))
+    }
+
+    /// Return the result if the job has already finished.
+    pub fn try_wait(&self) -> Option<Result<SolveReport, LkhError>> {
+        match self.reply.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(LkhError::PoolClosed)),
+        }
+    }
+}
+
+impl SolverPool {
+    /// Start `options.workers` worker processes using `backend`'s command.
+    ///
+    /// All workers are spawned before this returns, so a missing or broken
+    /// worker binary is reported here rather than on the first job.
+    pub fn new(backend: SubprocessBackend, options: PoolOptions) -> Result<Self, LkhError> {
+        let processes = (0..options.workers.max(1))
+            .map(|_| WorkerProcess::spawn(&backend))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (jobs, queue) = mpsc::sync_channel(options.queue_capacity);
+        let queue = Arc::new(Mutex::new(queue));
+        let threads = processes
+            .into_iter()
+            .map(|process| {
+                let slot = WorkerSlot {
+                    backend: backend.clone(),
+                    job_timeout: options.job_timeout,
+                    max_jobs: options.max_jobs_per_worker,
+                    process: Some(process),
+                    jobs_served: 0,
+                };
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || slot.serve(&queue))
+            })
+            .collect();
+        Ok(Self {
+            jobs: Some(jobs),
+            threads,
+        })
+    }
+
+    /// Number of worker processes in the pool.
+    pub fn workers(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Queue a programmatic problem.
+    ///
+    /// The problem is rendered on the calling thread. This blocks while the
+    /// queue is full.
+    pub fn submit(
+        &self,
+        problem: &RoutingProblem,
+        parameters: &SearchParameters,
+    ) -> Result<PendingSolve, LkhError> {
+        self.submit_with_options(problem, parameters, ProgrammaticSolveOptions::default())
+    }
+
+    /// Queue a programmatic problem with native options.
+    pub fn submit_with_options(
+        &self,
+        problem: &RoutingProblem,
+        parameters: &SearchParameters,
+        options: ProgrammaticSolveOptions,
+    ) -> Result<PendingSolve, LkhError> {
+        let request = WorkerRequest::problem(problem, parameters, options)?;
+        let (reply, pending) = mpsc::channel();
+        self.jobs
+            .as_ref()
+            .ok_or(LkhError::PoolClosed)?
+            .send(Job { request, reply })
+            .map_err(|_| LkhError::PoolClosed)?;
+        Ok(PendingSolve { reply: pending })
+    }
+
+    /// Solve every problem with the same parameters and return the results in
+    /// input order.
+    pub fn solve_all<'a>(
+        &self,
+        problems: impl IntoIterator<Item = &'a RoutingProblem>,
+        parameters: &SearchParameters,
+    ) -> Vec<Result<SolveReport, LkhError>> {
+        let pending = problems
+            .into_iter()
+            .map(|problem| self.submit(problem, parameters))
+            .collect::<Vec<_>>();
+        pending
+            .into_iter()
+            .map(|pending| pending.and_then(PendingSolve::wait))
+            .collect()
+    }
+}
+
+impl Drop for SolverPool {
+    fn drop(&mut self) {
+        // Closing the queue lets each worker thread finish its remaining jobs
+        // and exit.
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct WorkerSlot {
+    backend: SubprocessBackend,
+    job_timeout: Option<Duration>,
+    max_jobs: Option<usize>,
+    process: Option<WorkerProcess>,
+    jobs_served: usize,
+}
+
+impl WorkerSlot {
+    fn serve(mut self, queue: &Mutex<Receiver<Job>>) {
+        loop {
+            let job = match queue.lock() {
+                Ok(queue) => queue.recv(),
+                Err(_) => return,
+            };
+            let Ok(job) = job else {
+                break;
+            };
+            let result = self.run(&job.request);
+            let _ = job.reply.send(result);
+        }
+        if let Some(process) = self.process.take() {
+            process.shutdown();
+        }
+    }
+
+    fn run(&mut self, request: &WorkerRequest) -> Result<SolveReport, LkhError> {
+        let mut process = match self.process.take() {
+            Some(process) => process,
+            None => self.respawn()?,
+        };
+        let result = process.solve(request, self.job_timeout);
+        self.jobs_served += 1;
+
+        let recycle = self.max_jobs.is_some_and(|max| self.jobs_served >= max);
+        if !process.is_alive() || recycle {
+            process.shutdown();
+            // Replace the worker right away so the next job finds it warm. A
+            // failed spawn is retried, and reported, by the next job.
+            self.process = self.respawn().ok();
+        } else {
+            self.process = Some(process);
+        }
+        result
+    }
+
+    fn respawn(&mut self) -> Result<WorkerProcess, LkhError> {
+        self.jobs_served = 0;
+        WorkerProcess::spawn(&self.backend)
+    }
+}
@@ -6,13 +6,31 @@
 //! implementation detail of the native solver bridge.
 
 use crate::error::LkhError;
-use std::fmt::Write as _;
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
 
 const DEFAULT_PROBLEM_NAME: &str = "lkh_rs_problem";
 
+/// What LKH charges for an edge missing from an `EDGE_DATA_SECTION` graph
+/// (`Distance_LARGE`).
+const SPARSE_MISSING_EDGE_COST: i64 = 10_000_000;
+
+/// Sections whose LKH readers create the nodes when none exist yet.
+const NODE_CREATING_SECTIONS: [&str; 7] = [
+    "DISPLAY_DATA_SECTION",
+    "EDGE_DATA_SECTION",
+    "EDGE_WEIGHT_SECTION",
+    "FIXED_EDGES_SECTION",
+    "NODE_COORD_SECTION",
+    "REQUIRED_NODES_SECTION",
+    "TOUR_SECTION",
+];
+
 /// A 2-D point used by coordinate-based TSP problems.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point2d {
     /// X coordinate passed to LKH.
     pub x: f64,
@@ -39,6 +57,384 @@ impl From<[f64; 2]> for Point2d {
     }
 }
 
+/// A 3-D point used by the `EUC_3D` metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point3d {
+    /// X coordinate passed to LKH.
+    pub x: f64,
+    /// Y coordinate passed to LKH.
+    pub y: f64,
+    /// Z coordinate passed to LKH.
+    pub z: f64,
+}
+
+impl Point3d {
+    /// Create a 3-D point.
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<(f64, f64, f64)> for Point3d {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl From<[f64; 3]> for Point3d {
+    fn from([x, y, z]: [f64; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+/// A position on the earth in decimal degrees, used by the `GEO` and `GEOM`
+/// metrics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeoPoint {
+    /// Latitude in degrees, between -90 and 90.
+    pub lat: f64,
+    /// Longitude in degrees, between -180 and 180.
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Create a point from latitude and longitude in decimal degrees.
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+}
+
+/// Coordinate metrics (`EDGE_WEIGHT_TYPE`) for [`RoutingProblem::coordinates`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeWeightType {
+    /// Euclidean distance in 2-D, rounded to the nearest integer.
+    Euc2d,
+    /// Euclidean distance in 3-D, rounded to the nearest integer.
+    Euc3d,
+    /// Manhattan distance in 2-D.
+    Man2d,
+    /// Maximum of the coordinate differences in 2-D.
+    Max2d,
+    /// Euclidean distance in 2-D, rounded up.
+    Ceil2d,
+    /// The pseudo-Euclidean distance of TSPLIB's `att48` and `att532`.
+    Att,
+    /// TSPLIB's geographical distance in kilometers.
+    Geo,
+    /// Geographical distance in meters, as used for the World TSP.
+    Geom,
+}
+
+impl EdgeWeightType {
+    /// The `EDGE_WEIGHT_TYPE` value LKH expects.
+    pub fn as_tsplib_type(self) -> &'static str {
+        match self {
+            Self::Euc2d => "EUC_2D",
+            Self::Euc3d => "EUC_3D",
+            Self::Man2d => "MAN_2D",
+            Self::Max2d => "MAX_2D",
+            Self::Ceil2d => "CEIL_2D",
+            Self::Att => "ATT",
+            Self::Geo => "GEO",
+            Self::Geom => "GEOM",
+        }
+    }
+}
+
+impl fmt::Display for EdgeWeightType {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_tsplib_type())
+    }
+}
+
+/// Layouts of an explicit weight list (`EDGE_WEIGHT_FORMAT`) for
+/// [`RoutingProblem::explicit_weights`].
+///
+/// The triangular formats list each symmetric distance once. `Row` formats
+/// run through the matrix row by row and `Col` formats column by column;
+/// `Diag` formats include the zero diagonal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeWeightFormat {
+    /// All `n * n` entries, row by row.
+    FullMatrix,
+    /// Entries above the diagonal, row by row.
+    UpperRow,
+    /// Entries below the diagonal, row by row.
+    LowerRow,
+    /// Entries on and above the diagonal, row by row.
+    UpperDiagRow,
+    /// Entries on and below the diagonal, row by row.
+    LowerDiagRow,
+    /// Entries above the diagonal, column by column.
+    UpperCol,
+    /// Entries below the diagonal, column by column.
+    LowerCol,
+    /// Entries on and above the diagonal, column by column.
+    UpperDiagCol,
+    /// Entries on and below the diagonal, column by column.
+    LowerDiagCol,
+}
+
+impl EdgeWeightFormat {
+    /// The `EDGE_WEIGHT_FORMAT` value LKH expects.
+    pub fn as_tsplib_format(self) -> &'static str {
+        match self {
+            Self::FullMatrix => "FULL_MATRIX",
+            Self::UpperRow => "UPPER_ROW",
+            Self::LowerRow => "LOWER_ROW",
+            Self::UpperDiagRow => "UPPER_DIAG_ROW",
+            Self::LowerDiagRow => "LOWER_DIAG_ROW",
+            Self::UpperCol => "UPPER_COL",
+            Self::LowerCol => "LOWER_COL",
+            Self::UpperDiagCol => "UPPER_DIAG_COL",
+            Self::LowerDiagCol => "LOWER_DIAG_COL",
+        }
+    }
+
+    /// Number of weights this format lists for `dimension` nodes.
+    pub fn value_count(self, dimension: usize) -> usize {
+        self.line_lengths(dimension).sum()
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [
+            Self::FullMatrix,
+            Self::UpperRow,
+            Self::LowerRow,
+            Self::UpperDiagRow,
+            Self::LowerDiagRow,
+            Self::UpperCol,
+            Self::LowerCol,
+            Self::UpperDiagCol,
+            Self::LowerDiagCol,
+        ]
+        .into_iter()
+        .find(|format| value.eq_ignore_ascii_case(format.as_tsplib_format()))
+    }
+
+    /// Length of each matrix row (or column) the format lists, in order.
+    fn line_lengths(self, dimension: usize) -> impl Iterator<Item = usize> {
+        (0..dimension).filter_map(move |line| {
+            let length = match self {
+                Self::FullMatrix => dimension,
+                Self::UpperRow | Self::LowerCol => dimension - line - 1,
+                Self::LowerRow | Self::UpperCol => line,
+                Self::UpperDiagRow | Self::LowerDiagCol => dimension - line,
+                Self::LowerDiagRow | Self::UpperDiagCol => line + 1,
+            };
+            (length > 0).then_some(length)
+        })
+    }
+
+    /// Position of the diagonal entry within a listed line, if the format
+    /// lists the diagonal.
+    fn diagonal_offset(self, length: usize, line: usize) -> Option<usize> {
+        match self {
+            Self::FullMatrix => Some(line),
+            Self::UpperDiagRow | Self::LowerDiagCol => Some(0),
+            Self::LowerDiagRow | Self::UpperDiagCol => Some(length - 1),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EdgeWeightFormat {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_tsplib_format())
+    }
+}
+
+/// How [`RoutingProblem::float_distance_matrix`] turns distances into the
+/// integers LKH works with.
+///
+/// The distances are written unchanged together with a `SCALE` keyword, and
+/// LKH rounds `SCALE * distance` to an integer.
+/// [`SolveReport::best_cost_unscaled`](crate::SolveReport::best_cost_unscaled)
+/// divides the result back.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scaling {
+    /// The largest power of ten that keeps every scaled distance within
+    /// LKH's integer range at the default `PRECISION` of 100.
+    #[default]
+    Auto,
+    /// A fixed `SCALE`. LKH reads it as an integer, so it must be a whole
+    /// number of at least 1.
+    Factor(f64),
+}
+
+/// Node coordinates for [`RoutingProblem::coordinates`].
+///
+/// The variant has to fit the metric: [`EdgeWeightType::Euc3d`] takes
+/// [`Point3d`]s, [`EdgeWeightType::Geo`] and [`EdgeWeightType::Geom`] take
+/// [`GeoPoint`]s, and the other metrics take [`Point2d`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coordinates {
+    /// Planar coordinates.
+    Planar(Vec<Point2d>),
+    /// Spatial coordinates.
+    Spatial(Vec<Point3d>),
+    /// Latitudes and longitudes.
+    Geographic(Vec<GeoPoint>),
+}
+
+impl Coordinates {
+    /// Number of nodes described.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Planar(points) => points.len(),
+            Self::Spatial(points) => points.len(),
+            Self::Geographic(points) => points.len(),
+        }
+    }
+
+    /// Whether there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Vec<Point2d>> for Coordinates {
+    fn from(points: Vec<Point2d>) -> Self {
+        Self::Planar(points)
+    }
+}
+
+impl From<Vec<Point3d>> for Coordinates {
+    fn from(points: Vec<Point3d>) -> Self {
+        Self::Spatial(points)
+    }
+}
+
+impl From<Vec<GeoPoint>> for Coordinates {
+    fn from(points: Vec<GeoPoint>) -> Self {
+        Self::Geographic(points)
+    }
+}
+
+/// Node geometry for the typed problem builders.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distances {
+    /// 2-D coordinates measured with LKH's `EUC_2D` metric.
+    Euclidean2d(Vec<Point2d>),
+    /// A full symmetric distance matrix.
+    Matrix(Vec<Vec<i64>>),
+}
+
+impl Distances {
+    /// Number of nodes described.
+    pub fn dimension(&self) -> usize {
+        match self {
+            Self::Euclidean2d(points) => points.len(),
+            Self::Matrix(matrix) => matrix.len(),
+        }
+    }
+}
+
+/// Input for [`RoutingProblem::cvrp`].
+///
+/// Node ids are one-based in LKH, so `demands[0]` is the demand of node 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvrpSpec {
+    /// Locations or distances of all nodes, depot included.
+    pub distances: Distances,
+    /// Demand of every node. The depot's demand must be zero.
+    pub demands: Vec<i64>,
+    /// Capacity of each vehicle.
+    pub capacity: i64,
+    /// One-based node id of the depot.
+    pub depot: usize,
+    /// Fleet size. `None` lets LKH use the fewest vehicles whose combined
+    /// capacity covers the total demand.
+    pub vehicles: Option<usize>,
+}
+
+/// The interval in which service at a node may start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeWindow {
+    /// Earliest service start. A vehicle arriving earlier waits.
+    pub earliest: f64,
+    /// Latest service start.
+    pub latest: f64,
+}
+
+impl TimeWindow {
+    /// Create a time window.
+    pub fn new(earliest: f64, latest: f64) -> Self {
+        Self { earliest, latest }
+    }
+}
+
+impl From<(f64, f64)> for TimeWindow {
+    fn from((earliest, latest): (f64, f64)) -> Self {
+        Self::new(earliest, latest)
+    }
+}
+
+/// A load picked up at one node and delivered to another by the same
+/// vehicle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickupDelivery {
+    /// One-based node id of the pickup.
+    pub pickup: usize,
+    /// One-based node id of the delivery.
+    pub delivery: usize,
+    /// Load carried from the pickup to the delivery.
+    pub quantity: i64,
+}
+
+/// Input for [`RoutingProblem::tsptw`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsptwSpec {
+    /// Locations or distances of all nodes, depot included.
+    pub distances: Distances,
+    /// Time window of every node. The depot's window is the planning horizon
+    /// and must contain every other window.
+    pub time_windows: Vec<TimeWindow>,
+    /// Service time of every node, or empty for no service times.
+    pub service_times: Vec<f64>,
+    /// One-based node id of the depot.
+    pub depot: usize,
+}
+
+/// Input for [`RoutingProblem::cvrptw`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvrptwSpec {
+    /// Distances, demands, capacity, depot, and fleet size.
+    pub cvrp: CvrpSpec,
+    /// Time window of every node. The depot's window is the planning horizon
+    /// and must contain every other window.
+    pub time_windows: Vec<TimeWindow>,
+    /// Service time of every node, or empty for no service times.
+    pub service_times: Vec<f64>,
+}
+
+/// Input for [`RoutingProblem::pdptw`].
+///
+/// Nodes that are neither a pickup nor a delivery have no demand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdptwSpec {
+    /// Locations or distances of all nodes, depot included.
+    pub distances: Distances,
+    /// Pickup and delivery pairs. A node belongs to at most one pair.
+    pub requests: Vec<PickupDelivery>,
+    /// Time window of every node. The depot's window is the planning horizon
+    /// and must contain every other window.
+    pub time_windows: Vec<TimeWindow>,
+    /// Service time of every node, or empty for no service times.
+    pub service_times: Vec<f64>,
+    /// Capacity of each vehicle.
+    pub capacity: i64,
+    /// One-based node id of the depot.
+    pub depot: usize,
+    /// Fleet size. `None` leaves LKH's default of one vehicle.
+    pub vehicles: Option<usize>,
+}
+
 /// The TSPLIB/LKH problem class represented by a programmatic problem.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProblemKind {
@@ -235,12 +631,29 @@ impl ProblemKind {
     }
 }
 
+/// Problem kinds serialize as their TSPLIB `TYPE` spelling.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ProblemKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_tsplib_type())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ProblemKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        parse_problem_kind(&value).map_err(serde::de::Error::custom)
+    }
+}
+
 /// One ordered entry in the generated TSPLIB/LKH problem file.
 ///
 /// LKH problem files are order-sensitive for some variants because sections
 /// must appear after their defining scalar keywords. `RoutingProblem` preserves
 /// insertion order by storing entries as this flat list.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProblemEntry {
     /// A scalar `KEY: VALUE` problem specification.
     Keyword { key: String, value: String },
@@ -254,6 +667,11 @@ pub enum ProblemEntry {
 /// feeds LKH's parser from memory, and TSPLIB text is only an export format for
 /// interoperability.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self")
+)]
 pub struct RoutingProblem {
     name: String,
     kind: ProblemKind,
@@ -307,16 +725,33 @@ impl RoutingProblem {
         I: IntoIterator<Item = P>,
         P: Into<Point2d>,
     {
+        let points = points.into_iter().map(Into::into).collect::<Vec<Point2d>>();
+        Self::named_coordinates(name, EdgeWeightType::Euc2d, points)
+    }
+
+    /// Build a symmetric TSP from node coordinates and the metric LKH should
+    /// measure them with.
+    ///
+    /// [`GeoPoint`]s are written in the form each metric reads: `GEO` takes
+    /// TSPLIB's `DDD.MM` degrees and minutes, `GEOM` decimal degrees.
+    pub fn coordinates(
+        metric: EdgeWeightType,
+        points: impl Into<Coordinates>,
+    ) -> Result<Self, LkhError> {
+        Self::named_coordinates(DEFAULT_PROBLEM_NAME, metric, points)
+    }
+
+    /// Build a named symmetric TSP from node coordinates and a metric.
+    pub fn named_coordinates(
+        name: impl Into<String>,
+        metric: EdgeWeightType,
+        points: impl Into<Coordinates>,
+    ) -> Result<Self, LkhError> {
         let name = validate_name(name.into())?;
-        let points = points.into_iter().map(Into::into).collect::<Vec<_>>();
-        validate_points(&points)?;
-        let lines = points
-            .iter()
-            .enumerate()
-            .map(|(index, point)| format!("{} {} {}", index + 1, point.x, point.y))
-            .collect::<Vec<_>>();
+        let points = points.into();
+        let lines = coordinate_lines(metric, &points)?;
         Self::named(name, ProblemKind::Tsp, points.len())?
-            .with_keyword("EDGE_WEIGHT_TYPE", "EUC_2D")?
+            .with_keyword("EDGE_WEIGHT_TYPE", metric.as_tsplib_type())?
             .with_section("NODE_COORD_SECTION", lines)
     }
 
@@ -353,15 +788,6 @@ impl RoutingProblem {
     ) -> Result<Self, LkhError> {
         let name = validate_name(name.into())?;
         validate_matrix(&matrix, asymmetric)?;
-        let lines = matrix
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            })
-            .collect::<Vec<_>>();
         let kind = if asymmetric {
             ProblemKind::Atsp
         } else {
@@ -370,336 +796,3058 @@ impl RoutingProblem {
         Self::named(name, kind, matrix.len())?
             .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")?
             .with_keyword("EDGE_WEIGHT_FORMAT", "FULL_MATRIX")?
-            .with_section("EDGE_WEIGHT_SECTION", lines)
-    }
-
-    /// Return the problem name rendered into the TSPLIB header.
-    pub fn name(&self) -> &str {
-        &self.name
-    }
-
-    /// Return the declared node dimension.
-    pub fn dimension(&self) -> usize {
-        self.dimension
+            .with_section("EDGE_WEIGHT_SECTION", matrix_lines(&matrix))
     }
 
-    /// Return a cloned problem kind.
-    pub fn kind(&self) -> ProblemKind {
-        self.kind.clone()
+    /// Build a symmetric TSP from a flat list of weights in the given
+    /// layout.
+    ///
+    /// The triangular formats need about half the memory and text of a full
+    /// matrix: [`EdgeWeightFormat::UpperRow`] lists the `n * (n - 1) / 2`
+    /// distances above the diagonal, starting with `d(1, 2), d(1, 3), ...`.
+    /// A [`EdgeWeightFormat::FullMatrix`] list must be symmetric.
+    pub fn explicit_weights(
+        format: EdgeWeightFormat,
+        dimension: usize,
+        weights: &[i64],
+    ) -> Result<Self, LkhError> {
+        Self::named_explicit_weights(DEFAULT_PROBLEM_NAME, format, dimension, weights)
     }
 
-    /// Borrow the problem kind without cloning.
-    pub fn kind_ref(&self) -> &ProblemKind {
-        &self.kind
+    /// Build a named symmetric TSP from a flat list of weights.
+    pub fn named_explicit_weights(
+        name: impl Into<String>,
+        format: EdgeWeightFormat,
+        dimension: usize,
+        weights: &[i64],
+    ) -> Result<Self, LkhError> {
+        let name = validate_name(name.into())?;
+        let lines = explicit_weight_lines(format, dimension, weights)?;
+        Self::named(name, ProblemKind::Tsp, dimension)?
+            .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")?
+            .with_keyword("EDGE_WEIGHT_FORMAT", format.as_tsplib_format())?
+            .with_section("EDGE_WEIGHT_SECTION", lines)
     }
 
-    /// Return the ordered problem entries that will be rendered after the
-    /// standard `NAME`, `TYPE`, `COMMENT`, and `DIMENSION` header.
-    pub fn entries(&self) -> &[ProblemEntry] {
-        &self.entries
+    /// Build a symmetric TSP on a sparse graph from `(i, j, weight)` edges
+    /// with one-based node ids (`EDGE_DATA_FORMAT: EDGE_LIST`).
+    ///
+    /// LKH only considers the listed edges as candidates and charges
+    /// 10,000,000 for any other edge, so weights must stay below that.
+    pub fn sparse_edges<I>(dimension: usize, edges: I) -> Result<Self, LkhError>
+    where
+        I: IntoIterator<Item = (usize, usize, i64)>,
+    {
+        Self::named_sparse_edges(DEFAULT_PROBLEM_NAME, dimension, edges)
     }
 
-    /// Add or override a scalar TSPLIB/LKH problem keyword.
-    pub fn with_keyword(
-        mut self,
-        key: impl Into<String>,
-        value: impl Into<String>,
-    ) -> Result<Self, LkhError> {
-        let key = validate_problem_key(key.into())?;
-        let value = validate_problem_value(value.into())?;
-        self.entries.push(ProblemEntry::Keyword { key, value });
-        Ok(self)
+    /// Build a named symmetric TSP on a sparse graph.
+    pub fn named_sparse_edges<I>(
+        name: impl Into<String>,
+        dimension: usize,
+        edges: I,
+    ) -> Result<Self, LkhError>
+    where
+        I: IntoIterator<Item = (usize, usize, i64)>,
+    {
+        let name = validate_name(name.into())?;
+        validate_dimension(dimension)?;
+        let mut seen = HashSet::new();
+        let mut lines = Vec::new();
+        for (i, j, weight) in edges {
+            validate_edge(dimension, i, j, &mut seen)?;
+            if !(0..SPARSE_MISSING_EDGE_COST).contains(&weight) {
+                return Err(LkhError::InvalidProblem(format!(
+                    "edge ({i}, {j}) needs a weight between 0 and {}",
+                    SPARSE_MISSING_EDGE_COST - 1
+                )));
+            }
+            lines.push(format!("{i} {j} {weight}"));
+        }
+        if lines.is_empty() {
+            return Err(LkhError::InvalidProblem(
+                "a sparse graph needs at least one edge".to_owned(),
+            ));
+        }
+        lines.push("-1".to_owned());
+        Self::named(name, ProblemKind::Tsp, dimension)?
+            .with_keyword("EDGE_WEIGHT_TYPE", "SPECIAL")?
+            .with_keyword("EDGE_DATA_FORMAT", "EDGE_LIST")?
+            .with_section("EDGE_DATA_SECTION", lines)
     }
 
-    /// Add a raw TSPLIB/LKH data section.
-    pub fn with_section<I, S>(mut self, key: impl Into<String>, lines: I) -> Result<Self, LkhError>
+    /// Build a Hamiltonian cycle problem (HCP) on the graph of the given
+    /// undirected `(i, j)` edges, written as adjacency lists
+    /// (`EDGE_DATA_FORMAT: ADJ_LIST`).
+    ///
+    /// A solution of cost 0 is a Hamiltonian cycle; every other unit of cost
+    /// is an edge outside the graph.
+    pub fn hamiltonian_cycle<I>(dimension: usize, edges: I) -> Result<Self, LkhError>
     where
-        I: IntoIterator<Item = S>,
-        S: Into<String>,
+        I: IntoIterator<Item = (usize, usize)>,
     {
-        let key = validate_problem_key(key.into())?;
-        let lines = lines
-            .into_iter()
-            .map(|line| validate_section_line(line.into()))
-            .collect::<Result<Vec<_>, _>>()?;
-        self.entries.push(ProblemEntry::Section { key, lines });
-        Ok(self)
+        Self::named_hamiltonian_cycle(DEFAULT_PROBLEM_NAME, dimension, edges)
     }
 
-    /// Render this problem as TSPLIB text in memory.
-    pub fn to_tsplib(&self) -> String {
-        let mut output = String::new();
-        writeln!(&mut output, "NAME: {}", self.name).unwrap();
-        writeln!(&mut output, "TYPE: {}", self.kind.as_tsplib_type()).unwrap();
-        writeln!(&mut output, "COMMENT: generated by LKH-rs programmatic API").unwrap();
-        writeln!(&mut output, "DIMENSION: {}", self.dimension).unwrap();
-        for entry in &self.entries {
-            match entry {
-                ProblemEntry::Keyword { key, value } => {
-                    writeln!(&mut output, "{key}: {value}").unwrap();
-                }
-                ProblemEntry::Section { key, lines } => {
-                    writeln!(&mut output, "{key}").unwrap();
-                    for line in lines {
-                        writeln!(&mut output, "{line}").unwrap();
-                    }
+    /// Build a named Hamiltonian cycle problem.
+    pub fn named_hamiltonian_cycle<I>(
+        name: impl Into<String>,
+        dimension: usize,
+        edges: I,
+    ) -> Result<Self, LkhError>
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let name = validate_name(name.into())?;
+        validate_dimension(dimension)?;
+        let mut seen = HashSet::new();
+        let mut adjacent = vec![Vec::new(); dimension + 1];
+        for (i, j) in edges {
+            validate_edge(dimension, i, j, &mut seen)?;
+            adjacent[i.min(j)].push(i.max(j));
+        }
+        let mut lines: Vec<String> = adjacent
+            .iter()
+            .enumerate()
+            .filter(|(_, neighbors)| !neighbors.is_empty())
+            .map(|(node, neighbors)| {
+                let mut line = node.to_string();
+                for neighbor in neighbors {
+                    write!(line, " {neighbor}").unwrap();
                 }
-            }
+                line.push_str(" -1");
+                line
+            })
+            .collect();
+        if lines.is_empty() {
+            return Err(LkhError::InvalidProblem(
+                "a sparse graph needs at least one edge".to_owned(),
+            ));
         }
-        writeln!(&mut output, "EOF").unwrap();
-        output
+        lines.push("-1".to_owned());
+        Self::named(name, ProblemKind::Hcp, dimension)?
+            .with_keyword("EDGE_DATA_FORMAT", "ADJ_LIST")?
+            .with_section("EDGE_DATA_SECTION", lines)
     }
 
-    /// Write this problem as a TSPLIB file.
+    /// Build a TSP on `dimension` nodes whose distances are computed by a
+    /// Rust function, for [`solve_problem_with_distance`].
     ///
-    /// This is an explicit export adapter. It is not used by
-    /// `solve_problem`, which initializes LKH directly from memory.
-    pub fn write_tsplib(&self, path: impl AsRef<Path>) -> Result<(), LkhError> {
-        write_text(path.as_ref(), self.to_tsplib())
+    /// Only the header is passed to LKH's parser (`EDGE_WEIGHT_TYPE:
+    /// SPECIAL`, and an empty `FIXED_EDGES_SECTION` so that LKH creates the
+    /// nodes); the distances are never rendered. Other problem kinds can use
+    /// a distance function too by declaring the same keyword. The other
+    /// solve functions reject the problem, since it has no distances of its
+    /// own.
+    ///
+    /// [`solve_problem_with_distance`]: crate::solve_problem_with_distance
+    pub fn distance_function(dimension: usize) -> Result<Self, LkhError> {
+        Self::named_distance_function(DEFAULT_PROBLEM_NAME, dimension)
     }
-}
 
-/// Search settings for programmatic solves.
-///
-/// The defaults are intentionally lightweight and quiet (`RUNS = 1`,
-/// `TRACE_LEVEL = 0`). Heavier benchmark-style runs can be requested by setting
-/// the corresponding fields.
-#[derive(Debug, Clone, PartialEq)]
-pub struct SearchParameters {
-    /// Number of independent LKH runs.
-    pub runs: i32,
-    /// Native LKH trace level; zero keeps programmatic solves quiet.
-    pub trace_level: i32,
-    /// Maximum trials per run. `None` lets LKH choose its default.
-    pub max_trials: Option<i32>,
-    /// LKH move type. `None` keeps the upstream default.
-    pub move_type: Option<i32>,
-    /// Patching C parameter. `None` keeps the upstream default.
-    pub patching_c: Option<i32>,
-    /// Patching A parameter. `None` keeps the upstream default.
-    pub patching_a: Option<i32>,
-    /// Random seed used by LKH.
-    pub seed: Option<u32>,
-    /// Per-run time limit in seconds.
-    pub time_limit: Option<f64>,
-    /// Total time limit in seconds across all runs.
-    pub total_time_limit: Option<f64>,
-    /// Known optimum used by LKH for reporting and optional early stopping.
-    pub optimum: Option<i64>,
-    /// Whether LKH should stop when the optimum is reached.
-    pub stop_at_optimum: Option<bool>,
-    /// Expert escape hatch for LKH parameters not yet modeled directly.
-    ///
-    /// Unknown or incompatible keywords can still terminate inside upstream C,
-    /// so prefer typed fields when available.
-    pub additional_parameters: Vec<(String, String)>,
-}
+    /// Build a named TSP whose distances are computed by a Rust function.
+    pub fn named_distance_function(
+        name: impl Into<String>,
+        dimension: usize,
+    ) -> Result<Self, LkhError> {
+        Self::named(name, ProblemKind::Tsp, dimension)?
+            .with_keyword("EDGE_WEIGHT_TYPE", "SPECIAL")?
+            .with_section("FIXED_EDGES_SECTION", ["-1"])
+    }
 
-impl Default for SearchParameters {
-    fn default() -> Self {
-        Self {
-            runs: 1,
-            trace_level: 0,
-            max_trials: None,
-            move_type: None,
-            patching_c: None,
-            patching_a: None,
-            seed: None,
-            time_limit: None,
-            total_time_limit: None,
-            optimum: None,
-            stop_at_optimum: None,
-            additional_parameters: Vec::new(),
-        }
+    /// Build a symmetric TSP from a full square matrix of fractional
+    /// distances, such as kilometers.
+    ///
+    /// The matrix is written as `UPPER_ROW` with a `SCALE` chosen by
+    /// `scaling`. With [`Scaling::Auto`], a matrix of kilometers up to 10,000
+    /// is solved to the meter. Solving with a `PRECISION` above 100 can push
+    /// the scaled distances out of range, which the solve reports as
+    /// [`LkhError::InvalidProblem`]; pick a smaller [`Scaling::Factor`] then.
+    pub fn float_distance_matrix(
+        matrix: Vec<Vec<f64>>,
+        scaling: Scaling,
+    ) -> Result<Self, LkhError> {
+        Self::named_float_distance_matrix(DEFAULT_PROBLEM_NAME, matrix, scaling)
     }
-}
 
-impl SearchParameters {
-    /// Create default lightweight search settings.
-    pub fn new() -> Self {
-        Self::default()
+    /// Build a named symmetric TSP from a matrix of fractional distances.
+    pub fn named_float_distance_matrix(
+        name: impl Into<String>,
+        matrix: Vec<Vec<f64>>,
+        scaling: Scaling,
+    ) -> Result<Self, LkhError> {
+        let name = validate_name(name.into())?;
+        let largest = validate_float_matrix(&matrix)?;
+        let scale = float_matrix_scale(largest, scaling)?;
+        let lines = matrix
+            .iter()
+            .enumerate()
+            .filter(|(i, row)| i + 1 < row.len())
+            .map(|(i, row)| {
+                row[i + 1..]
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>();
+        Self::named(name, ProblemKind::Tsp, matrix.len())?
+            .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")?
+            .with_keyword(
+                "EDGE_WEIGHT_FORMAT",
+                EdgeWeightFormat::UpperRow.as_tsplib_format(),
+            )?
+            .with_keyword("SCALE", scale.to_string())?
+            .with_section("EDGE_WEIGHT_SECTION", lines)
     }
 
-    /// Return a copy with a custom run count.
-    pub fn with_runs(mut self, runs: i32) -> Self {
-        self.runs = runs;
-        self
+    /// Build a capacitated vehicle routing problem.
+    ///
+    /// Demands, capacity, depot, and fleet size are checked here, before LKH
+    /// reads the problem: every node needs a demand no larger than the
+    /// capacity, and a fixed fleet must be able to carry the total demand.
+    pub fn cvrp(spec: CvrpSpec) -> Result<Self, LkhError> {
+        Self::named_cvrp(DEFAULT_PROBLEM_NAME, spec)
     }
 
-    /// Return a copy with a custom native trace level.
-    pub fn with_trace_level(mut self, trace_level: i32) -> Self {
-        self.trace_level = trace_level;
-        self
+    /// Build a named capacitated vehicle routing problem.
+    pub fn named_cvrp(name: impl Into<String>, spec: CvrpSpec) -> Result<Self, LkhError> {
+        let name = validate_name(name.into())?;
+        validate_cvrp(&spec)?;
+        Self::capacitated(name, ProblemKind::Cvrp, &spec)?
+            .with_section("DEPOT_SECTION", depot_lines(spec.depot))
     }
 
-    /// Return a copy with a maximum trial count.
-    pub fn with_max_trials(mut self, max_trials: i32) -> Self {
-        self.max_trials = Some(max_trials);
-        self
+    /// Build a traveling salesman problem with time windows.
+    ///
+    /// Every window must be ordered and lie within the depot's window, and
+    /// service times must be non-negative.
+    pub fn tsptw(spec: TsptwSpec) -> Result<Self, LkhError> {
+        Self::named_tsptw(DEFAULT_PROBLEM_NAME, spec)
     }
 
-    /// Return a copy with a custom LKH move type.
-    pub fn with_move_type(mut self, move_type: i32) -> Self {
-        self.move_type = Some(move_type);
-        self
+    /// Build a named traveling salesman problem with time windows.
+    pub fn named_tsptw(name: impl Into<String>, spec: TsptwSpec) -> Result<Self, LkhError> {
+        let name = validate_name(name.into())?;
+        validate_distances(&spec.distances)?;
+        let dimension = spec.distances.dimension();
+        validate_depot(spec.depot, dimension)?;
+        validate_schedule(
+            &spec.time_windows,
+            &spec.service_times,
+            spec.depot,
+            dimension,
+        )?;
+        Self::named(name, ProblemKind::Tsptw, dimension)?
+            .with_distances(&spec.distances)?
+            .with_schedule(&spec.time_windows, &spec.service_times)?
+            .with_section("DEPOT_SECTION", depot_lines(spec.depot))
     }
 
-    /// Return a copy with a custom `PATCHING_C` value.
-    pub fn with_patching_c(mut self, patching_c: i32) -> Self {
-        self.patching_c = Some(patching_c);
-        self
+    /// Build a capacitated vehicle routing problem with time windows.
+    ///
+    /// The CVRP part is checked as by [`cvrp`](Self::cvrp) and the schedule
+    /// as by [`tsptw`](Self::tsptw).
+    pub fn cvrptw(spec: CvrptwSpec) -> Result<Self, LkhError> {
+        Self::named_cvrptw(DEFAULT_PROBLEM_NAME, spec)
     }
 
-    /// Return a copy with a custom `PATCHING_A` value.
-    pub fn with_patching_a(mut self, patching_a: i32) -> Self {
-        self.patching_a = Some(patching_a);
-        self
+    /// Build a named capacitated vehicle routing problem with time windows.
+    pub fn named_cvrptw(name: impl Into<String>, spec: CvrptwSpec) -> Result<Self, LkhError> {
+        let name = validate_name(name.into())?;
+        let cvrp = &spec.cvrp;
+        validate_cvrp(cvrp)?;
+        validate_schedule(
+            &spec.time_windows,
+            &spec.service_times,
+            cvrp.depot,
+            cvrp.distances.dimension(),
+        )?;
+        Self::capacitated(name, ProblemKind::Cvrptw, cvrp)?
+            .with_schedule(&spec.time_windows, &spec.service_times)?
+            .with_section("DEPOT_SECTION", depot_lines(cvrp.depot))
     }
 
-    /// Return a copy with a deterministic LKH seed.
-    pub fn with_seed(mut self, seed: u32) -> Self {
-        self.seed = Some(seed);
-        self
+    /// Build a pickup and delivery problem with time windows.
+    ///
+    /// Besides the schedule checks of [`tsptw`](Self::tsptw), every pair must
+    /// join two distinct customers, no node may appear in two pairs, and each
+    /// quantity must fit in a vehicle.
+    pub fn pdptw(spec: PdptwSpec) -> Result<Self, LkhError> {
+        Self::named_pdptw(DEFAULT_PROBLEM_NAME, spec)
     }
 
-    /// Return a copy with a per-run time limit in seconds.
-    pub fn with_time_limit(mut self, seconds: f64) -> Self {
-        self.time_limit = Some(seconds);
-        self
+    /// Build a named pickup and delivery problem with time windows.
+    pub fn named_pdptw(name: impl Into<String>, spec: PdptwSpec) -> Result<Self, LkhError> {
+        let name = validate_name(name.into())?;
+        validate_pdptw(&spec)?;
+        let dimension = spec.distances.dimension();
+        let mut problem = Self::named(name, ProblemKind::Pdptw, dimension)?
+            .with_keyword("CAPACITY", spec.capacity.to_string())?;
+        if let Some(vehicles) = spec.vehicles {
+            problem = problem.with_keyword("VEHICLES", vehicles.to_string())?;
+        }
+
+        // Each line is "id demand earliest latest service pickup delivery",
+        // where a delivery names its pickup and a pickup its delivery.
+        let mut links = vec![(0, 0, 0); dimension];
+        for request in &spec.requests {
+            links[request.pickup - 1] = (request.quantity, 0, request.delivery);
+            links[request.delivery - 1] = (-request.quantity, request.pickup, 0);
+        }
+        let lines = spec.time_windows.iter().enumerate().map(|(index, window)| {
+            let (demand, pickup, delivery) = links[index];
+            format!(
+                "{} {demand} {} {} {} {pickup} {delivery}",
+                index + 1,
+                window.earliest,
+                window.latest,
+                service_time(&spec.service_times, index)
+            )
+        });
+        problem
+            .with_distances(&spec.distances)?
+            .with_section("PICKUP_AND_DELIVERY_SECTION", lines)?
+            .with_section("DEPOT_SECTION", depot_lines(spec.depot))
     }
 
-    /// Return a copy with a total time limit in seconds.
-    pub fn with_total_time_limit(mut self, seconds: f64) -> Self {
-        self.total_time_limit = Some(seconds);
-        self
+    /// Start an already validated capacitated problem: header, capacity,
+    /// fleet size, distances, and demands.
+    fn capacitated(name: String, kind: ProblemKind, spec: &CvrpSpec) -> Result<Self, LkhError> {
+        let mut problem = Self::named(name, kind, spec.distances.dimension())?
+            .with_keyword("CAPACITY", spec.capacity.to_string())?;
+        if let Some(vehicles) = spec.vehicles {
+            problem = problem.with_keyword("VEHICLES", vehicles.to_string())?;
+        }
+        let demands = spec
+            .demands
+            .iter()
+            .enumerate()
+            .map(|(index, demand)| format!("{} {demand}", index + 1));
+        problem
+            .with_distances(&spec.distances)?
+            .with_section("DEMAND_SECTION", demands)
     }
 
-    /// Return a copy with a known optimum.
-    pub fn with_optimum(mut self, optimum: i64) -> Self {
-        self.optimum = Some(optimum);
-        self
+    /// Add the time window section and, if any are given, the service time
+    /// section for an already validated schedule.
+    fn with_schedule(
+        self,
+        time_windows: &[TimeWindow],
+        service_times: &[f64],
+    ) -> Result<Self, LkhError> {
+        let windows = time_windows
+            .iter()
+            .enumerate()
+            .map(|(index, window)| format!("{} {} {}", index + 1, window.earliest, window.latest));
+        let problem = self.with_section("TIME_WINDOW_SECTION", windows)?;
+        if service_times.is_empty() {
+            return Ok(problem);
+        }
+        let service_times = service_times
+            .iter()
+            .enumerate()
+            .map(|(index, time)| format!("{} {time}", index + 1));
+        problem.with_section("SERVICE_TIME_SECTION", service_times)
     }
 
-    /// Return a copy with `STOP_AT_OPTIMUM` enabled or disabled.
-    pub fn with_stop_at_optimum(mut self, stop_at_optimum: bool) -> Self {
-        self.stop_at_optimum = Some(stop_at_optimum);
-        self
+    /// Add the edge weight keywords and section for already validated
+    /// distances.
+    fn with_distances(self, distances: &Distances) -> Result<Self, LkhError> {
+        match distances {
+            Distances::Euclidean2d(points) => self
+                .with_keyword("EDGE_WEIGHT_TYPE", "EUC_2D")?
+                .with_section("NODE_COORD_SECTION", point_lines(points)),
+            Distances::Matrix(matrix) => self
+                .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")?
+                .with_keyword("EDGE_WEIGHT_FORMAT", "FULL_MATRIX")?
+                .with_section("EDGE_WEIGHT_SECTION", matrix_lines(matrix)),
+        }
     }
 
-    /// Add a native LKH parameter not yet modeled as a typed field.
+    /// Parse TSPLIB/LKH problem text, the inverse of [`to_tsplib`](Self::to_tsplib).
     ///
-    /// This is intentionally an expert escape hatch. Prefer typed fields so the
-    /// Rust layer can validate values before handing them to upstream C.
-    pub fn with_lkh_parameter(
+    /// `NAME`, `TYPE`, and `DIMENSION` fill the header. Every other keyword and
+    /// section becomes a [`ProblemEntry`] in file order, with section lines
+    /// kept verbatim. `COMMENT` and blank lines are dropped and parsing stops
+    /// at `EOF`. `TYPE` values the vendored LKH does not know become
+    /// [`ProblemKind::Custom`]. Errors carry the one-based line number.
+    pub fn from_tsplib_str(text: &str) -> Result<Self, LkhError> {
+        parse_tsplib(text)
+    }
+
+    /// Read and parse a TSPLIB/LKH problem file.
+    pub fn from_tsplib_path(path: impl AsRef<Path>) -> Result<Self, LkhError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| LkhError::ReadFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        parse_tsplib(&text)
+    }
+
+    /// Return the problem name rendered into the TSPLIB header.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the declared node dimension.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Return a cloned problem kind.
+    pub fn kind(&self) -> ProblemKind {
+        self.kind.clone()
+    }
+
+    /// Borrow the problem kind without cloning.
+    pub fn kind_ref(&self) -> &ProblemKind {
+        &self.kind
+    }
+
+    /// Return the ordered problem entries that will be rendered after the
+    /// standard `NAME`, `TYPE`, `COMMENT`, and `DIMENSION` header.
+    pub fn entries(&self) -> &[ProblemEntry] {
+        &self.entries
+    }
+
+    /// Add or override a scalar TSPLIB/LKH problem keyword.
+    pub fn with_keyword(
         mut self,
         key: impl Into<String>,
         value: impl Into<String>,
     ) -> Result<Self, LkhError> {
-        let key = validate_parameter_key(key.into())?;
-        let value = validate_parameter_value(value.into())?;
-        self.additional_parameters.push((key, value));
+        let key = validate_problem_key(key.into())?;
+        let value = validate_problem_value(value.into())?;
+        self.entries.push(ProblemEntry::Keyword { key, value });
         Ok(self)
     }
 
-    /// Validate search settings without running LKH.
-    pub fn validate(&self) -> Result<(), LkhError> {
-        if self.runs <= 0 {
-            return Err(LkhError::InvalidSearchParameters(
-                "runs must be positive".to_owned(),
-            ));
-        }
-        if let Some(max_trials) = self.max_trials {
-            if max_trials < 0 {
-                return Err(LkhError::InvalidSearchParameters(
-                    "max_trials must be non-negative".to_owned(),
-                ));
+    /// Add a raw TSPLIB/LKH data section.
+    pub fn with_section<I, S>(mut self, key: impl Into<String>, lines: I) -> Result<Self, LkhError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let key = validate_problem_key(key.into())?;
+        let lines = lines
+            .into_iter()
+            .map(|line| validate_section_line(line.into()))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.entries.push(ProblemEntry::Section { key, lines });
+        Ok(self)
+    }
+
+    /// Render this problem as TSPLIB text in memory.
+    pub fn to_tsplib(&self) -> String {
+        let mut output = String::new();
+        writeln!(&mut output, "NAME: {}", self.name).unwrap();
+        writeln!(&mut output, "TYPE: {}", self.kind.as_tsplib_type()).unwrap();
+        writeln!(&mut output, "COMMENT: generated by LKH-rs programmatic API").unwrap();
+        writeln!(&mut output, "DIMENSION: {}", self.dimension).unwrap();
+        for entry in &self.entries {
+            match entry {
+                ProblemEntry::Keyword { key, value } => {
+                    writeln!(&mut output, "{key}: {value}").unwrap();
+                }
+                ProblemEntry::Section { key, lines } => {
+                    writeln!(&mut output, "{key}").unwrap();
+                    for line in lines {
+                        writeln!(&mut output, "{line}").unwrap();
+                    }
+                }
             }
         }
-        if let Some(move_type) = self.move_type {
-            if move_type < 2 {
-                return Err(LkhError::InvalidSearchParameters(
-                    "move_type must be at least 2".to_owned(),
-                ));
+        writeln!(&mut output, "EOF").unwrap();
+        output
+    }
+
+    /// Write this problem as a TSPLIB file.
+    ///
+    /// This is an explicit export adapter. It is not used by
+    /// `solve_problem`, which initializes LKH directly from memory.
+    pub fn write_tsplib(&self, path: impl AsRef<Path>) -> Result<(), LkhError> {
+        write_text(path.as_ref(), self.to_tsplib())
+    }
+
+    /// Check the entries against what LKH's problem reader requires.
+    ///
+    /// `with_keyword` and `with_section` only check characters. This checks
+    /// the meaning of the entries that most often make LKH abort:
+    ///
+    /// - kinds that need a capacity have a positive `CAPACITY`, and CVRP and
+    ///   CVRPTW have a `DEMAND_SECTION`;
+    /// - `NODE_COORD_SECTION` has one line per node, with distinct ids between
+    ///   1 and the dimension and numeric coordinates;
+    /// - `DEMAND_SECTION` and `DEPOT_SECTION` only name nodes in range;
+    /// - `EDGE_WEIGHT_TYPE: EXPLICIT` has a known `EDGE_WEIGHT_FORMAT`, and
+    ///   `EDGE_WEIGHT_SECTION` has as many weights as that format implies.
+    ///
+    /// [`solve_problem`](crate::solve_problem) and the other solve functions
+    /// call this before handing the problem to LKH. Custom kinds are only
+    /// checked for their sections.
+    pub fn validate(&self) -> Result<(), LkhError> {
+        let dimension = self.dimension;
+        if matches!(
+            self.kind,
+            ProblemKind::Cvrp | ProblemKind::Cvrptw | ProblemKind::Cluvrp | ProblemKind::SoftCluvrp
+        ) {
+            let capacity = self.keyword("CAPACITY").ok_or_else(|| {
+                LkhError::InvalidProblem(format!(
+                    "{} problems need a CAPACITY",
+                    self.kind.as_tsplib_type()
+                ))
+            })?;
+            if !capacity.parse::<i64>().is_ok_and(|capacity| capacity > 0) {
+                return Err(LkhError::InvalidProblem(format!(
+                    "CAPACITY must be a positive integer, got {capacity:?}"
+                )));
             }
         }
-        if let Some(patching_c) = self.patching_c {
-            if patching_c < 0 {
-                return Err(LkhError::InvalidSearchParameters(
-                    "patching_c must be non-negative".to_owned(),
-                ));
+        if matches!(self.kind, ProblemKind::Cvrp | ProblemKind::Cvrptw)
+            && self.section("DEMAND_SECTION").is_none()
+        {
+            return Err(LkhError::InvalidProblem(format!(
+                "{} problems need a DEMAND_SECTION",
+                self.kind.as_tsplib_type()
+            )));
+        }
+
+        if let Some(lines) = self.section("NODE_COORD_SECTION") {
+            let three_d = self
+                .keyword("NODE_COORD_TYPE")
+                .map(|value| value.eq_ignore_ascii_case("THREED_COORDS"))
+                .or_else(|| {
+                    self.keyword("EDGE_WEIGHT_TYPE")
+                        .map(|value| value.to_ascii_uppercase().ends_with("_3D"))
+                })
+                .unwrap_or(false);
+            validate_node_lines(
+                "NODE_COORD_SECTION",
+                lines,
+                dimension,
+                2 + usize::from(three_d),
+            )?;
+        }
+        if !matches!(self.kind, ProblemKind::Cluvrp | ProblemKind::SoftCluvrp) {
+            if let Some(lines) = self.section("DEMAND_SECTION") {
+                validate_node_lines("DEMAND_SECTION", lines, dimension, 1)?;
             }
         }
-        if let Some(patching_a) = self.patching_a {
-            if patching_a < 0 {
-                return Err(LkhError::InvalidSearchParameters(
-                    "patching_a must be non-negative".to_owned(),
-                ));
+        if let Some(lines) = self.section("DEPOT_SECTION") {
+            let values = section_numbers("DEPOT_SECTION", lines)?;
+            match values.as_slice() {
+                [depot] | [depot, -1.0] if *depot >= 1.0 && *depot <= dimension as f64 => {}
+                _ => {
+                    return Err(LkhError::InvalidProblem(format!(
+                        "DEPOT_SECTION must hold one node id between 1 and {dimension}, \
+                         optionally followed by -1"
+                    )))
+                }
             }
         }
-        validate_optional_seconds("time_limit", self.time_limit)?;
-        validate_optional_seconds("total_time_limit", self.total_time_limit)?;
+
+        if self.section("EDGE_DATA_SECTION").is_some()
+            && !self.keyword("EDGE_DATA_FORMAT").is_some_and(|format| {
+                format.eq_ignore_ascii_case("EDGE_LIST") || format.eq_ignore_ascii_case("ADJ_LIST")
+            })
+        {
+            return Err(LkhError::InvalidProblem(
+                "EDGE_DATA_SECTION needs EDGE_DATA_FORMAT: EDGE_LIST or ADJ_LIST".to_owned(),
+            ));
+        }
+
+        let explicit = self
+            .keyword("EDGE_WEIGHT_TYPE")
+            .is_some_and(|value| value.eq_ignore_ascii_case("EXPLICIT"));
+        let weights = self.section("EDGE_WEIGHT_SECTION");
+        if explicit && weights.is_none() {
+            return Err(LkhError::InvalidProblem(
+                "EDGE_WEIGHT_TYPE: EXPLICIT needs an EDGE_WEIGHT_SECTION".to_owned(),
+            ));
+        }
+        if let Some(lines) = weights {
+            let format = self.keyword("EDGE_WEIGHT_FORMAT").ok_or_else(|| {
+                LkhError::InvalidProblem(
+                    "EDGE_WEIGHT_SECTION needs an EDGE_WEIGHT_FORMAT".to_owned(),
+                )
+            })?;
+            let expected = explicit_weight_count(format, dimension)?;
+            // SOP and PCTSP matrices start with the dimension itself.
+            let expected =
+                expected + usize::from(matches!(self.kind, ProblemKind::Sop | ProblemKind::Pctsp));
+            let found = section_numbers("EDGE_WEIGHT_SECTION", lines)?.len();
+            if found != expected {
+                return Err(LkhError::InvalidProblem(format!(
+                    "EDGE_WEIGHT_SECTION has {found} values, but EDGE_WEIGHT_FORMAT: {format} \
+                     with DIMENSION {dimension} needs {expected}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The value of the last `key` keyword, as LKH keeps the last one.
+    pub(crate) fn keyword(&self, key: &str) -> Option<&str> {
+        self.entries.iter().rev().find_map(|entry| match entry {
+            ProblemEntry::Keyword { key: name, value } if name == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Check that the `EDGE_WEIGHT_SECTION` of a problem with a `SCALE` stays
+    /// below the distance LKH treats as infinite at the `PRECISION` of
+    /// `parameters`, `INT_MAX / 2 / PRECISION`, once scaled.
+    ///
+    /// [`RoutingProblem::float_distance_matrix`] picks its scale for the
+    /// default precision, so a higher one can push the scaled weights out of
+    /// range.
+    pub(crate) fn validate_scaled_weights(
+        &self,
+        parameters: &SearchParameters,
+    ) -> Result<(), LkhError> {
+        let Some(scale) = self
+            .keyword("SCALE")
+            .and_then(|scale| scale.trim().parse::<i32>().ok())
+        else {
+            return Ok(());
+        };
+        let Some(lines) = self.section("EDGE_WEIGHT_SECTION") else {
+            return Ok(());
+        };
+        let precision = parameters.precision.unwrap_or(100).max(1);
+        let limit = f64::from(i32::MAX / 2 / precision);
+        let largest = lines
+            .iter()
+            .flat_map(|line| line.split_whitespace())
+            .filter_map(|token| token.parse::<f64>().ok())
+            .fold(0.0, f64::max);
+        if (largest * f64::from(scale.max(1))).round() >= limit {
+            return Err(LkhError::InvalidProblem(format!(
+                "distance {largest} times SCALE {scale} does not stay below {limit}, \
+                 the largest distance LKH handles at PRECISION {precision}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that a problem with `EDGE_WEIGHT_TYPE: SPECIAL` carries the data
+    /// LKH's own SPECIAL distance reads, a `NODE_COORD_SECTION` or an
+    /// `EDGE_DATA_SECTION`.
+    ///
+    /// Without either, every distance would be 0. Such problems, like those of
+    /// [`RoutingProblem::distance_function`], are only solved by
+    /// [`solve_problem_with_distance`](crate::solve_problem_with_distance).
+    pub(crate) fn validate_native_distances(&self) -> Result<(), LkhError> {
+        let special = self
+            .keyword("EDGE_WEIGHT_TYPE")
+            .is_some_and(|value| value.eq_ignore_ascii_case("SPECIAL"));
+        if special
+            && self.section("NODE_COORD_SECTION").is_none()
+            && self.section("EDGE_DATA_SECTION").is_none()
+        {
+            return Err(LkhError::InvalidProblem(
+                "EDGE_WEIGHT_TYPE: SPECIAL without a NODE_COORD_SECTION or EDGE_DATA_SECTION \
+                 needs a distance function; use solve_problem_with_distance"
+                    .to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// This problem with an empty `FIXED_EDGES_SECTION` ahead of its first
+    /// section, unless that section already makes LKH create the nodes.
+    ///
+    /// Readers such as `DEMAND_SECTION` assume the nodes exist, which a
+    /// problem without coordinates or weights never guarantees.
+    pub(crate) fn with_nodes_created(&self) -> Cow<'_, Self> {
+        let first_section = self
+            .entries
+            .iter()
+            .position(|entry| matches!(entry, ProblemEntry::Section { .. }));
+        if let Some(ProblemEntry::Section { key, .. }) =
+            first_section.map(|index| &self.entries[index])
+        {
+            if NODE_CREATING_SECTIONS.contains(&key.as_str()) {
+                return Cow::Borrowed(self);
+            }
+        }
+        let mut problem = self.clone();
+        problem.entries.insert(
+            first_section.unwrap_or(problem.entries.len()),
+            ProblemEntry::Section {
+                key: "FIXED_EDGES_SECTION".to_owned(),
+                lines: vec!["-1".to_owned()],
+            },
+        );
+        Cow::Owned(problem)
+    }
+
+    /// The lines of the last `key` section.
+    fn section(&self, key: &str) -> Option<&[String]> {
+        self.entries.iter().rev().find_map(|entry| match entry {
+            ProblemEntry::Section { key: name, lines } if name == key => Some(lines.as_slice()),
+            _ => None,
+        })
+    }
+}
+
+fn parse_tsplib(text: &str) -> Result<RoutingProblem, LkhError> {
+    let mut name = None;
+    let mut kind = None;
+    let mut dimension = None;
+    let mut entries = Vec::new();
+    let mut in_section = false;
+    let mut line_number = 0;
+    for (index, line) in text.lines().enumerate() {
+        line_number = index + 1;
+        let at_line = |err: LkhError| tsplib_error(line_number, err);
+        let line = line.trim_start_matches('\u{feff}');
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        // Section data is numeric; anything starting with a letter is a
+        // keyword, as in LKH's own reader.
+        if !trimmed.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+            match entries.last_mut() {
+                Some(ProblemEntry::Section { lines, .. }) if in_section => {
+                    lines.push(validate_section_line(line.to_owned()).map_err(at_line)?);
+                }
+                _ => {
+                    return Err(at_line(LkhError::InvalidProblem(
+                        "data line outside a section".to_owned(),
+                    )))
+                }
+            }
+            continue;
+        }
+        in_section = false;
+        let (key, value) = split_tsplib_keyword(trimmed);
+        let key = key.to_ascii_uppercase();
+        match key.as_str() {
+            "EOF" => break,
+            "COMMENT" => {}
+            "NAME" => name = Some(validate_name(value.to_owned()).map_err(at_line)?),
+            "TYPE" => kind = Some(parse_problem_kind(value).map_err(at_line)?),
+            "DIMENSION" => {
+                let parsed = value.parse::<usize>().map_err(|_| {
+                    at_line(LkhError::InvalidProblem(format!(
+                        "DIMENSION must be a positive integer, got {value:?}"
+                    )))
+                })?;
+                validate_dimension(parsed).map_err(at_line)?;
+                dimension = Some(parsed);
+            }
+            _ if key.ends_with("_SECTION") => {
+                if !value.is_empty() {
+                    return Err(at_line(LkhError::InvalidProblem(format!(
+                        "unexpected text after {key}"
+                    ))));
+                }
+                let key = validate_problem_key(key).map_err(at_line)?;
+                entries.push(ProblemEntry::Section {
+                    key,
+                    lines: Vec::new(),
+                });
+                in_section = true;
+            }
+            _ => {
+                let key = validate_problem_key(key).map_err(at_line)?;
+                let value = validate_problem_value(value.to_owned()).map_err(at_line)?;
+                entries.push(ProblemEntry::Keyword { key, value });
+            }
+        }
+    }
+    let missing = |keyword: &str| {
+        tsplib_error(
+            line_number,
+            LkhError::InvalidProblem(format!("missing {keyword}")),
+        )
+    };
+    let kind = kind.ok_or_else(|| missing("TYPE"))?;
+    let dimension = dimension.ok_or_else(|| missing("DIMENSION"))?;
+    let mut problem = RoutingProblem::named(
+        name.unwrap_or_else(|| DEFAULT_PROBLEM_NAME.to_owned()),
+        kind,
+        dimension,
+    )?;
+    problem.entries = entries;
+    Ok(problem)
+}
+
+/// Split `KEY: VALUE`, `KEY = VALUE`, or `KEY VALUE` into key and value.
+fn split_tsplib_keyword(line: &str) -> (&str, &str) {
+    let end = line
+        .find(|ch: char| ch.is_whitespace() || ch == ':' || ch == '=')
+        .unwrap_or(line.len());
+    let (key, rest) = line.split_at(end);
+    let rest = rest.trim_start();
+    let rest = rest
+        .strip_prefix(':')
+        .or_else(|| rest.strip_prefix('='))
+        .unwrap_or(rest);
+    (key, rest.trim())
+}
+
+fn parse_problem_kind(value: &str) -> Result<ProblemKind, LkhError> {
+    let upper = value.to_ascii_uppercase();
+    match ProblemKind::ALL
+        .iter()
+        .find(|kind| kind.as_tsplib_type() == upper)
+    {
+        Some(kind) => Ok(kind.clone()),
+        None => ProblemKind::custom(value),
+    }
+}
+
+/// Attach a line number to a validation error raised while parsing TSPLIB.
+fn tsplib_error(line: usize, err: LkhError) -> LkhError {
+    let message = match err {
+        LkhError::InvalidProblem(message) => message,
+        other => other.to_string(),
+    };
+    LkhError::TsplibParse { line, message }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RoutingProblem {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::serialize(self, serializer)
+    }
+}
+
+/// Deserialized problems pass the same checks as the builder methods.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RoutingProblem {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let unchecked = Self::deserialize(deserializer)?;
+        let mut problem = Self::named(unchecked.name, unchecked.kind, unchecked.dimension)
+            .map_err(serde::de::Error::custom)?;
+        for entry in unchecked.entries {
+            problem = match entry {
+                ProblemEntry::Keyword { key, value } => problem.with_keyword(key, value),
+                ProblemEntry::Section { key, lines } => problem.with_section(key, lines),
+            }
+            .map_err(serde::de::Error::custom)?;
+        }
+        Ok(problem)
+    }
+}
+
+/// Search settings for programmatic solves.
+///
+/// The defaults are intentionally lightweight and quiet (`RUNS = 1`,
+/// `TRACE_LEVEL = 0`). Heavier benchmark-style runs can be requested by setting
+/// the corresponding fields.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
+pub struct SearchParameters {
+    /// Number of independent LKH runs.
+    pub runs: i32,
+    /// Native LKH trace level; zero keeps programmatic solves quiet.
+    pub trace_level: i32,
+    /// Maximum trials per run. `None` lets LKH choose its default.
+    pub max_trials: Option<i32>,
+    /// LKH move type. `None` keeps the upstream default.
+    pub move_type: Option<i32>,
+    /// Whether `move_type` uses LKH-3's special moves (`SPECIAL`).
+    pub move_type_special: bool,
+    /// Patching C parameter. `None` keeps the upstream default.
+    pub patching_c: Option<i32>,
+    /// Qualifier of `patching_c`.
+    pub patching_c_mode: Option<PatchingMode>,
+    /// Patching A parameter. `None` keeps the upstream default.
+    pub patching_a: Option<i32>,
+    /// Qualifier of `patching_a`.
+    pub patching_a_mode: Option<PatchingMode>,
+    /// Random seed used by LKH.
+    pub seed: Option<u32>,
+    /// Per-run time limit in seconds.
+    pub time_limit: Option<f64>,
+    /// Total time limit in seconds across all runs.
+    pub total_time_limit: Option<f64>,
+    /// Known optimum used by LKH for reporting and optional early stopping.
+    pub optimum: Option<i64>,
+    /// Whether LKH should stop when the optimum is reached.
+    pub stop_at_optimum: Option<bool>,
+    /// Tour used as the starting point of the first trial of each run
+    /// (`INITIAL_TOUR_FILE`).
+    pub initial_tour: Option<Vec<usize>>,
+    /// Tour whose edges are added to the candidate sets and used to limit
+    /// the search (`INPUT_TOUR_FILE`).
+    pub input_tour: Option<Vec<usize>>,
+    /// Tours LKH merges with its best tour after each run
+    /// (`MERGE_TOUR_FILE`).
+    pub merge_tours: Vec<Vec<usize>>,
+    /// Multiple traveling salesmen settings. `None` leaves a single salesman,
+    /// or the fleet size given by the problem.
+    pub mtsp: Option<MtspSettings>,
+    /// Subproblem partitioning for very large instances (`SUBPROBLEM_SIZE`).
+    /// `None` solves the whole problem at once.
+    pub subproblem: Option<SubproblemSettings>,
+    /// Tour that subproblem partitioning starts from and improves
+    /// (`SUBPROBLEM_TOUR_FILE`). Required by in-memory solves when
+    /// `subproblem` is set.
+    pub subproblem_tour: Option<Vec<usize>>,
+    /// Travel cost per unit of distance in a PTP instance.
+    pub alpha: Option<f64>,
+    /// Candidate edges per node during the ascent.
+    pub ascent_candidates: Option<i32>,
+    /// Backbone trials in each run.
+    pub backbone_trials: Option<i32>,
+    /// Whether the first move of a sequence is a backtracking move.
+    pub backtracking: Option<bool>,
+    /// Parameters of a BWTSP instance.
+    pub bwtsp: Option<BwtspParameters>,
+    /// How candidate edges are chosen.
+    pub candidate_set_type: Option<CandidateSetType>,
+    /// Depot node of mTSP and VRP instances.
+    pub depot: Option<i32>,
+    /// Maximum length of each route in a CVRP.
+    pub distance_limit: Option<f64>,
+    /// Number of drones in a TSPMD instance.
+    pub drones: Option<i32>,
+    /// Drone endurance in a TSPMD instance.
+    pub endurance: Option<f64>,
+    /// Maximum alpha-value of a candidate edge, relative to the lower bound.
+    pub excess: Option<f64>,
+    /// Number of external salesmen in an OCMTSP instance.
+    pub external_salesmen: Option<i32>,
+    /// Extra candidate edges per node.
+    pub extra_candidates: Option<i32>,
+    /// Whether extra candidate edges are added to both end nodes (`SYMMETRIC`).
+    pub extra_candidates_symmetric: bool,
+    /// How extra candidate edges are chosen.
+    pub extra_candidate_set_type: Option<ExtraCandidateSetType>,
+    /// Whether the Gain23 function is used.
+    pub gain23: Option<bool>,
+    /// Whether Lin and Kernighan's gain criterion is used.
+    pub gain_criterion: Option<bool>,
+    /// Length of the first period in the ascent.
+    pub initial_period: Option<i32>,
+    /// Initial step size in the ascent.
+    pub initial_step_size: Option<i32>,
+    /// Construction of the initial tour of each run.
+    pub initial_tour_algorithm: Option<InitialTourAlgorithm>,
+    /// Fraction of the initial tour built from `initial_tour` edges.
+    pub initial_tour_fraction: Option<f64>,
+    /// The k of a k-TSP instance.
+    pub k: Option<i32>,
+    /// Number of kicks applied to each tour found by Lin-Kernighan.
+    pub kicks: Option<i32>,
+    /// Size of the random k-swap kick, or 0 for LKH's special kick.
+    pub kick_type: Option<i32>,
+    /// Whether a TSPTW instance minimizes the makespan.
+    pub makespan: Option<bool>,
+    /// Candidate edges considered at each level of the move search.
+    pub max_breadth: Option<i32>,
+    /// Maximum candidate edges per node.
+    pub max_candidates: Option<i32>,
+    /// Whether candidate edges are added to both end nodes (`SYMMETRIC`).
+    pub max_candidates_symmetric: bool,
+    /// Maximum swaps in any search for a tour improvement.
+    pub max_swaps: Option<i32>,
+    /// Largest k of the nonsequential k-opt moves tried.
+    pub nonsequential_move_type: Option<i32>,
+    /// Whether the best POPMUSIC tour is the initial tour.
+    pub popmusic_initial_tour: Option<bool>,
+    /// Nearest neighbors used as 3-opt candidates by POPMUSIC.
+    pub popmusic_max_neighbors: Option<i32>,
+    /// POPMUSIC sample size.
+    pub popmusic_sample_size: Option<i32>,
+    /// Number of solutions generated by POPMUSIC.
+    pub popmusic_solutions: Option<i32>,
+    /// Trials of iterated 3-opt in POPMUSIC, or 0 for the subpath size.
+    pub popmusic_trials: Option<i32>,
+    /// Maximum population size of the genetic algorithm.
+    pub population_size: Option<i32>,
+    /// Internal precision of transformed distances.
+    pub precision: Option<i32>,
+    /// Node probability of a PTSP instance, in percent.
+    pub probability: Option<i32>,
+    /// Crossover used by the genetic algorithm.
+    pub recombination: Option<Recombination>,
+    /// Whether the first edge broken must not be in the best tour.
+    pub restricted_search: Option<bool>,
+    /// Factor applied to all distances.
+    pub scale: Option<i32>,
+    /// Whether Pi-values are computed by subgradient optimization.
+    pub subgradient: Option<bool>,
+    /// Move type after the first move of a sequence, or 0 for `move_type`.
+    pub subsequent_move_type: Option<i32>,
+    /// Whether `subsequent_move_type` uses LKH-3's special moves (`SPECIAL`).
+    pub subsequent_move_type_special: bool,
+    /// Whether moves after the first of a sequence use patching.
+    pub subsequent_patching: Option<bool>,
+    /// Expert escape hatch for LKH parameters not yet modeled directly.
+    ///
+    /// Unknown or incompatible keywords can still terminate inside upstream C,
+    /// so prefer typed fields when available.
+    pub additional_parameters: Vec<(String, String)>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SearchParameters {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::serialize(self, serializer)
+    }
+}
+
+/// Missing fields take their [`Default`] values, and the result is checked
+/// with [`SearchParameters::validate`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SearchParameters {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parameters = Self::deserialize(deserializer)?;
+        parameters.validate().map_err(serde::de::Error::custom)?;
+        Ok(parameters)
+    }
+}
+
+impl Default for SearchParameters {
+    fn default() -> Self {
+        Self {
+            runs: 1,
+            trace_level: 0,
+            max_trials: None,
+            move_type: None,
+            move_type_special: false,
+            patching_c: None,
+            patching_c_mode: None,
+            patching_a: None,
+            patching_a_mode: None,
+            seed: None,
+            time_limit: None,
+            total_time_limit: None,
+            optimum: None,
+            stop_at_optimum: None,
+            initial_tour: None,
+            input_tour: None,
+            merge_tours: Vec::new(),
+            mtsp: None,
+            subproblem: None,
+            subproblem_tour: None,
+            alpha: None,
+            ascent_candidates: None,
+            backbone_trials: None,
+            backtracking: None,
+            bwtsp: None,
+            candidate_set_type: None,
+            depot: None,
+            distance_limit: None,
+            drones: None,
+            endurance: None,
+            excess: None,
+            external_salesmen: None,
+            extra_candidates: None,
+            extra_candidates_symmetric: false,
+            extra_candidate_set_type: None,
+            gain23: None,
+            gain_criterion: None,
+            initial_period: None,
+            initial_step_size: None,
+            initial_tour_algorithm: None,
+            initial_tour_fraction: None,
+            k: None,
+            kicks: None,
+            kick_type: None,
+            makespan: None,
+            max_breadth: None,
+            max_candidates: None,
+            max_candidates_symmetric: false,
+            max_swaps: None,
+            nonsequential_move_type: None,
+            popmusic_initial_tour: None,
+            popmusic_max_neighbors: None,
+            popmusic_sample_size: None,
+            popmusic_solutions: None,
+            popmusic_trials: None,
+            population_size: None,
+            precision: None,
+            probability: None,
+            recombination: None,
+            restricted_search: None,
+            scale: None,
+            subgradient: None,
+            subsequent_move_type: None,
+            subsequent_move_type_special: false,
+            subsequent_patching: None,
+            additional_parameters: Vec::new(),
+        }
+    }
+}
+
+impl SearchParameters {
+    /// Create default lightweight search settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a copy with a custom run count.
+    pub fn with_runs(mut self, runs: i32) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Return a copy with a custom native trace level.
+    pub fn with_trace_level(mut self, trace_level: i32) -> Self {
+        self.trace_level = trace_level;
+        self
+    }
+
+    /// Return a copy with a maximum trial count.
+    pub fn with_max_trials(mut self, max_trials: i32) -> Self {
+        self.max_trials = Some(max_trials);
+        self
+    }
+
+    /// Return a copy with a custom LKH move type.
+    pub fn with_move_type(mut self, move_type: i32) -> Self {
+        self.move_type = Some(move_type);
+        self
+    }
+
+    /// Return a copy with LKH-3's special moves enabled or disabled.
+    pub fn with_move_type_special(mut self, move_type_special: bool) -> Self {
+        self.move_type_special = move_type_special;
+        self
+    }
+
+    /// Return a copy with a custom `PATCHING_C` value.
+    pub fn with_patching_c(mut self, patching_c: i32) -> Self {
+        self.patching_c = Some(patching_c);
+        self
+    }
+
+    /// Return a copy with a `PATCHING_C` qualifier.
+    pub fn with_patching_c_mode(mut self, mode: PatchingMode) -> Self {
+        self.patching_c_mode = Some(mode);
+        self
+    }
+
+    /// Return a copy with a custom `PATCHING_A` value.
+    pub fn with_patching_a(mut self, patching_a: i32) -> Self {
+        self.patching_a = Some(patching_a);
+        self
+    }
+
+    /// Return a copy with a `PATCHING_A` qualifier.
+    pub fn with_patching_a_mode(mut self, mode: PatchingMode) -> Self {
+        self.patching_a_mode = Some(mode);
+        self
+    }
+
+    /// Return a copy with a deterministic LKH seed.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Return a copy with a per-run time limit in seconds.
+    pub fn with_time_limit(mut self, seconds: f64) -> Self {
+        self.time_limit = Some(seconds);
+        self
+    }
+
+    /// Return a copy with a total time limit in seconds.
+    pub fn with_total_time_limit(mut self, seconds: f64) -> Self {
+        self.total_time_limit = Some(seconds);
+        self
+    }
+
+    /// Return a copy with a known optimum.
+    pub fn with_optimum(mut self, optimum: i64) -> Self {
+        self.optimum = Some(optimum);
+        self
+    }
+
+    /// Return a copy with `STOP_AT_OPTIMUM` enabled or disabled.
+    pub fn with_stop_at_optimum(mut self, stop_at_optimum: bool) -> Self {
+        self.stop_at_optimum = Some(stop_at_optimum);
+        self
+    }
+
+    /// Return a copy that starts each run from `tour`.
+    ///
+    /// Tours list one-based node ids in the numbering of
+    /// [`SolveReport::tour`](crate::SolveReport::tour), so the tour of an
+    /// earlier solve of the same problem can be passed back unchanged.
+    pub fn with_initial_tour(mut self, tour: Vec<usize>) -> Self {
+        self.initial_tour = Some(tour);
+        self
+    }
+
+    /// Return a copy with an `INPUT_TOUR_FILE` tour.
+    pub fn with_input_tour(mut self, tour: Vec<usize>) -> Self {
+        self.input_tour = Some(tour);
+        self
+    }
+
+    /// Return a copy with tours to merge with each run's best tour.
+    pub fn with_merge_tours(mut self, tours: Vec<Vec<usize>>) -> Self {
+        self.merge_tours = tours;
+        self
+    }
+
+    /// Return a copy that splits the tour between several salesmen.
+    pub fn with_mtsp(mut self, mtsp: MtspSettings) -> Self {
+        self.mtsp = Some(mtsp);
+        self
+    }
+
+    /// Return a copy that solves the problem in subproblems.
+    pub fn with_subproblem(mut self, subproblem: SubproblemSettings) -> Self {
+        self.subproblem = Some(subproblem);
+        self
+    }
+
+    /// Return a copy with the tour that subproblem partitioning improves.
+    pub fn with_subproblem_tour(mut self, tour: Vec<usize>) -> Self {
+        self.subproblem_tour = Some(tour);
+        self
+    }
+
+    /// Return a copy with `ALPHA` set.
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = Some(alpha);
+        self
+    }
+
+    /// Return a copy with `ASCENT_CANDIDATES` set.
+    pub fn with_ascent_candidates(mut self, ascent_candidates: i32) -> Self {
+        self.ascent_candidates = Some(ascent_candidates);
+        self
+    }
+
+    /// Return a copy with `BACKBONE_TRIALS` set.
+    pub fn with_backbone_trials(mut self, backbone_trials: i32) -> Self {
+        self.backbone_trials = Some(backbone_trials);
+        self
+    }
+
+    /// Return a copy with `BACKTRACKING` set.
+    pub fn with_backtracking(mut self, backtracking: bool) -> Self {
+        self.backtracking = Some(backtracking);
+        self
+    }
+
+    /// Return a copy with `BWTSP` set.
+    pub fn with_bwtsp(mut self, bwtsp: BwtspParameters) -> Self {
+        self.bwtsp = Some(bwtsp);
+        self
+    }
+
+    /// Return a copy with `CANDIDATE_SET_TYPE` set.
+    pub fn with_candidate_set_type(mut self, candidate_set_type: CandidateSetType) -> Self {
+        self.candidate_set_type = Some(candidate_set_type);
+        self
+    }
+
+    /// Return a copy with `DEPOT` set.
+    pub fn with_depot(mut self, depot: i32) -> Self {
+        self.depot = Some(depot);
+        self
+    }
+
+    /// Return a copy with `DISTANCE` set.
+    pub fn with_distance_limit(mut self, distance_limit: f64) -> Self {
+        self.distance_limit = Some(distance_limit);
+        self
+    }
+
+    /// Return a copy with `DRONES` set.
+    pub fn with_drones(mut self, drones: i32) -> Self {
+        self.drones = Some(drones);
+        self
+    }
+
+    /// Return a copy with `ENDURANCE` set.
+    pub fn with_endurance(mut self, endurance: f64) -> Self {
+        self.endurance = Some(endurance);
+        self
+    }
+
+    /// Return a copy with `EXCESS` set.
+    pub fn with_excess(mut self, excess: f64) -> Self {
+        self.excess = Some(excess);
+        self
+    }
+
+    /// Return a copy with `EXTERNAL_SALESMEN` set.
+    pub fn with_external_salesmen(mut self, external_salesmen: i32) -> Self {
+        self.external_salesmen = Some(external_salesmen);
+        self
+    }
+
+    /// Return a copy with `EXTRA_CANDIDATES` set.
+    pub fn with_extra_candidates(mut self, extra_candidates: i32) -> Self {
+        self.extra_candidates = Some(extra_candidates);
+        self
+    }
+
+    /// Return a copy with the `SYMMETRIC` qualifier of `EXTRA_CANDIDATES` enabled or
+    /// disabled.
+    pub fn with_extra_candidates_symmetric(mut self, extra_candidates_symmetric: bool) -> Self {
+        self.extra_candidates_symmetric = extra_candidates_symmetric;
+        self
+    }
+
+    /// Return a copy with `EXTRA_CANDIDATE_SET_TYPE` set.
+    pub fn with_extra_candidate_set_type(
+        mut self,
+        extra_candidate_set_type: ExtraCandidateSetType,
+    ) -> Self {
+        self.extra_candidate_set_type = Some(extra_candidate_set_type);
+        self
+    }
+
+    /// Return a copy with `GAIN23` set.
+    pub fn with_gain23(mut self, gain23: bool) -> Self {
+        self.gain23 = Some(gain23);
+        self
+    }
+
+    /// Return a copy with `GAIN_CRITERION` set.
+    pub fn with_gain_criterion(mut self, gain_criterion: bool) -> Self {
+        self.gain_criterion = Some(gain_criterion);
+        self
+    }
+
+    /// Return a copy with `INITIAL_PERIOD` set.
+    pub fn with_initial_period(mut self, initial_period: i32) -> Self {
+        self.initial_period = Some(initial_period);
+        self
+    }
+
+    /// Return a copy with `INITIAL_STEP_SIZE` set.
+    pub fn with_initial_step_size(mut self, initial_step_size: i32) -> Self {
+        self.initial_step_size = Some(initial_step_size);
+        self
+    }
+
+    /// Return a copy with `INITIAL_TOUR_ALGORITHM` set.
+    pub fn with_initial_tour_algorithm(
+        mut self,
+        initial_tour_algorithm: InitialTourAlgorithm,
+    ) -> Self {
+        self.initial_tour_algorithm = Some(initial_tour_algorithm);
+        self
+    }
+
+    /// Return a copy with `INITIAL_TOUR_FRACTION` set.
+    pub fn with_initial_tour_fraction(mut self, initial_tour_fraction: f64) -> Self {
+        self.initial_tour_fraction = Some(initial_tour_fraction);
+        self
+    }
+
+    /// Return a copy with `K` set.
+    pub fn with_k(mut self, k: i32) -> Self {
+        self.k = Some(k);
+        self
+    }
+
+    /// Return a copy with `KICKS` set.
+    pub fn with_kicks(mut self, kicks: i32) -> Self {
+        self.kicks = Some(kicks);
+        self
+    }
+
+    /// Return a copy with `KICK_TYPE` set.
+    pub fn with_kick_type(mut self, kick_type: i32) -> Self {
+        self.kick_type = Some(kick_type);
+        self
+    }
+
+    /// Return a copy with `MAKESPAN` set.
+    pub fn with_makespan(mut self, makespan: bool) -> Self {
+        self.makespan = Some(makespan);
+        self
+    }
+
+    /// Return a copy with `MAX_BREADTH` set.
+    pub fn with_max_breadth(mut self, max_breadth: i32) -> Self {
+        self.max_breadth = Some(max_breadth);
+        self
+    }
+
+    /// Return a copy with `MAX_CANDIDATES` set.
+    pub fn with_max_candidates(mut self, max_candidates: i32) -> Self {
+        self.max_candidates = Some(max_candidates);
+        self
+    }
+
+    /// Return a copy with the `SYMMETRIC` qualifier of `MAX_CANDIDATES` enabled or
+    /// disabled.
+    pub fn with_max_candidates_symmetric(mut self, max_candidates_symmetric: bool) -> Self {
+        self.max_candidates_symmetric = max_candidates_symmetric;
+        self
+    }
+
+    /// Return a copy with `MAX_SWAPS` set.
+    pub fn with_max_swaps(mut self, max_swaps: i32) -> Self {
+        self.max_swaps = Some(max_swaps);
+        self
+    }
+
+    /// Return a copy with `NONSEQUENTIAL_MOVE_TYPE` set.
+    pub fn with_nonsequential_move_type(mut self, nonsequential_move_type: i32) -> Self {
+        self.nonsequential_move_type = Some(nonsequential_move_type);
+        self
+    }
+
+    /// Return a copy with `POPMUSIC_INITIAL_TOUR` set.
+    pub fn with_popmusic_initial_tour(mut self, popmusic_initial_tour: bool) -> Self {
+        self.popmusic_initial_tour = Some(popmusic_initial_tour);
+        self
+    }
+
+    /// Return a copy with `POPMUSIC_MAX_NEIGHBORS` set.
+    pub fn with_popmusic_max_neighbors(mut self, popmusic_max_neighbors: i32) -> Self {
+        self.popmusic_max_neighbors = Some(popmusic_max_neighbors);
+        self
+    }
+
+    /// Return a copy with `POPMUSIC_SAMPLE_SIZE` set.
+    pub fn with_popmusic_sample_size(mut self, popmusic_sample_size: i32) -> Self {
+        self.popmusic_sample_size = Some(popmusic_sample_size);
+        self
+    }
+
+    /// Return a copy with `POPMUSIC_SOLUTIONS` set.
+    pub fn with_popmusic_solutions(mut self, popmusic_solutions: i32) -> Self {
+        self.popmusic_solutions = Some(popmusic_solutions);
+        self
+    }
+
+    /// Return a copy with `POPMUSIC_TRIALS` set.
+    pub fn with_popmusic_trials(mut self, popmusic_trials: i32) -> Self {
+        self.popmusic_trials = Some(popmusic_trials);
+        self
+    }
+
+    /// Return a copy with `POPULATION_SIZE` set.
+    pub fn with_population_size(mut self, population_size: i32) -> Self {
+        self.population_size = Some(population_size);
+        self
+    }
+
+    /// Return a copy with `PRECISION` set.
+    pub fn with_precision(mut self, precision: i32) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Return a copy with `PROBABILITY` set.
+    pub fn with_probability(mut self, probability: i32) -> Self {
+        self.probability = Some(probability);
+        self
+    }
+
+    /// Return a copy with `RECOMBINATION` set.
+    pub fn with_recombination(mut self, recombination: Recombination) -> Self {
+        self.recombination = Some(recombination);
+        self
+    }
+
+    /// Return a copy with `RESTRICTED_SEARCH` set.
+    pub fn with_restricted_search(mut self, restricted_search: bool) -> Self {
+        self.restricted_search = Some(restricted_search);
+        self
+    }
+
+    /// Return a copy with `SCALE` set.
+    pub fn with_scale(mut self, scale: i32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Return a copy with `SUBGRADIENT` set.
+    pub fn with_subgradient(mut self, subgradient: bool) -> Self {
+        self.subgradient = Some(subgradient);
+        self
+    }
+
+    /// Return a copy with `SUBSEQUENT_MOVE_TYPE` set.
+    pub fn with_subsequent_move_type(mut self, subsequent_move_type: i32) -> Self {
+        self.subsequent_move_type = Some(subsequent_move_type);
+        self
+    }
+
+    /// Return a copy with the `SPECIAL` qualifier of `SUBSEQUENT_MOVE_TYPE` enabled or
+    /// disabled.
+    pub fn with_subsequent_move_type_special(mut self, subsequent_move_type_special: bool) -> Self {
+        self.subsequent_move_type_special = subsequent_move_type_special;
+        self
+    }
+
+    /// Return a copy with `SUBSEQUENT_PATCHING` set.
+    pub fn with_subsequent_patching(mut self, subsequent_patching: bool) -> Self {
+        self.subsequent_patching = Some(subsequent_patching);
+        self
+    }
+
+    /// Add a native LKH parameter not yet modeled as a typed field.
+    ///
+    /// This is intentionally an expert escape hatch. Prefer typed fields so the
+    /// Rust layer can validate values before handing them to upstream C.
+    pub fn with_lkh_parameter(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Self, LkhError> {
+        let key = validate_parameter_key(key.into())?;
+        let value = validate_parameter_value(value.into())?;
+        self.additional_parameters.push((key, value));
+        Ok(self)
+    }
+
+    /// Validate search settings without running LKH.
+    pub fn validate(&self) -> Result<(), LkhError> {
+        if self.runs <= 0 {
+            return Err(LkhError::InvalidSearchParameters(
+                "runs must be positive".to_owned(),
+            ));
+        }
+        if let Some(max_trials) = self.max_trials {
+            if max_trials < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "max_trials must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(move_type) = self.move_type {
+            if move_type < 2 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "move_type must be at least 2".to_owned(),
+                ));
+            }
+        }
+        validate_special_move("move_type", self.move_type, self.move_type_special)?;
+        if let Some(patching_c) = self.patching_c {
+            if patching_c < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "patching_c must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(patching_a) = self.patching_a {
+            if patching_a < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "patching_a must be non-negative".to_owned(),
+                ));
+            }
+        }
+        validate_optional_seconds("time_limit", self.time_limit)?;
+        validate_optional_seconds("total_time_limit", self.total_time_limit)?;
+        if let Some(tour) = &self.initial_tour {
+            validate_tour("initial_tour", tour)?;
+        }
+        if let Some(tour) = &self.input_tour {
+            validate_tour("input_tour", tour)?;
+        }
+        for tour in &self.merge_tours {
+            validate_tour("merge_tours", tour)?;
+        }
+        if let Some(mtsp) = &self.mtsp {
+            mtsp.validate()?;
+        }
+        if let Some(subproblem) = &self.subproblem {
+            subproblem.validate()?;
+            if self.mtsp.as_ref().is_some_and(|mtsp| mtsp.salesmen > 1) {
+                return Err(LkhError::InvalidSearchParameters(
+                    "subproblem partitioning is not possible with more than one salesman"
+                        .to_owned(),
+                ));
+            }
+        }
+        if let Some(tour) = &self.subproblem_tour {
+            if self.subproblem.is_none() {
+                return Err(LkhError::InvalidSearchParameters(
+                    "subproblem_tour requires subproblem".to_owned(),
+                ));
+            }
+            validate_tour("subproblem_tour", tour)?;
+        }
+        if self.patching_c_mode.is_some() && self.patching_c.is_none() {
+            return Err(LkhError::InvalidSearchParameters(
+                "patching_c_mode requires patching_c".to_owned(),
+            ));
+        }
+        if self.patching_a_mode.is_some() && self.patching_a.is_none() {
+            return Err(LkhError::InvalidSearchParameters(
+                "patching_a_mode requires patching_a".to_owned(),
+            ));
+        }
+        if let Some(value) = self.alpha {
+            if !value.is_finite() {
+                return Err(LkhError::InvalidSearchParameters(
+                    "alpha must be finite".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.ascent_candidates {
+            if value < 2 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "ascent_candidates must be at least 2".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.backbone_trials {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "backbone_trials must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.depot {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "depot must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.distance_limit {
+            if !value.is_finite() || value < 0.0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "distance_limit must be a finite non-negative number".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.drones {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "drones must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.endurance {
+            if !value.is_finite() || value < 0.0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "endurance must be a finite non-negative number".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.excess {
+            if !value.is_finite() || value < 0.0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "excess must be a finite non-negative number".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.external_salesmen {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "external_salesmen must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.extra_candidates {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "extra_candidates must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if self.extra_candidates_symmetric && self.extra_candidates.is_none() {
+            return Err(LkhError::InvalidSearchParameters(
+                "extra_candidates_symmetric requires extra_candidates".to_owned(),
+            ));
+        }
+        if let Some(value) = self.initial_period {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "initial_period must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.initial_step_size {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "initial_step_size must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.initial_tour_fraction {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(LkhError::InvalidSearchParameters(
+                    "initial_tour_fraction must be between 0 and 1".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.k {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "k must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.kicks {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "kicks must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.kick_type {
+            if value != 0 && value < 4 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "kick_type must be 0 or at least 4".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.max_breadth {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "max_breadth must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.max_candidates {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "max_candidates must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if self.max_candidates_symmetric && self.max_candidates.is_none() {
+            return Err(LkhError::InvalidSearchParameters(
+                "max_candidates_symmetric requires max_candidates".to_owned(),
+            ));
+        }
+        if let Some(value) = self.max_swaps {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "max_swaps must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.nonsequential_move_type {
+            if value < 4 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "nonsequential_move_type must be at least 4".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.popmusic_max_neighbors {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "popmusic_max_neighbors must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.popmusic_sample_size {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "popmusic_sample_size must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.popmusic_solutions {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "popmusic_solutions must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.popmusic_trials {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "popmusic_trials must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.population_size {
+            if value < 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "population_size must be non-negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.precision {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "precision must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.probability {
+            if !(0..=100).contains(&value) {
+                return Err(LkhError::InvalidSearchParameters(
+                    "probability must be between 0 and 100".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.scale {
+            if value <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "scale must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some(value) = self.subsequent_move_type {
+            if value != 0 && value < 2 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "subsequent_move_type must be 0 or at least 2".to_owned(),
+                ));
+            }
+        }
+        if self.subsequent_move_type_special && self.subsequent_move_type.is_none() {
+            return Err(LkhError::InvalidSearchParameters(
+                "subsequent_move_type_special requires subsequent_move_type".to_owned(),
+            ));
+        }
+        validate_special_move(
+            "subsequent_move_type",
+            self.subsequent_move_type,
+            self.subsequent_move_type_special,
+        )?;
+        if let Some(bwtsp) = self.bwtsp {
+            if bwtsp.black_nodes <= 0 || bwtsp.max_white_nodes <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "bwtsp node counts must be positive".to_owned(),
+                ));
+            }
+        }
+        for (key, value) in &self.additional_parameters {
+            validate_parameter_key(key.clone())?;
+            validate_parameter_value(value.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Render an LKH parameter file in memory for a given problem file name.
+    pub fn to_lkh_parameter_file(&self, problem_file_name: &str) -> Result<String, LkhError> {
+        validate_problem_file_name(problem_file_name)?;
+        self.validate()?;
+
+        let mut output = String::new();
+        writeln!(&mut output, "PROBLEM_FILE = {problem_file_name}").unwrap();
+        writeln!(&mut output, "RUNS = {}", self.runs).unwrap();
+        writeln!(&mut output, "TRACE_LEVEL = {}", self.trace_level).unwrap();
+        if let Some(max_trials) = self.max_trials {
+            writeln!(&mut output, "MAX_TRIALS = {max_trials}").unwrap();
+        }
+        if let Some(move_type) = self.move_type {
+            let special = if self.move_type_special {
+                " SPECIAL"
+            } else {
+                ""
+            };
+            writeln!(&mut output, "MOVE_TYPE = {move_type}{special}").unwrap();
+        }
+        if let Some(patching_c) = self.patching_c {
+            let mode = patching_mode_suffix(self.patching_c_mode);
+            writeln!(&mut output, "PATCHING_C = {patching_c}{mode}").unwrap();
+        }
+        if let Some(patching_a) = self.patching_a {
+            let mode = patching_mode_suffix(self.patching_a_mode);
+            writeln!(&mut output, "PATCHING_A = {patching_a}{mode}").unwrap();
+        }
+        if let Some(seed) = self.seed {
+            writeln!(&mut output, "SEED = {seed}").unwrap();
+        }
+        if let Some(time_limit) = self.time_limit {
+            writeln!(&mut output, "TIME_LIMIT = {time_limit}").unwrap();
+        }
+        if let Some(total_time_limit) = self.total_time_limit {
+            writeln!(&mut output, "TOTAL_TIME_LIMIT = {total_time_limit}").unwrap();
+        }
+        if let Some(optimum) = self.optimum {
+            writeln!(&mut output, "OPTIMUM = {optimum}").unwrap();
+        }
+        if let Some(stop_at_optimum) = self.stop_at_optimum {
+            writeln!(
+                &mut output,
+                "STOP_AT_OPTIMUM = {}",
+                yes_or_no(stop_at_optimum)
+            )
+            .unwrap();
+        }
+        if let Some(mtsp) = &self.mtsp {
+            writeln!(&mut output, "SALESMEN = {}", mtsp.salesmen).unwrap();
+            if let Some(objective) = mtsp.objective {
+                writeln!(&mut output, "MTSP_OBJECTIVE = {}", objective.as_str()).unwrap();
+            }
+            if let Some(min_size) = mtsp.min_size {
+                writeln!(&mut output, "MTSP_MIN_SIZE = {min_size}").unwrap();
+            }
+            if let Some(max_size) = mtsp.max_size {
+                writeln!(&mut output, "MTSP_MAX_SIZE = {max_size}").unwrap();
+            }
+        }
+        if let Some(subproblem) = &self.subproblem {
+            writeln!(&mut output, "SUBPROBLEM_SIZE = {subproblem}").unwrap();
+        }
+        if let Some(alpha) = self.alpha {
+            writeln!(&mut output, "ALPHA = {alpha}").unwrap();
+        }
+        if let Some(ascent_candidates) = self.ascent_candidates {
+            writeln!(&mut output, "ASCENT_CANDIDATES = {ascent_candidates}").unwrap();
+        }
+        if let Some(backbone_trials) = self.backbone_trials {
+            writeln!(&mut output, "BACKBONE_TRIALS = {backbone_trials}").unwrap();
+        }
+        if let Some(backtracking) = self.backtracking {
+            writeln!(&mut output, "BACKTRACKING = {}", yes_or_no(backtracking)).unwrap();
+        }
+        if let Some(bwtsp) = self.bwtsp {
+            writeln!(&mut output, "BWTSP = {bwtsp}").unwrap();
+        }
+        if let Some(candidate_set_type) = self.candidate_set_type {
+            writeln!(&mut output, "CANDIDATE_SET_TYPE = {candidate_set_type}").unwrap();
+        }
+        if let Some(depot) = self.depot {
+            writeln!(&mut output, "DEPOT = {depot}").unwrap();
+        }
+        if let Some(distance_limit) = self.distance_limit {
+            writeln!(&mut output, "DISTANCE = {distance_limit}").unwrap();
+        }
+        if let Some(drones) = self.drones {
+            writeln!(&mut output, "DRONES = {drones}").unwrap();
+        }
+        if let Some(endurance) = self.endurance {
+            writeln!(&mut output, "ENDURANCE = {endurance}").unwrap();
+        }
+        if let Some(excess) = self.excess {
+            writeln!(&mut output, "EXCESS = {excess}").unwrap();
+        }
+        if let Some(external_salesmen) = self.external_salesmen {
+            writeln!(&mut output, "EXTERNAL_SALESMEN = {external_salesmen}").unwrap();
+        }
+        if let Some(extra_candidates) = self.extra_candidates {
+            let qualifier = if self.extra_candidates_symmetric {
+                " SYMMETRIC"
+            } else {
+                ""
+            };
+            writeln!(
+                &mut output,
+                "EXTRA_CANDIDATES = {extra_candidates}{qualifier}"
+            )
+            .unwrap();
+        }
+        if let Some(extra_candidate_set_type) = self.extra_candidate_set_type {
+            writeln!(
+                &mut output,
+                "EXTRA_CANDIDATE_SET_TYPE = {extra_candidate_set_type}"
+            )
+            .unwrap();
+        }
+        if let Some(gain23) = self.gain23 {
+            writeln!(&mut output, "GAIN23 = {}", yes_or_no(gain23)).unwrap();
+        }
+        if let Some(gain_criterion) = self.gain_criterion {
+            writeln!(
+                &mut output,
+                "GAIN_CRITERION = {}",
+                yes_or_no(gain_criterion)
+            )
+            .unwrap();
+        }
+        if let Some(initial_period) = self.initial_period {
+            writeln!(&mut output, "INITIAL_PERIOD = {initial_period}").unwrap();
+        }
+        if let Some(initial_step_size) = self.initial_step_size {
+            writeln!(&mut output, "INITIAL_STEP_SIZE = {initial_step_size}").unwrap();
+        }
+        if let Some(initial_tour_algorithm) = self.initial_tour_algorithm {
+            writeln!(
+                &mut output,
+                "INITIAL_TOUR_ALGORITHM = {initial_tour_algorithm}"
+            )
+            .unwrap();
+        }
+        if let Some(initial_tour_fraction) = self.initial_tour_fraction {
+            writeln!(
+                &mut output,
+                "INITIAL_TOUR_FRACTION = {initial_tour_fraction}"
+            )
+            .unwrap();
+        }
+        if let Some(k) = self.k {
+            writeln!(&mut output, "K = {k}").unwrap();
+        }
+        if let Some(kicks) = self.kicks {
+            writeln!(&mut output, "KICKS = {kicks}").unwrap();
+        }
+        if let Some(kick_type) = self.kick_type {
+            writeln!(&mut output, "KICK_TYPE = {kick_type}").unwrap();
+        }
+        if let Some(makespan) = self.makespan {
+            writeln!(&mut output, "MAKESPAN = {}", yes_or_no(makespan)).unwrap();
+        }
+        if let Some(max_breadth) = self.max_breadth {
+            writeln!(&mut output, "MAX_BREADTH = {max_breadth}").unwrap();
+        }
+        if let Some(max_candidates) = self.max_candidates {
+            let qualifier = if self.max_candidates_symmetric {
+                " SYMMETRIC"
+            } else {
+                ""
+            };
+            writeln!(&mut output, "MAX_CANDIDATES = {max_candidates}{qualifier}").unwrap();
+        }
+        if let Some(max_swaps) = self.max_swaps {
+            writeln!(&mut output, "MAX_SWAPS = {max_swaps}").unwrap();
+        }
+        if let Some(nonsequential_move_type) = self.nonsequential_move_type {
+            writeln!(
+                &mut output,
+                "NONSEQUENTIAL_MOVE_TYPE = {nonsequential_move_type}"
+            )
+            .unwrap();
+        }
+        if let Some(popmusic_initial_tour) = self.popmusic_initial_tour {
+            writeln!(
+                &mut output,
+                "POPMUSIC_INITIAL_TOUR = {}",
+                yes_or_no(popmusic_initial_tour)
+            )
+            .unwrap();
+        }
+        if let Some(popmusic_max_neighbors) = self.popmusic_max_neighbors {
+            writeln!(
+                &mut output,
+                "POPMUSIC_MAX_NEIGHBORS = {popmusic_max_neighbors}"
+            )
+            .unwrap();
+        }
+        if let Some(popmusic_sample_size) = self.popmusic_sample_size {
+            writeln!(&mut output, "POPMUSIC_SAMPLE_SIZE = {popmusic_sample_size}").unwrap();
+        }
+        if let Some(popmusic_solutions) = self.popmusic_solutions {
+            writeln!(&mut output, "POPMUSIC_SOLUTIONS = {popmusic_solutions}").unwrap();
+        }
+        if let Some(popmusic_trials) = self.popmusic_trials {
+            writeln!(&mut output, "POPMUSIC_TRIALS = {popmusic_trials}").unwrap();
+        }
+        if let Some(population_size) = self.population_size {
+            writeln!(&mut output, "POPULATION_SIZE = {population_size}").unwrap();
+        }
+        if let Some(precision) = self.precision {
+            writeln!(&mut output, "PRECISION = {precision}").unwrap();
+        }
+        if let Some(probability) = self.probability {
+            writeln!(&mut output, "PROBABILITY = {probability}").unwrap();
+        }
+        if let Some(recombination) = self.recombination {
+            writeln!(&mut output, "RECOMBINATION = {recombination}").unwrap();
+        }
+        if let Some(restricted_search) = self.restricted_search {
+            writeln!(
+                &mut output,
+                "RESTRICTED_SEARCH = {}",
+                yes_or_no(restricted_search)
+            )
+            .unwrap();
+        }
+        if let Some(scale) = self.scale {
+            writeln!(&mut output, "SCALE = {scale}").unwrap();
+        }
+        if let Some(subgradient) = self.subgradient {
+            writeln!(&mut output, "SUBGRADIENT = {}", yes_or_no(subgradient)).unwrap();
+        }
+        if let Some(subsequent_move_type) = self.subsequent_move_type {
+            let qualifier = if self.subsequent_move_type_special {
+                " SPECIAL"
+            } else {
+                ""
+            };
+            writeln!(
+                &mut output,
+                "SUBSEQUENT_MOVE_TYPE = {subsequent_move_type}{qualifier}"
+            )
+            .unwrap();
+        }
+        if let Some(subsequent_patching) = self.subsequent_patching {
+            writeln!(
+                &mut output,
+                "SUBSEQUENT_PATCHING = {}",
+                yes_or_no(subsequent_patching)
+            )
+            .unwrap();
+        }
         for (key, value) in &self.additional_parameters {
-            validate_parameter_key(key.clone())?;
-            validate_parameter_value(value.clone())?;
+            writeln!(&mut output, "{key} = {value}").unwrap();
+        }
+        Ok(output)
+    }
+
+    /// Parse LKH `.par` text, the inverse of
+    /// [`to_lkh_parameter_file`](Self::to_lkh_parameter_file).
+    ///
+    /// Keywords with a typed field populate it. File names are returned
+    /// separately in [`LkhParameterFile`], and every other keyword is kept in
+    /// [`additional_parameters`](Self::additional_parameters). Keywords that
+    /// are absent keep this type's defaults, which differ from LKH's for
+    /// `RUNS` (1 instead of 10) and `TRACE_LEVEL` (0 instead of 1). Errors carry
+    /// the one-based line number.
+    pub fn from_lkh_parameter_str(text: &str) -> Result<LkhParameterFile, LkhError> {
+        parse_lkh_parameters(text)
+    }
+
+    /// Read and parse an LKH `.par` file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<LkhParameterFile, LkhError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| LkhError::ReadFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        parse_lkh_parameters(&text)
+    }
+
+    /// Write an LKH parameter file for an explicitly exported problem file.
+    ///
+    /// This is an explicit export adapter. Programmatic solves use typed
+    /// parameters directly and do not read this file.
+    pub fn write_lkh_parameter_file(
+        &self,
+        path: impl AsRef<Path>,
+        problem_file_name: &str,
+    ) -> Result<(), LkhError> {
+        let text = self.to_lkh_parameter_file(problem_file_name)?;
+        write_text(path.as_ref(), text)
+    }
+}
+
+/// How LKH balances the tours of multiple salesmen (`MTSP_OBJECTIVE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MtspObjective {
+    /// Minimize the length of the longest tour.
+    MinMax,
+    /// Minimize the number of nodes on the largest tour.
+    MinMaxSize,
+    /// Minimize the total length of all tours.
+    MinSum,
+}
+
+impl MtspObjective {
+    /// LKH parameter value, e.g. `"MINMAX"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MinMax => "MINMAX",
+            Self::MinMaxSize => "MINMAX_SIZE",
+            Self::MinSum => "MINSUM",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [Self::MinMax, Self::MinMaxSize, Self::MinSum]
+            .into_iter()
+            .find(|objective| objective.as_str().eq_ignore_ascii_case(value))
+    }
+}
+
+/// Multiple traveling salesmen settings for [`SearchParameters::mtsp`].
+///
+/// All salesmen start and end at the problem's depot, node 1 unless the
+/// problem has a `DEPOT_SECTION`. Use
+/// [`SolveReport::mtsp_solution`](crate::SolveReport::mtsp_solution) to read
+/// the tour of each salesman.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MtspSettings {
+    /// Number of salesmen (`SALESMEN`).
+    pub salesmen: i32,
+    /// Objective. `None` keeps LKH's default, which minimizes the total
+    /// length without penalizing tours outside the size bounds.
+    pub objective: Option<MtspObjective>,
+    /// Fewest customers each salesman must visit (`MTSP_MIN_SIZE`). Zero
+    /// allows idle salesmen, and a negative value lets LKH derive the bound
+    /// from `max_size`. `None` keeps LKH's default of 1.
+    pub min_size: Option<i32>,
+    /// Most customers a salesman may visit (`MTSP_MAX_SIZE`). `None` leaves
+    /// the size unbounded.
+    pub max_size: Option<i32>,
+}
+
+impl MtspSettings {
+    /// Create settings for `salesmen` salesmen with LKH's defaults.
+    pub fn new(salesmen: i32) -> Self {
+        Self {
+            salesmen,
+            objective: None,
+            min_size: None,
+            max_size: None,
+        }
+    }
+
+    /// Return a copy with an explicit objective.
+    pub fn with_objective(mut self, objective: MtspObjective) -> Self {
+        self.objective = Some(objective);
+        self
+    }
+
+    /// Return a copy with a minimum tour size.
+    pub fn with_min_size(mut self, min_size: i32) -> Self {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    /// Return a copy with a maximum tour size.
+    pub fn with_max_size(mut self, max_size: i32) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    fn validate(&self) -> Result<(), LkhError> {
+        if self.salesmen <= 0 {
+            return Err(LkhError::InvalidSearchParameters(
+                "mtsp.salesmen must be positive".to_owned(),
+            ));
+        }
+        if let Some(max_size) = self.max_size {
+            if max_size <= 0 {
+                return Err(LkhError::InvalidSearchParameters(
+                    "mtsp.max_size must be positive".to_owned(),
+                ));
+            }
+            if self.min_size.is_some_and(|min_size| min_size > max_size) {
+                return Err(LkhError::InvalidSearchParameters(
+                    "mtsp.min_size must not exceed mtsp.max_size".to_owned(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How LKH divides a large instance into subproblems.
+///
+/// All variants except `TourSegment` need node coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Partitioning {
+    /// Karp's partitioning of the plane into rectangles (`KARP`).
+    Karp,
+    /// K-center clustering (`K-CENTER`).
+    KCenter,
+    /// K-means clustering (`K-MEANS`).
+    KMeans,
+    /// Rohe's random rectangles (`ROHE`).
+    Rohe,
+    /// Delaunay graph clusters (`DELAUNAY`).
+    Delaunay,
+    /// Segments of a Moore space-filling curve (`MOORE`).
+    Moore,
+    /// Segments of a Sierpinski space-filling curve (`SIERPINSKI`).
+    Sierpinski,
+    /// Consecutive segments of the subproblem tour, LKH's default.
+    TourSegment,
+}
+
+impl Partitioning {
+    /// LKH keyword, or `None` for the default tour segment partitioning.
+    fn keyword(self) -> Option<&'static str> {
+        match self {
+            Self::Karp => Some("KARP"),
+            Self::KCenter => Some("K-CENTER"),
+            Self::KMeans => Some("K-MEANS"),
+            Self::Rohe => Some("ROHE"),
+            Self::Delaunay => Some("DELAUNAY"),
+            Self::Moore => Some("MOORE"),
+            Self::Sierpinski => Some("SIERPINSKI"),
+            Self::TourSegment => None,
+        }
+    }
+}
+
+/// Subproblem partitioning settings for [`SearchParameters::subproblem`].
+///
+/// LKH improves the [`SearchParameters::subproblem_tour`] by solving
+/// subproblems of at most `size` nodes and splicing the results back in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubproblemSettings {
+    /// Most nodes per subproblem, at least 3.
+    pub size: i32,
+    /// How nodes are grouped into subproblems.
+    pub partitioning: Partitioning,
+    /// Also solve subproblems along the borders between subproblems
+    /// (`BORDERS`).
+    pub borders: bool,
+    /// Retry subproblems that did not improve in compressed form
+    /// (`COMPRESSED`).
+    pub compressed: bool,
+}
+
+impl SubproblemSettings {
+    /// Create settings for subproblems of at most `size` nodes.
+    pub fn new(size: i32, partitioning: Partitioning) -> Self {
+        Self {
+            size,
+            partitioning,
+            borders: false,
+            compressed: false,
+        }
+    }
+
+    /// Return a copy that also solves border subproblems.
+    pub fn with_borders(mut self, borders: bool) -> Self {
+        self.borders = borders;
+        self
+    }
+
+    /// Return a copy that retries subproblems in compressed form.
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    fn validate(&self) -> Result<(), LkhError> {
+        if self.size < 3 {
+            return Err(LkhError::InvalidSearchParameters(
+                "subproblem.size must be at least 3".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse(key: &str, value: &str) -> Result<Self, LkhError> {
+        #[derive(Clone, Copy)]
+        enum Token {
+            Partitioning(Partitioning),
+            Borders,
+            Compressed,
+        }
+
+        let mut tokens = value.split_whitespace();
+        let size = parse_parameter(key, tokens.next().unwrap_or_default())?;
+        let mut settings = Self::new(size, Partitioning::TourSegment);
+        for (index, token) in tokens.enumerate() {
+            let mut choices = vec![
+                ("BORDERS", 1, Token::Borders),
+                ("COMPRESSED", 1, Token::Compressed),
+            ];
+            // Only the first qualifier may name a partitioning.
+            if index == 0 {
+                choices.splice(
+                    0..0,
+                    [
+                        ("DELAUNAY", 1, Partitioning::Delaunay),
+                        ("KARP", 2, Partitioning::Karp),
+                        ("K-CENTER", 3, Partitioning::KCenter),
+                        ("K-MEANS", 3, Partitioning::KMeans),
+                        ("MOORE", 1, Partitioning::Moore),
+                        ("ROHE", 1, Partitioning::Rohe),
+                        ("SIERPINSKI", 1, Partitioning::Sierpinski),
+                    ]
+                    .map(|(name, length, partitioning)| {
+                        (name, length, Token::Partitioning(partitioning))
+                    }),
+                );
+            }
+            match parse_abbreviation(key, token, &choices)? {
+                Token::Partitioning(partitioning) => settings.partitioning = partitioning,
+                Token::Borders => settings.borders = true,
+                Token::Compressed => settings.compressed = true,
+            }
+        }
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+impl fmt::Display for SubproblemSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.size)?;
+        if let Some(keyword) = self.partitioning.keyword() {
+            write!(f, " {keyword}")?;
+        }
+        if self.borders {
+            f.write_str(" BORDERS")?;
+        }
+        if self.compressed {
+            f.write_str(" COMPRESSED")?;
+        }
+        Ok(())
+    }
+}
+
+/// Candidate edge selection (`CANDIDATE_SET_TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CandidateSetType {
+    /// Alpha-nearness, LKH's default.
+    Alpha,
+    /// Edges of the Delaunay graph, complemented by nearest neighbors unless
+    /// `pure` is set. Requires coordinates.
+    Delaunay {
+        /// Use only Delaunay edges (`PURE`).
+        pure: bool,
+    },
+    /// Nearest neighbors. Requires coordinates.
+    NearestNeighbor,
+    /// Edges found by POPMUSIC.
+    Popmusic,
+    /// Nearest neighbors in each quadrant. Requires coordinates.
+    Quadrant,
+}
+
+impl CandidateSetType {
+    fn parse(key: &str, value: &str) -> Result<Self, LkhError> {
+        let mut tokens = value.split_whitespace();
+        let kind = parse_abbreviation(
+            key,
+            tokens.next().unwrap_or_default(),
+            &[
+                ("ALPHA", 1, Self::Alpha),
+                ("DELAUNAY", 1, Self::Delaunay { pure: false }),
+                ("NEAREST-NEIGHBOR", 1, Self::NearestNeighbor),
+                ("POPMUSIC", 1, Self::Popmusic),
+                ("QUADRANT", 1, Self::Quadrant),
+            ],
+        )?;
+        match (kind, tokens.next()) {
+            (_, None) => Ok(kind),
+            (Self::Delaunay { .. }, Some(token)) => {
+                parse_abbreviation(key, token, &[("PURE", 1, Self::Delaunay { pure: true })])
+            }
+            (_, Some(token)) => Err(LkhError::InvalidSearchParameters(format!(
+                "{key}: unexpected {token:?}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for CandidateSetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Alpha => "ALPHA",
+            Self::Delaunay { pure: false } => "DELAUNAY",
+            Self::Delaunay { pure: true } => "DELAUNAY PURE",
+            Self::NearestNeighbor => "NEAREST-NEIGHBOR",
+            Self::Popmusic => "POPMUSIC",
+            Self::Quadrant => "QUADRANT",
+        })
+    }
+}
+
+/// Selection of extra candidate edges (`EXTRA_CANDIDATE_SET_TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtraCandidateSetType {
+    /// Nearest neighbors.
+    NearestNeighbor,
+    /// Edges found by POPMUSIC.
+    Popmusic,
+    /// Nearest neighbors in each quadrant, LKH's default.
+    Quadrant,
+}
+
+impl ExtraCandidateSetType {
+    fn parse(key: &str, value: &str) -> Result<Self, LkhError> {
+        parse_abbreviation(
+            key,
+            value,
+            &[
+                ("NEAREST-NEIGHBOR", 1, Self::NearestNeighbor),
+                ("POPMUSIC", 1, Self::Popmusic),
+                ("QUADRANT", 1, Self::Quadrant),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for ExtraCandidateSetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NearestNeighbor => "NEAREST-NEIGHBOR",
+            Self::Popmusic => "POPMUSIC",
+            Self::Quadrant => "QUADRANT",
+        })
+    }
+}
+
+/// Construction of the initial tour of each run (`INITIAL_TOUR_ALGORITHM`).
+///
+/// The problem-specific algorithms only apply to instances of that type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InitialTourAlgorithm {
+    Boruvka,
+    Ctsp,
+    Cvrp,
+    Gctsp,
+    Greedy,
+    Moore,
+    Mtsp,
+    NearestNeighbor,
+    Pctsp,
+    QuickBoruvka,
+    Sierpinski,
+    Sop,
+    Tspdl,
+    /// Random walk on the candidate edges, LKH's default.
+    Walk,
+}
+
+impl InitialTourAlgorithm {
+    fn parse(key: &str, value: &str) -> Result<Self, LkhError> {
+        // Same order and minimum abbreviations as LKH's ReadParameters.
+        parse_abbreviation(
+            key,
+            value,
+            &[
+                ("BORUVKA", 1, Self::Boruvka),
+                ("CTSP", 1, Self::Ctsp),
+                ("GREEDY", 1, Self::Greedy),
+                ("GCTSP", 1, Self::Gctsp),
+                ("CVRP", 1, Self::Cvrp),
+                ("MOORE", 2, Self::Moore),
+                ("MTSP", 2, Self::Mtsp),
+                ("NEAREST-NEIGHBOR", 1, Self::NearestNeighbor),
+                ("PCTSP", 2, Self::Pctsp),
+                ("QUICK-BORUVKA", 1, Self::QuickBoruvka),
+                ("SIERPINSKI", 2, Self::Sierpinski),
+                ("SOP", 2, Self::Sop),
+                ("TSPDL", 1, Self::Tspdl),
+                ("WALK", 1, Self::Walk),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for InitialTourAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Boruvka => "BORUVKA",
+            Self::Ctsp => "CTSP",
+            Self::Cvrp => "CVRP",
+            Self::Gctsp => "GCTSP",
+            Self::Greedy => "GREEDY",
+            Self::Moore => "MOORE",
+            Self::Mtsp => "MTSP",
+            Self::NearestNeighbor => "NEAREST-NEIGHBOR",
+            Self::Pctsp => "PCTSP",
+            Self::QuickBoruvka => "QUICK-BORUVKA",
+            Self::Sierpinski => "SIERPINSKI",
+            Self::Sop => "SOP",
+            Self::Tspdl => "TSPDL",
+            Self::Walk => "WALK",
+        })
+    }
+}
+
+/// Crossover operator of the genetic algorithm (`RECOMBINATION`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Recombination {
+    /// Iterative partial transcription, LKH's default.
+    Ipt,
+    /// Generalized partition crossover 2.
+    Gpx2,
+    /// Clarist recombination.
+    Clarist,
+}
+
+impl Recombination {
+    fn parse(key: &str, value: &str) -> Result<Self, LkhError> {
+        parse_abbreviation(
+            key,
+            value,
+            &[
+                ("IPT", 1, Self::Ipt),
+                ("GPX2", 1, Self::Gpx2),
+                ("CLARIST", 1, Self::Clarist),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for Recombination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ipt => "IPT",
+            Self::Gpx2 => "GPX2",
+            Self::Clarist => "CLARIST",
+        })
+    }
+}
+
+/// Qualifier of `PATCHING_A` and `PATCHING_C`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatchingMode {
+    /// Gainful moves are only considered if all their inclusion edges are
+    /// candidate edges.
+    Restricted,
+    /// Moves need not be gainful if all their inclusion edges are candidate
+    /// edges.
+    Extended,
+}
+
+impl fmt::Display for PatchingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Restricted => "RESTRICTED",
+            Self::Extended => "EXTENDED",
+        })
+    }
+}
+
+/// Parameters of a black and white TSP (`BWTSP`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BwtspParameters {
+    /// Number of black nodes.
+    pub black_nodes: i32,
+    /// Most white nodes on a path between two black nodes.
+    pub max_white_nodes: i32,
+    /// Longest path between two black nodes. `None` leaves it unbounded.
+    pub max_length: Option<i32>,
+}
+
+impl BwtspParameters {
+    fn parse(key: &str, value: &str) -> Result<Self, LkhError> {
+        let numbers = value
+            .split_whitespace()
+            .map(|token| parse_parameter(key, token))
+            .collect::<Result<Vec<i32>, _>>()?;
+        match numbers[..] {
+            [black_nodes, max_white_nodes] => Ok(Self {
+                black_nodes,
+                max_white_nodes,
+                max_length: None,
+            }),
+            [black_nodes, max_white_nodes, max_length] => Ok(Self {
+                black_nodes,
+                max_white_nodes,
+                max_length: Some(max_length),
+            }),
+            _ => Err(LkhError::InvalidSearchParameters(format!(
+                "{key}: two or three integers expected"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for BwtspParameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.black_nodes, self.max_white_nodes)?;
+        if let Some(max_length) = self.max_length {
+            write!(f, " {max_length}")?;
         }
         Ok(())
     }
+}
+
+/// An LKH `.par` file split into search settings and the files it names.
+///
+/// Paths are returned as written in the file. LKH resolves relative paths
+/// against its working directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LkhParameterFile {
+    /// Settings for [`solve_problem`](crate::solve_problem).
+    pub parameters: SearchParameters,
+    /// `PROBLEM_FILE`.
+    pub problem_file: Option<PathBuf>,
+    /// `INITIAL_TOUR_FILE`.
+    pub initial_tour_file: Option<PathBuf>,
+    /// `INPUT_TOUR_FILE`.
+    pub input_tour_file: Option<PathBuf>,
+    /// Every `MERGE_TOUR_FILE`, in file order and without duplicates.
+    pub merge_tour_files: Vec<PathBuf>,
+    /// `TOUR_FILE`, where LKH writes the best tour.
+    pub tour_file: Option<PathBuf>,
+    /// `OUTPUT_TOUR_FILE`, where LKH writes the best tour of each run.
+    pub output_tour_file: Option<PathBuf>,
+    /// `SUBPROBLEM_TOUR_FILE`, the tour subproblem partitioning improves.
+    pub subproblem_tour_file: Option<PathBuf>,
+}
+
+fn parse_lkh_parameters(text: &str) -> Result<LkhParameterFile, LkhError> {
+    let mut file = LkhParameterFile {
+        parameters: SearchParameters::default(),
+        problem_file: None,
+        initial_tour_file: None,
+        input_tour_file: None,
+        merge_tour_files: Vec::new(),
+        tour_file: None,
+        output_tour_file: None,
+        subproblem_tour_file: None,
+    };
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let at_line = |err: LkhError| parameter_error(line_number, err);
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let end = line
+            .find(|ch: char| ch.is_whitespace() || ch == '=')
+            .unwrap_or(line.len());
+        let (key, rest) = line.split_at(end);
+        let rest = rest.trim_start();
+        let value = rest.strip_prefix('=').unwrap_or(rest).trim();
+        let key = key.to_ascii_uppercase();
+        let parameters = &mut file.parameters;
+        let path = || {
+            if value.is_empty() {
+                Err(at_line(LkhError::InvalidSearchParameters(format!(
+                    "{key} expects a file name"
+                ))))
+            } else {
+                Ok(PathBuf::from(value))
+            }
+        };
+        match key.as_str() {
+            "EOF" => break,
+            "COMMENT" => {}
+            "PROBLEM_FILE" => file.problem_file = Some(path()?),
+            "INITIAL_TOUR_FILE" => file.initial_tour_file = Some(path()?),
+            "INPUT_TOUR_FILE" => file.input_tour_file = Some(path()?),
+            "TOUR_FILE" => file.tour_file = Some(path()?),
+            "OUTPUT_TOUR_FILE" => file.output_tour_file = Some(path()?),
+            "SUBPROBLEM_TOUR_FILE" => file.subproblem_tour_file = Some(path()?),
+            "MERGE_TOUR_FILE" => {
+                let path = path()?;
+                if !file.merge_tour_files.contains(&path) {
+                    file.merge_tour_files.push(path);
+                }
+            }
+            "RUNS" => parameters.runs = parse_parameter(&key, value).map_err(at_line)?,
+            "TRACE_LEVEL" => {
+                parameters.trace_level = parse_parameter(&key, value).map_err(at_line)?;
+            }
+            "MAX_TRIALS" => {
+                parameters.max_trials = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "MOVE_TYPE" => {
+                let (move_type, special) =
+                    parse_qualified(&key, value, "SPECIAL").map_err(at_line)?;
+                parameters.move_type = Some(move_type);
+                parameters.move_type_special = special;
+            }
+            "PATCHING_C" => {
+                let (patching_c, mode) = parse_patching(&key, value).map_err(at_line)?;
+                parameters.patching_c = Some(patching_c);
+                parameters.patching_c_mode = mode;
+            }
+            "PATCHING_A" => {
+                let (patching_a, mode) = parse_patching(&key, value).map_err(at_line)?;
+                parameters.patching_a = Some(patching_a);
+                parameters.patching_a_mode = mode;
+            }
+            // Shorthand for the settings LKH-3 recommends for its special
+            // problem types.
+            "SPECIAL" => {
+                parameters.gain23 = Some(false);
+                parameters.kicks = Some(1);
+                parameters.kick_type = Some(4);
+                parameters.max_swaps = Some(0);
+                parameters.move_type = Some(5);
+                parameters.move_type_special = true;
+                parameters.population_size = Some(10);
+            }
+            "SEED" => parameters.seed = Some(parse_parameter(&key, value).map_err(at_line)?),
+            "TIME_LIMIT" => {
+                parameters.time_limit = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "TOTAL_TIME_LIMIT" => {
+                parameters.total_time_limit = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "OPTIMUM" => {
+                parameters.optimum = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "STOP_AT_OPTIMUM" => {
+                parameters.stop_at_optimum = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "SALESMEN" | "VEHICLES" => {
+                let salesmen = parse_parameter(&key, value).map_err(at_line)?;
+                parameters
+                    .mtsp
+                    .get_or_insert_with(|| MtspSettings::new(1))
+                    .salesmen = salesmen;
+            }
+            "MTSP_OBJECTIVE" => {
+                let objective = MtspObjective::parse(value).ok_or_else(|| {
+                    at_line(LkhError::InvalidSearchParameters(format!(
+                        "{key}: expected MINMAX, MINMAX_SIZE or MINSUM, got {value:?}"
+                    )))
+                })?;
+                parameters
+                    .mtsp
+                    .get_or_insert_with(|| MtspSettings::new(1))
+                    .objective = Some(objective);
+            }
+            "MTSP_MIN_SIZE" => {
+                let min_size = parse_parameter(&key, value).map_err(at_line)?;
+                parameters
+                    .mtsp
+                    .get_or_insert_with(|| MtspSettings::new(1))
+                    .min_size = Some(min_size);
+            }
+            "MTSP_MAX_SIZE" => {
+                let max_size = parse_parameter(&key, value).map_err(at_line)?;
+                parameters
+                    .mtsp
+                    .get_or_insert_with(|| MtspSettings::new(1))
+                    .max_size = Some(max_size);
+            }
+            "SUBPROBLEM_SIZE" => {
+                parameters.subproblem =
+                    Some(SubproblemSettings::parse(&key, value).map_err(at_line)?);
+            }
+            "ALPHA" => {
+                parameters.alpha = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "ASCENT_CANDIDATES" => {
+                parameters.ascent_candidates = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "BACKBONE_TRIALS" => {
+                parameters.backbone_trials = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "BACKTRACKING" => {
+                parameters.backtracking = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "BWTSP" => {
+                parameters.bwtsp = Some(BwtspParameters::parse(&key, value).map_err(at_line)?);
+            }
+            "CANDIDATE_SET_TYPE" => {
+                parameters.candidate_set_type =
+                    Some(CandidateSetType::parse(&key, value).map_err(at_line)?);
+            }
+            "DEPOT" => {
+                parameters.depot = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "DISTANCE" => {
+                parameters.distance_limit = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "DRONES" => {
+                parameters.drones = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "ENDURANCE" => {
+                parameters.endurance = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "EXCESS" => {
+                parameters.excess = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "EXTERNAL_SALESMEN" => {
+                parameters.external_salesmen = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "EXTRA_CANDIDATES" => {
+                let (value, qualified) =
+                    parse_qualified(&key, value, "SYMMETRIC").map_err(at_line)?;
+                parameters.extra_candidates = Some(value);
+                parameters.extra_candidates_symmetric = qualified;
+            }
+            "EXTRA_CANDIDATE_SET_TYPE" => {
+                parameters.extra_candidate_set_type =
+                    Some(ExtraCandidateSetType::parse(&key, value).map_err(at_line)?);
+            }
+            "GAIN23" => {
+                parameters.gain23 = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "GAIN_CRITERION" => {
+                parameters.gain_criterion = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "INITIAL_PERIOD" => {
+                parameters.initial_period = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "INITIAL_STEP_SIZE" => {
+                parameters.initial_step_size = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "INITIAL_TOUR_ALGORITHM" => {
+                parameters.initial_tour_algorithm =
+                    Some(InitialTourAlgorithm::parse(&key, value).map_err(at_line)?);
+            }
+            "INITIAL_TOUR_FRACTION" => {
+                parameters.initial_tour_fraction =
+                    Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "K" => {
+                parameters.k = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "KICKS" => {
+                parameters.kicks = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "KICK_TYPE" => {
+                parameters.kick_type = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "MAKESPAN" => {
+                parameters.makespan = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "MAX_BREADTH" => {
+                parameters.max_breadth = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "MAX_CANDIDATES" => {
+                let (value, qualified) =
+                    parse_qualified(&key, value, "SYMMETRIC").map_err(at_line)?;
+                parameters.max_candidates = Some(value);
+                parameters.max_candidates_symmetric = qualified;
+            }
+            "MAX_SWAPS" => {
+                parameters.max_swaps = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "NONSEQUENTIAL_MOVE_TYPE" => {
+                parameters.nonsequential_move_type =
+                    Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "POPMUSIC_INITIAL_TOUR" => {
+                parameters.popmusic_initial_tour =
+                    Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "POPMUSIC_MAX_NEIGHBORS" => {
+                parameters.popmusic_max_neighbors =
+                    Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "POPMUSIC_SAMPLE_SIZE" => {
+                parameters.popmusic_sample_size =
+                    Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "POPMUSIC_SOLUTIONS" => {
+                parameters.popmusic_solutions =
+                    Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "POPMUSIC_TRIALS" => {
+                parameters.popmusic_trials = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "POPULATION_SIZE" => {
+                parameters.population_size = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "PRECISION" => {
+                parameters.precision = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "PROBABILITY" => {
+                parameters.probability = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "RECOMBINATION" => {
+                parameters.recombination =
+                    Some(Recombination::parse(&key, value).map_err(at_line)?);
+            }
+            "RESTRICTED_SEARCH" => {
+                parameters.restricted_search = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "SCALE" => {
+                parameters.scale = Some(parse_parameter(&key, value).map_err(at_line)?);
+            }
+            "SUBGRADIENT" => {
+                parameters.subgradient = Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            "SUBSEQUENT_MOVE_TYPE" => {
+                let (value, qualified) =
+                    parse_qualified(&key, value, "SPECIAL").map_err(at_line)?;
+                parameters.subsequent_move_type = Some(value);
+                parameters.subsequent_move_type_special = qualified;
+            }
+            "SUBSEQUENT_PATCHING" => {
+                parameters.subsequent_patching =
+                    Some(parse_yes_or_no(&key, value).map_err(at_line)?);
+            }
+            _ => {
+                let key = validate_parameter_key(key).map_err(at_line)?;
+                let value = validate_parameter_value(value.to_owned()).map_err(at_line)?;
+                parameters.additional_parameters.push((key, value));
+            }
+        }
+    }
+    file.parameters.validate()?;
+    Ok(file)
+}
+
+fn parse_parameter<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, LkhError> {
+    value
+        .parse()
+        .map_err(|_| LkhError::InvalidSearchParameters(format!("{key}: invalid value {value:?}")))
+}
+
+/// Accept any prefix of `YES` or `NO`, like LKH's `ReadYesOrNo`.
+fn parse_yes_or_no(key: &str, value: &str) -> Result<bool, LkhError> {
+    let upper = value.to_ascii_uppercase();
+    if !upper.is_empty() && "YES".starts_with(&upper) {
+        Ok(true)
+    } else if !upper.is_empty() && "NO".starts_with(&upper) {
+        Ok(false)
+    } else {
+        Err(LkhError::InvalidSearchParameters(format!(
+            "{key}: YES or NO expected"
+        )))
+    }
+}
+
+fn yes_or_no(value: bool) -> &'static str {
+    if value {
+        "YES"
+    } else {
+        "NO"
+    }
+}
 
-    /// Render an LKH parameter file in memory for a given problem file name.
-    pub fn to_lkh_parameter_file(&self, problem_file_name: &str) -> Result<String, LkhError> {
-        validate_problem_file_name(problem_file_name)?;
-        self.validate()?;
+/// Match a value that LKH accepts abbreviated to any prefix of at least the
+/// given length. Choices are tried in order, as in LKH's `ReadParameters`.
+fn parse_abbreviation<T: Copy>(
+    key: &str,
+    value: &str,
+    choices: &[(&str, usize, T)],
+) -> Result<T, LkhError> {
+    let upper = value.to_ascii_uppercase();
+    choices
+        .iter()
+        .find(|(name, min_length, _)| upper.len() >= *min_length && name.starts_with(&upper))
+        .map(|&(_, _, choice)| choice)
+        .ok_or_else(|| {
+            let names: Vec<_> = choices.iter().map(|(name, _, _)| *name).collect();
+            LkhError::InvalidSearchParameters(format!(
+                "{key}: expected {}, got {value:?}",
+                names.join(", ")
+            ))
+        })
+}
 
-        let mut output = String::new();
-        writeln!(&mut output, "PROBLEM_FILE = {problem_file_name}").unwrap();
-        writeln!(&mut output, "RUNS = {}", self.runs).unwrap();
-        writeln!(&mut output, "TRACE_LEVEL = {}", self.trace_level).unwrap();
-        if let Some(max_trials) = self.max_trials {
-            writeln!(&mut output, "MAX_TRIALS = {max_trials}").unwrap();
-        }
-        if let Some(move_type) = self.move_type {
-            writeln!(&mut output, "MOVE_TYPE = {move_type}").unwrap();
-        }
-        if let Some(patching_c) = self.patching_c {
-            writeln!(&mut output, "PATCHING_C = {patching_c}").unwrap();
-        }
-        if let Some(patching_a) = self.patching_a {
-            writeln!(&mut output, "PATCHING_A = {patching_a}").unwrap();
-        }
-        if let Some(seed) = self.seed {
-            writeln!(&mut output, "SEED = {seed}").unwrap();
-        }
-        if let Some(time_limit) = self.time_limit {
-            writeln!(&mut output, "TIME_LIMIT = {time_limit}").unwrap();
-        }
-        if let Some(total_time_limit) = self.total_time_limit {
-            writeln!(&mut output, "TOTAL_TIME_LIMIT = {total_time_limit}").unwrap();
-        }
-        if let Some(optimum) = self.optimum {
-            writeln!(&mut output, "OPTIMUM = {optimum}").unwrap();
-        }
-        if let Some(stop_at_optimum) = self.stop_at_optimum {
-            let value = if stop_at_optimum { "YES" } else { "NO" };
-            writeln!(&mut output, "STOP_AT_OPTIMUM = {value}").unwrap();
-        }
-        for (key, value) in &self.additional_parameters {
-            writeln!(&mut output, "{key} = {value}").unwrap();
-        }
-        Ok(output)
+/// Split a value like `5 SPECIAL` into its number and whether the optional
+/// qualifier is present.
+fn parse_qualified<T: std::str::FromStr>(
+    key: &str,
+    value: &str,
+    qualifier: &str,
+) -> Result<(T, bool), LkhError> {
+    let (number, rest) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+    let number = parse_parameter(key, number)?;
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Ok((number, false));
     }
+    parse_abbreviation(key, rest, &[(qualifier, 1, true)]).map(|_| (number, true))
+}
 
-    /// Write an LKH parameter file for an explicitly exported problem file.
-    ///
-    /// This is an explicit export adapter. Programmatic solves use typed
-    /// parameters directly and do not read this file.
-    pub fn write_lkh_parameter_file(
-        &self,
-        path: impl AsRef<Path>,
-        problem_file_name: &str,
-    ) -> Result<(), LkhError> {
-        let text = self.to_lkh_parameter_file(problem_file_name)?;
-        write_text(path.as_ref(), text)
+fn parse_patching(key: &str, value: &str) -> Result<(i32, Option<PatchingMode>), LkhError> {
+    let (number, rest) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+    let number = parse_parameter(key, number)?;
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Ok((number, None));
     }
+    let mode = parse_abbreviation(
+        key,
+        rest,
+        &[
+            ("RESTRICTED", 1, PatchingMode::Restricted),
+            ("EXTENDED", 1, PatchingMode::Extended),
+        ],
+    )?;
+    Ok((number, Some(mode)))
+}
+
+fn patching_mode_suffix(mode: Option<PatchingMode>) -> String {
+    mode.map(|mode| format!(" {mode}")).unwrap_or_default()
+}
+
+/// Attach a line number to a validation error raised while parsing a `.par`
+/// file.
+fn parameter_error(line: usize, err: LkhError) -> LkhError {
+    let message = match err {
+        LkhError::InvalidSearchParameters(message) => message,
+        other => other.to_string(),
+    };
+    LkhError::ParameterParse { line, message }
 }
 
 fn write_text(path: &Path, text: String) -> Result<(), LkhError> {
@@ -768,6 +3916,285 @@ fn validate_points(points: &[Point2d]) -> Result<(), LkhError> {
     Ok(())
 }
 
+/// `NODE_COORD_SECTION` lines for `points` under `metric`.
+fn coordinate_lines(metric: EdgeWeightType, points: &Coordinates) -> Result<Vec<String>, LkhError> {
+    if points.len() < 2 {
+        return Err(LkhError::InvalidProblem(
+            "at least two points are required".to_owned(),
+        ));
+    }
+    let values: Vec<Vec<f64>> = match (metric, points) {
+        (EdgeWeightType::Euc3d, Coordinates::Spatial(points)) => points
+            .iter()
+            .map(|point| vec![point.x, point.y, point.z])
+            .collect(),
+        (EdgeWeightType::Geo | EdgeWeightType::Geom, Coordinates::Geographic(points)) => {
+            for (index, point) in points.iter().enumerate() {
+                if !(-90.0..=90.0).contains(&point.lat) || !(-180.0..=180.0).contains(&point.lon) {
+                    return Err(LkhError::InvalidProblem(format!(
+                        "point {} must have a latitude in [-90, 90] and a longitude in [-180, 180]",
+                        index + 1
+                    )));
+                }
+            }
+            let convert = if metric == EdgeWeightType::Geo {
+                degrees_minutes
+            } else {
+                |degrees| degrees
+            };
+            points
+                .iter()
+                .map(|point| vec![convert(point.lat), convert(point.lon)])
+                .collect()
+        }
+        (
+            EdgeWeightType::Euc2d
+            | EdgeWeightType::Man2d
+            | EdgeWeightType::Max2d
+            | EdgeWeightType::Ceil2d
+            | EdgeWeightType::Att,
+            Coordinates::Planar(points),
+        ) => points.iter().map(|point| vec![point.x, point.y]).collect(),
+        _ => {
+            let expected = match metric {
+                EdgeWeightType::Euc3d => "Point3d",
+                EdgeWeightType::Geo | EdgeWeightType::Geom => "GeoPoint",
+                _ => "Point2d",
+            };
+            return Err(LkhError::InvalidProblem(format!(
+                "{metric} coordinates must be given as {expected}s"
+            )));
+        }
+    };
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, values)| {
+            if values.iter().any(|value| !value.is_finite()) {
+                return Err(LkhError::InvalidProblem(format!(
+                    "point {} must contain finite coordinates",
+                    index + 1
+                )));
+            }
+            let mut line = (index + 1).to_string();
+            for value in values {
+                write!(line, " {value}").unwrap();
+            }
+            Ok(line)
+        })
+        .collect()
+}
+
+/// Decimal degrees in TSPLIB's `DDD.MM` form, where the fraction holds
+/// minutes.
+fn degrees_minutes(degrees: f64) -> f64 {
+    let whole = degrees.trunc();
+    whole + (degrees - whole) * 0.6
+}
+
+fn point_lines(points: &[Point2d]) -> Vec<String> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| format!("{} {} {}", index + 1, point.x, point.y))
+        .collect()
+}
+
+fn matrix_lines(matrix: &[Vec<i64>]) -> Vec<String> {
+    matrix
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+fn validate_distances(distances: &Distances) -> Result<(), LkhError> {
+    match distances {
+        Distances::Euclidean2d(points) => validate_points(points),
+        Distances::Matrix(matrix) => validate_matrix(matrix, false),
+    }
+}
+
+fn validate_depot(depot: usize, dimension: usize) -> Result<(), LkhError> {
+    if depot == 0 || depot > dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "depot {depot} is not a node id between 1 and {dimension}"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_cvrp(spec: &CvrpSpec) -> Result<(), LkhError> {
+    validate_distances(&spec.distances)?;
+    let dimension = spec.distances.dimension();
+    validate_depot(spec.depot, dimension)?;
+    validate_capacity(spec.capacity)?;
+    if spec.demands.len() != dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "demands has length {}, expected one demand per node ({dimension})",
+            spec.demands.len()
+        )));
+    }
+    for (index, &demand) in spec.demands.iter().enumerate() {
+        if demand < 0 || demand > spec.capacity {
+            return Err(LkhError::InvalidProblem(format!(
+                "demand {demand} of node {} must be between 0 and the capacity {}",
+                index + 1,
+                spec.capacity
+            )));
+        }
+    }
+    if spec.demands[spec.depot - 1] != 0 {
+        return Err(LkhError::InvalidProblem(format!(
+            "depot {} must have zero demand",
+            spec.depot
+        )));
+    }
+    if let Some(vehicles) = spec.vehicles {
+        validate_vehicles(vehicles, dimension)?;
+        let total_demand: i64 = spec.demands.iter().sum();
+        let fleet_capacity = spec.capacity * vehicles as i64;
+        if total_demand > fleet_capacity {
+            return Err(LkhError::InvalidProblem(format!(
+                "total demand {total_demand} exceeds the fleet capacity {fleet_capacity}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn depot_lines(depot: usize) -> [String; 2] {
+    [depot.to_string(), "-1".to_owned()]
+}
+
+fn service_time(service_times: &[f64], index: usize) -> f64 {
+    service_times.get(index).copied().unwrap_or(0.0)
+}
+
+fn validate_schedule(
+    time_windows: &[TimeWindow],
+    service_times: &[f64],
+    depot: usize,
+    dimension: usize,
+) -> Result<(), LkhError> {
+    if time_windows.len() != dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "time_windows has length {}, expected one window per node ({dimension})",
+            time_windows.len()
+        )));
+    }
+    for (index, window) in time_windows.iter().enumerate() {
+        if !window.earliest.is_finite() || !window.latest.is_finite() {
+            return Err(LkhError::InvalidProblem(format!(
+                "time window of node {} must be finite",
+                index + 1
+            )));
+        }
+        if window.earliest > window.latest {
+            return Err(LkhError::InvalidProblem(format!(
+                "time window of node {} starts at {} after it ends at {}",
+                index + 1,
+                window.earliest,
+                window.latest
+            )));
+        }
+    }
+    let horizon = time_windows[depot - 1];
+    for (index, window) in time_windows.iter().enumerate() {
+        if window.earliest < horizon.earliest || window.latest > horizon.latest {
+            return Err(LkhError::InvalidProblem(format!(
+                "time window [{}, {}] of node {} is outside the depot horizon [{}, {}]",
+                window.earliest,
+                window.latest,
+                index + 1,
+                horizon.earliest,
+                horizon.latest
+            )));
+        }
+    }
+    if !service_times.is_empty() && service_times.len() != dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "service_times has length {}, expected none or one per node ({dimension})",
+            service_times.len()
+        )));
+    }
+    for (index, &time) in service_times.iter().enumerate() {
+        if !time.is_finite() || time < 0.0 {
+            return Err(LkhError::InvalidProblem(format!(
+                "service time {time} of node {} must be finite and non-negative",
+                index + 1
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_pdptw(spec: &PdptwSpec) -> Result<(), LkhError> {
+    validate_distances(&spec.distances)?;
+    let dimension = spec.distances.dimension();
+    validate_depot(spec.depot, dimension)?;
+    validate_capacity(spec.capacity)?;
+    validate_schedule(
+        &spec.time_windows,
+        &spec.service_times,
+        spec.depot,
+        dimension,
+    )?;
+    if let Some(vehicles) = spec.vehicles {
+        validate_vehicles(vehicles, dimension)?;
+    }
+    let mut paired = HashSet::new();
+    for request in &spec.requests {
+        for node in [request.pickup, request.delivery] {
+            if node == 0 || node > dimension {
+                return Err(LkhError::InvalidProblem(format!(
+                    "pickup and delivery node {node} is not a node id between 1 and {dimension}"
+                )));
+            }
+            if node == spec.depot {
+                return Err(LkhError::InvalidProblem(format!(
+                    "depot {node} cannot be a pickup or delivery node"
+                )));
+            }
+            if !paired.insert(node) {
+                return Err(LkhError::InvalidProblem(format!(
+                    "node {node} appears in more than one pickup and delivery pair"
+                )));
+            }
+        }
+        if request.quantity < 0 || request.quantity > spec.capacity {
+            return Err(LkhError::InvalidProblem(format!(
+                "quantity {} from node {} to node {} must be between 0 and the capacity {}",
+                request.quantity, request.pickup, request.delivery, spec.capacity
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_capacity(capacity: i64) -> Result<(), LkhError> {
+    if capacity <= 0 || capacity > i64::from(i32::MAX) {
+        return Err(LkhError::InvalidProblem(
+            "capacity must be between 1 and i32::MAX".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_vehicles(vehicles: usize, dimension: usize) -> Result<(), LkhError> {
+    if vehicles == 0 || vehicles >= dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "vehicles must be between 1 and {}",
+            dimension - 1
+        )));
+    }
+    Ok(())
+}
+
 fn validate_problem_key(key: String) -> Result<String, LkhError> {
     if key.is_empty()
         || !key
@@ -804,6 +4231,244 @@ fn validate_section_line(line: String) -> Result<String, LkhError> {
     Ok(line)
 }
 
+/// Check a section of `id value...` lines: one line per node, distinct ids
+/// in range, and at least `values` numbers after each id.
+fn validate_node_lines(
+    key: &str,
+    lines: &[String],
+    dimension: usize,
+    values: usize,
+) -> Result<(), LkhError> {
+    let lines: Vec<&String> = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.len() != dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "{key} has {} lines, expected one per node ({dimension})",
+            lines.len()
+        )));
+    }
+    let mut seen = vec![false; dimension];
+    for line in lines {
+        let mut tokens = line.split_whitespace();
+        let id = tokens
+            .next()
+            .and_then(|id| id.parse::<usize>().ok())
+            .filter(|id| (1..=dimension).contains(id))
+            .ok_or_else(|| {
+                LkhError::InvalidProblem(format!(
+                    "{key}: {line:?} does not start with a node id between 1 and {dimension}"
+                ))
+            })?;
+        if std::mem::replace(&mut seen[id - 1], true) {
+            return Err(LkhError::InvalidProblem(format!(
+                "{key}: node {id} occurs twice"
+            )));
+        }
+        let numbers = tokens.map(str::parse::<f64>).collect::<Result<Vec<_>, _>>();
+        if !numbers.is_ok_and(|numbers| numbers.len() >= values) {
+            return Err(LkhError::InvalidProblem(format!(
+                "{key}: node {id} needs {values} numeric values, got {line:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// All numbers of a section, which LKH reads regardless of line breaks.
+fn section_numbers(key: &str, lines: &[String]) -> Result<Vec<f64>, LkhError> {
+    lines
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| LkhError::InvalidProblem(format!("{key}: {token:?} is not a number")))
+        })
+        .collect()
+}
+
+/// Number of weights in an `EDGE_WEIGHT_SECTION` of the given format.
+fn explicit_weight_count(format: &str, dimension: usize) -> Result<usize, LkhError> {
+    EdgeWeightFormat::parse(format)
+        .map(|format| format.value_count(dimension))
+        .ok_or_else(|| {
+            LkhError::InvalidProblem(format!(
+                "EDGE_WEIGHT_FORMAT: {format} cannot be used with an EDGE_WEIGHT_SECTION"
+            ))
+        })
+}
+
+/// Check a flat weight list and split it into one line per listed row.
+fn explicit_weight_lines(
+    format: EdgeWeightFormat,
+    dimension: usize,
+    weights: &[i64],
+) -> Result<Vec<String>, LkhError> {
+    validate_dimension(dimension)?;
+    let expected = format.value_count(dimension);
+    if weights.len() != expected {
+        return Err(LkhError::InvalidProblem(format!(
+            "{format} with dimension {dimension} needs {expected} weights, got {}",
+            weights.len()
+        )));
+    }
+    if let Some(index) = weights
+        .iter()
+        .position(|&weight| !(0..=i64::from(i32::MAX)).contains(&weight))
+    {
+        return Err(LkhError::InvalidProblem(format!(
+            "weight {} must be between 0 and i32::MAX",
+            index + 1
+        )));
+    }
+    if format == EdgeWeightFormat::FullMatrix {
+        for i in 0..dimension {
+            for j in i + 1..dimension {
+                if weights[i * dimension + j] != weights[j * dimension + i] {
+                    return Err(LkhError::InvalidProblem(format!(
+                        "symmetric distance matrix entries ({}, {}) and ({}, {}) differ",
+                        i + 1,
+                        j + 1,
+                        j + 1,
+                        i + 1
+                    )));
+                }
+            }
+        }
+    }
+    let mut rest = weights;
+    let mut lines = Vec::new();
+    for (line, length) in format.line_lengths(dimension).enumerate() {
+        let (values, next) = rest.split_at(length);
+        rest = next;
+        if let Some(offset) = format.diagonal_offset(length, line) {
+            if values[offset] != 0 {
+                return Err(LkhError::InvalidProblem(format!(
+                    "{format} diagonal entry in line {} must be zero",
+                    line + 1
+                )));
+            }
+        }
+        lines.push(
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+    }
+    Ok(lines)
+}
+
+/// Check a symmetric matrix of fractional distances and return its largest
+/// entry.
+fn validate_float_matrix(matrix: &[Vec<f64>]) -> Result<f64, LkhError> {
+    let dimension = matrix.len();
+    validate_dimension(dimension)?;
+    let mut largest = 0.0_f64;
+    for (i, row) in matrix.iter().enumerate() {
+        if row.len() != dimension {
+            return Err(LkhError::InvalidProblem(format!(
+                "distance matrix row {} has length {}, expected {}",
+                i + 1,
+                row.len(),
+                dimension
+            )));
+        }
+        for (j, &value) in row.iter().enumerate() {
+            if !value.is_finite() || value < 0.0 {
+                return Err(LkhError::InvalidProblem(format!(
+                    "distance matrix entry ({}, {}) must be finite and non-negative",
+                    i + 1,
+                    j + 1
+                )));
+            }
+            largest = largest.max(value);
+        }
+        if row[i] != 0.0 {
+            return Err(LkhError::InvalidProblem(format!(
+                "distance matrix diagonal entry ({}, {}) must be zero",
+                i + 1,
+                i + 1
+            )));
+        }
+    }
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate().skip(i + 1) {
+            if value != matrix[j][i] {
+                return Err(LkhError::InvalidProblem(format!(
+                    "symmetric distance matrix entries ({}, {}) and ({}, {}) differ",
+                    i + 1,
+                    j + 1,
+                    j + 1,
+                    i + 1
+                )));
+            }
+        }
+    }
+    Ok(largest)
+}
+
+/// The `SCALE` for a matrix whose largest distance is `largest`.
+///
+/// LKH treats `INT_MAX / 2 / PRECISION` as an infinite distance, so scaled
+/// distances have to stay below it. The scale is chosen for the default
+/// precision of 100; solves check it again against their own.
+fn float_matrix_scale(largest: f64, scaling: Scaling) -> Result<i32, LkhError> {
+    let limit = f64::from(i32::MAX / 2 / 100);
+    let scale = match scaling {
+        Scaling::Auto => {
+            let mut scale = 1_i32;
+            while largest * f64::from(scale) * 10.0 < limit && scale < 1_000_000_000 {
+                scale *= 10;
+            }
+            scale
+        }
+        Scaling::Factor(factor) => {
+            if !(factor.fract() == 0.0 && (1.0..=f64::from(i32::MAX)).contains(&factor)) {
+                return Err(LkhError::InvalidProblem(format!(
+                    "scale factor must be a whole number between 1 and i32::MAX, got {factor}"
+                )));
+            }
+            factor as i32
+        }
+    };
+    if (largest * f64::from(scale)).round() >= limit {
+        return Err(LkhError::InvalidProblem(format!(
+            "distance {largest} times SCALE {scale} does not stay below {limit}, \
+             the largest distance LKH handles at PRECISION 100"
+        )));
+    }
+    Ok(scale)
+}
+
+/// Check one undirected edge of a sparse graph.
+fn validate_edge(
+    dimension: usize,
+    i: usize,
+    j: usize,
+    seen: &mut HashSet<(usize, usize)>,
+) -> Result<(), LkhError> {
+    if !(1..=dimension).contains(&i) || !(1..=dimension).contains(&j) {
+        return Err(LkhError::InvalidProblem(format!(
+            "edge ({i}, {j}) needs node ids between 1 and {dimension}"
+        )));
+    }
+    if i == j {
+        return Err(LkhError::InvalidProblem(format!(
+            "edge ({i}, {j}) is a loop"
+        )));
+    }
+    if !seen.insert((i.min(j), i.max(j))) {
+        return Err(LkhError::InvalidProblem(format!(
+            "edge ({i}, {j}) occurs twice"
+        )));
+    }
+    Ok(())
+}
+
 fn validate_matrix(matrix: &[Vec<i64>], asymmetric: bool) -> Result<(), LkhError> {
     let dimension = matrix.len();
     if dimension < 2 {
@@ -845,9 +4510,9 @@ fn validate_matrix(matrix: &[Vec<i64>], asymmetric: bool) -> Result<(), LkhError
         }
     }
     if !asymmetric {
-        for i in 0..dimension {
-            for j in (i + 1)..dimension {
-                if matrix[i][j] != matrix[j][i] {
+        for (i, row) in matrix.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate().skip(i + 1) {
+                if value != matrix[j][i] {
                     return Err(LkhError::InvalidProblem(format!(
                         "symmetric distance matrix entries ({}, {}) and ({}, {}) differ",
                         i + 1,
@@ -873,6 +4538,41 @@ fn validate_optional_seconds(name: &str, value: Option<f64>) -> Result<(), LkhEr
     Ok(())
 }
 
+fn validate_special_move(
+    name: &str,
+    move_type: Option<i32>,
+    special: bool,
+) -> Result<(), LkhError> {
+    if special && !matches!(move_type, Some(3 | 5)) {
+        return Err(LkhError::InvalidSearchParameters(format!(
+            "{name} must be 3 or 5 for special moves"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_tour(name: &str, tour: &[usize]) -> Result<(), LkhError> {
+    if tour.len() < 2 {
+        return Err(LkhError::InvalidSearchParameters(format!(
+            "{name} must contain at least two nodes"
+        )));
+    }
+    let mut seen = HashSet::with_capacity(tour.len());
+    for &node in tour {
+        if node == 0 || node > i32::MAX as usize {
+            return Err(LkhError::InvalidSearchParameters(format!(
+                "{name} contains invalid node id {node}"
+            )));
+        }
+        if !seen.insert(node) {
+            return Err(LkhError::InvalidSearchParameters(format!(
+                "{name} visits node {node} twice"
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn validate_problem_file_name(value: &str) -> Result<(), LkhError> {
     if value.trim().is_empty() {
         return Err(LkhError::InvalidSearchParameters(
@@ -6,7 +6,7 @@
 
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyList};
 use pyo3::Bound;
 
 #[pymodule]
@@ -39,10 +39,43 @@ mod _native {
     fn report_to_dict(py: Python<'_>, report: crate::SolveReport) -> PyResult<Py<PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("best_cost", report.best_cost)?;
+        dict.set_item("best_cost_unscaled", report.best_cost_unscaled)?;
         dict.set_item("best_penalty", report.best_penalty)?;
         dict.set_item("runs", report.runs)?;
         dict.set_item("dimension", report.dimension)?;
         dict.set_item("tour", report.tour)?;
+        dict.set_item("termination", report.termination.as_str())?;
+        dict.set_item("lower_bound", report.lower_bound)?;
+        dict.set_item("lower_bound_gap", report.lower_bound_gap)?;
+        dict.set_item("optimum", report.optimum)?;
+        dict.set_item("gap", report.gap)?;
+        dict.set_item(
+            "preprocessing_time",
+            report.preprocessing_time.as_secs_f64(),
+        )?;
+        dict.set_item("wall_time", report.wall_time.as_secs_f64())?;
+        let run_results = PyList::empty(py);
+        for result in &report.run_results {
+            let entry = PyDict::new(py);
+            entry.set_item("run", result.run)?;
+            entry.set_item("cost", result.cost)?;
+            entry.set_item("penalty", result.penalty)?;
+            entry.set_item("trials", result.trials)?;
+            entry.set_item("time", result.time.as_secs_f64())?;
+            run_results.append(entry)?;
+        }
+        dict.set_item("run_results", run_results)?;
+        let routes = PyList::empty(py);
+        for route in report.routes() {
+            let entry = PyDict::new(py);
+            entry.set_item("vehicle", route.vehicle)?;
+            entry.set_item("nodes", route.nodes.clone())?;
+            entry.set_item("cost", route.cost)?;
+            entry.set_item("load", route.load)?;
+            entry.set_item("duration", route.duration)?;
+            routes.append(entry)?;
+        }
+        dict.set_item("routes", routes)?;
         Ok(dict.into())
     }
 
@@ -123,14 +156,25 @@ mod _native {
             | crate::LkhError::NonUtf8Path(_)
             | crate::LkhError::Canonicalize { .. }
             | crate::LkhError::WriteFile { .. }
+            | crate::LkhError::ReadFile { .. }
             | crate::LkhError::CString { .. }
             | crate::LkhError::InvalidProblem(_)
+            | crate::LkhError::TsplibParse { .. }
+            | crate::LkhError::ParameterParse { .. }
             | crate::LkhError::InvalidSearchParameters(_)
             | crate::LkhError::UnsupportedProgrammaticParameter(_)
-            | crate::LkhError::InMemoryInitialization(_) => PyValueError::new_err(err.to_string()),
-            crate::LkhError::SolverLockPoisoned | crate::LkhError::MissingBestTour => {
-                PyRuntimeError::new_err(err.to_string())
-            }
+            | crate::LkhError::InMemoryInitialization(_)
+            | crate::LkhError::Native(_) => PyValueError::new_err(err.to_string()),
+            crate::LkhError::SolverLockPoisoned
+            | crate::LkhError::MissingBestTour
+            | crate::LkhError::Cancelled
+            | crate::LkhError::WorkerSpawn { .. }
+            | crate::LkhError::WorkerIo(_)
+            | crate::LkhError::WorkerProtocol(_)
+            | crate::LkhError::WorkerFailed(_)
+            | crate::LkhError::WorkerTimeout { .. }
+            | crate::LkhError::PoolClosed
+            | crate::LkhError::NativeAbort { .. } => PyRuntimeError::new_err(err.to_string()),
         }
     }
 }
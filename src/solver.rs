@@ -8,19 +8,31 @@
 //! programmatic path.
 
 use crate::error::LkhError;
+use crate::observer::{IgnoreEvents, SolveEvent, SolveObserver};
 use crate::problem::{RoutingProblem, SearchParameters};
 use crate::sys::*;
 use crate::{MINUS_INFINITY, PLUS_INFINITY};
-use std::ffi::CString;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString};
 use std::io;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
 use std::ptr;
-use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 static SOLVER_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
+/// Whether C's `stdout` currently writes to a [`NativeOutputCapture`].
+static NATIVE_OUTPUT_CAPTURED: AtomicBool = AtomicBool::new(false);
+
+/// Token of the in-memory solve currently holding `SOLVER_LOCK`, if any.
+static ACTIVE_CANCELLATION: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
 /// Options for running LKH from a TSPLIB parameter file.
 #[derive(Debug, Clone)]
 pub struct SolveOptions {
@@ -47,10 +59,14 @@ impl SolveOptions {
 }
 
 /// Summary copied out of LKH's global state after a solve.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SolveReport {
     /// Best objective cost found by LKH.
     pub best_cost: i64,
+    /// `best_cost` divided by the `SCALE` LKH applied to the distances, in
+    /// the units of the input. Equal to `best_cost` for unscaled problems.
+    pub best_cost_unscaled: f64,
     /// Best penalty found by LKH. Feasible VRP-like solutions usually have
     /// penalty zero.
     pub best_penalty: i64,
@@ -60,6 +76,290 @@ pub struct SolveReport {
     pub dimension: i32,
     /// Best tour copied from LKH's `BestTour` array.
     pub tour: Vec<i32>,
+    /// Why the search stopped.
+    pub termination: TerminationReason,
+    /// Lower bound computed by LKH's subgradient ascent, or zero when no
+    /// ascent ran.
+    pub lower_bound: f64,
+    /// Percentage by which `best_cost` exceeds `lower_bound`, when both are
+    /// known and positive.
+    pub lower_bound_gap: Option<f64>,
+    /// Optimum known to LKH at the end of the search: the `OPTIMUM`
+    /// parameter, or a better cost found while solving.
+    pub optimum: Option<i64>,
+    /// Percentage gap between the best solution and `optimum`, computed as in
+    /// LKH's own reports.
+    pub gap: Option<f64>,
+    /// One entry per completed run, in run order.
+    pub run_results: Vec<RunResult>,
+    /// Time spent in the subgradient ascent and candidate set generation.
+    pub preprocessing_time: Duration,
+    /// Wall-clock time of the whole solve, including reading the input.
+    pub wall_time: Duration,
+    /// Everything LKH printed to stdout during the solve, when it ran with
+    /// [`NativeOutput::Capture`].
+    pub native_log: Option<String>,
+    pub(crate) routes: Vec<Route>,
+}
+
+impl SolveReport {
+    /// Min/avg/max over [`run_results`](Self::run_results), as printed by
+    /// LKH's `PrintStatistics`. Returns `None` when no run completed.
+    pub fn statistics(&self) -> Option<RunStatistics> {
+        let first = self.run_results.first()?;
+        let count = self.run_results.len() as f64;
+        let mut statistics = RunStatistics {
+            successes: 0,
+            cost_min: first.cost,
+            cost_avg: 0.0,
+            cost_max: first.cost,
+            penalty_min: first.penalty,
+            penalty_avg: 0.0,
+            penalty_max: first.penalty,
+            trials_min: first.trials,
+            trials_avg: 0.0,
+            trials_max: first.trials,
+            time_min: first.time,
+            time_avg: Duration::ZERO,
+            time_max: first.time,
+        };
+        let mut time_total = Duration::ZERO;
+        for result in &self.run_results {
+            if self
+                .optimum
+                .is_some_and(|optimum| result.penalty == 0 && result.cost <= optimum)
+            {
+                statistics.successes += 1;
+            }
+            statistics.cost_min = statistics.cost_min.min(result.cost);
+            statistics.cost_max = statistics.cost_max.max(result.cost);
+            statistics.cost_avg += result.cost as f64 / count;
+            statistics.penalty_min = statistics.penalty_min.min(result.penalty);
+            statistics.penalty_max = statistics.penalty_max.max(result.penalty);
+            statistics.penalty_avg += result.penalty as f64 / count;
+            statistics.trials_min = statistics.trials_min.min(result.trials);
+            statistics.trials_max = statistics.trials_max.max(result.trials);
+            statistics.trials_avg += f64::from(result.trials) / count;
+            statistics.time_min = statistics.time_min.min(result.time);
+            statistics.time_max = statistics.time_max.max(result.time);
+            time_total += result.time;
+        }
+        statistics.time_avg = time_total.div_f64(count);
+        Some(statistics)
+    }
+
+    /// The best tour split into one route per vehicle, as written by LKH's
+    /// `MTSP_WriteSolution`.
+    ///
+    /// [`tour`](Self::tour) encodes the vehicles of CVRP, mTSP and related
+    /// problems as extra depot nodes numbered above the customers. Each route
+    /// instead starts at the real depot, lists the customers it visits and
+    /// returns to the depot. Routes are empty for problems with a single
+    /// salesman, whose only route is `tour` itself.
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// The tour of each salesman of a multiple traveling salesmen solve, or
+    /// `None` when the solution has a single salesman.
+    ///
+    /// This is [`routes`](Self::routes) reduced to what the
+    /// [`MtspObjective`](crate::MtspObjective)s measure.
+    pub fn mtsp_solution(&self) -> Option<MtspSolution> {
+        if self.routes.is_empty() {
+            return None;
+        }
+        let tours = self
+            .routes
+            .iter()
+            .map(|route| SalesmanTour {
+                salesman: route.vehicle,
+                nodes: route.nodes.clone(),
+                length: route.cost,
+            })
+            .collect();
+        Some(MtspSolution { tours })
+    }
+}
+
+/// Per-salesman tours of a multiple traveling salesmen solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MtspSolution {
+    /// One tour per salesman, in salesman order.
+    pub tours: Vec<SalesmanTour>,
+}
+
+impl MtspSolution {
+    /// Sum of the tour lengths, the `MINSUM` objective.
+    pub fn total_length(&self) -> i64 {
+        self.tours.iter().map(|tour| tour.length).sum()
+    }
+
+    /// Length of the longest tour, the `MINMAX` objective.
+    pub fn max_length(&self) -> i64 {
+        self.tours.iter().map(|tour| tour.length).max().unwrap_or(0)
+    }
+
+    /// Customers on the largest tour, the `MINMAX_SIZE` objective.
+    pub fn max_size(&self) -> usize {
+        self.tours.iter().map(SalesmanTour::size).max().unwrap_or(0)
+    }
+}
+
+/// The tour of one salesman.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SalesmanTour {
+    /// One-based salesman number.
+    pub salesman: usize,
+    /// Node ids in visiting order, starting with the depot and, unless the
+    /// salesman is external, ending with it.
+    pub nodes: Vec<i32>,
+    /// Length of the tour.
+    pub length: i64,
+}
+
+impl SalesmanTour {
+    /// Number of customers visited, not counting the depot.
+    pub fn size(&self) -> usize {
+        let depot = self.nodes.first();
+        self.nodes
+            .iter()
+            .filter(|&node| Some(node) != depot)
+            .count()
+    }
+}
+
+/// One vehicle's route in a multi-salesman solution.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Route {
+    /// One-based vehicle number.
+    pub vehicle: usize,
+    /// Node ids in visiting order, starting with the depot. The depot is
+    /// repeated at the end unless the route belongs to an external salesman,
+    /// which does not return.
+    pub nodes: Vec<i32>,
+    /// Cost of the route's edges.
+    pub cost: i64,
+    /// Sum of the `DEMAND_SECTION` values of the visited customers.
+    pub load: i64,
+    /// Time from leaving the depot until returning to it, including service
+    /// times and waiting for time windows to open.
+    pub duration: f64,
+}
+
+/// Outcome of a single LKH run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunResult {
+    /// One-based run number.
+    pub run: i32,
+    /// Cost of the tour found by the run, after merging.
+    pub cost: i64,
+    /// Penalty of the tour found by the run.
+    pub penalty: i64,
+    /// Number of trials the run performed.
+    pub trials: i32,
+    /// Time spent in the run, measured with LKH's `GetTime`.
+    pub time: Duration,
+}
+
+/// Summary statistics over the runs of a solve.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunStatistics {
+    /// Runs that reached the known optimum with zero penalty.
+    pub successes: usize,
+    /// Lowest run cost.
+    pub cost_min: i64,
+    /// Mean run cost.
+    pub cost_avg: f64,
+    /// Highest run cost.
+    pub cost_max: i64,
+    /// Lowest run penalty.
+    pub penalty_min: i64,
+    /// Mean run penalty.
+    pub penalty_avg: f64,
+    /// Highest run penalty.
+    pub penalty_max: i64,
+    /// Fewest trials in a run.
+    pub trials_min: i32,
+    /// Mean trials per run.
+    pub trials_avg: f64,
+    /// Most trials in a run.
+    pub trials_max: i32,
+    /// Shortest run.
+    pub time_min: Duration,
+    /// Mean run time.
+    pub time_avg: Duration,
+    /// Longest run.
+    pub time_max: Duration,
+}
+
+/// Why LKH's search loop stopped.
+///
+/// serde uses the names of [`as_str`](Self::as_str).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TerminationReason {
+    /// Every requested run completed.
+    RunsExhausted,
+    /// `TIME_LIMIT` cut a run short, or `TOTAL_TIME_LIMIT` was reached
+    /// before all runs completed.
+    ///
+    /// This holds when any run was cut short, not only the last: the search
+    /// goes on with the next run, and the report still says `TimeLimit` once
+    /// every run is done.
+    TimeLimit,
+    /// `STOP_AT_OPTIMUM` ended the search at the known optimum.
+    OptimumReached,
+    /// The solve's [`CancellationToken`] was cancelled.
+    Cancelled,
+}
+
+impl TerminationReason {
+    /// Stable snake_case name, e.g. `"runs_exhausted"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RunsExhausted => "runs_exhausted",
+            Self::TimeLimit => "time_limit",
+            Self::OptimumReached => "optimum_reached",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Shared flag for stopping an in-memory solve from another thread.
+///
+/// Clones share the same flag. The solver checks it between runs and, through
+/// a hook in LKH's `FindTour`, before each trial. A cancelled solve returns
+/// the best tour found so far with [`TerminationReason::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every solve using this token to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
 }
 
 /// Solve an LKH parameter file with default options.
@@ -85,6 +385,11 @@ pub struct ProgrammaticSolveOptions {
     pub trace_level_override: Option<i32>,
     /// Maximum dimension used for explicit matrix allocation.
     pub max_matrix_dimension: i32,
+    /// Token for stopping the solve early. Only honored by in-process solves;
+    /// worker processes do not receive it.
+    pub cancellation: Option<CancellationToken>,
+    /// Where LKH's console output goes.
+    pub native_output: NativeOutput,
 }
 
 impl Default for ProgrammaticSolveOptions {
@@ -92,32 +397,270 @@ impl Default for ProgrammaticSolveOptions {
         Self {
             trace_level_override: None,
             max_matrix_dimension: 20_000,
+            cancellation: None,
+            native_output: NativeOutput::Inherit,
         }
     }
 }
 
+/// Where LKH's console output goes during an in-memory solve.
+///
+/// `Capture` and `Log` replace the C library's `stdout` stream with a pipe
+/// for the duration of the solve. The process's stdout file descriptor is
+/// left alone, so output the host writes itself is not affected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NativeOutput {
+    /// Traces of the search go to the process's stdout. Output printed while
+    /// reading the parameters and the problem is discarded.
+    #[default]
+    Inherit,
+    /// Collect all output in [`SolveReport::native_log`].
+    Capture,
+    /// Forward each line of output to the `log` crate at this level, with
+    /// the target `lkh`.
+    Log(log::Level),
+}
+
 /// Solve a programmatic problem with native backend options.
 pub fn solve_problem_with_options(
     problem: &RoutingProblem,
     parameters: &SearchParameters,
     options: ProgrammaticSolveOptions,
 ) -> Result<SolveReport, LkhError> {
-    parameters.validate()?;
+    problem.validate()?;
+    problem.validate_scaled_weights(parameters)?;
+    problem.validate_native_distances()?;
+    let parameter_text = parameters.to_lkh_parameter_file(IN_MEMORY_PROBLEM_FILE_NAME)?;
+    solve_rendered_problem(
+        problem.to_tsplib(),
+        parameter_text,
+        &InputTours::from_parameters(parameters)?,
+        &options,
+        &mut IgnoreEvents,
+        None,
+    )
+}
+
+/// Solve a programmatic problem and report progress to `observer`.
+///
+/// Events are emitted regardless of `TRACE_LEVEL`; see
+/// [`SolveEvent`] for the milestones reported.
+pub fn solve_problem_with_observer(
+    problem: &RoutingProblem,
+    parameters: &SearchParameters,
+    observer: &mut dyn SolveObserver,
+) -> Result<SolveReport, LkhError> {
+    solve_problem_with_observer_and_options(
+        problem,
+        parameters,
+        ProgrammaticSolveOptions::default(),
+        observer,
+    )
+}
+
+/// Solve a programmatic problem with native backend options and report
+/// progress to `observer`.
+///
+/// This combines [`solve_problem_with_options`] and
+/// [`solve_problem_with_observer`], e.g. for a UI that shows progress and
+/// can cancel the solve.
+pub fn solve_problem_with_observer_and_options(
+    problem: &RoutingProblem,
+    parameters: &SearchParameters,
+    options: ProgrammaticSolveOptions,
+    observer: &mut dyn SolveObserver,
+) -> Result<SolveReport, LkhError> {
+    problem.validate()?;
+    problem.validate_scaled_weights(parameters)?;
+    problem.validate_native_distances()?;
+    let parameter_text = parameters.to_lkh_parameter_file(IN_MEMORY_PROBLEM_FILE_NAME)?;
+    solve_rendered_problem(
+        problem.to_tsplib(),
+        parameter_text,
+        &InputTours::from_parameters(parameters)?,
+        &options,
+        observer,
+        None,
+    )
+}
+
+/// Solve a programmatic problem whose distances come from `distance`.
+///
+/// The problem must declare `EDGE_WEIGHT_TYPE: SPECIAL`, as
+/// [`RoutingProblem::distance_function`] does. LKH then asks `distance` for
+/// the distance from one node id to another (one-based, up to the problem's
+/// dimension) whenever it needs one, so no weights are rendered as text or
+/// copied into LKH's cost matrix. The function runs on the calling thread
+/// while the solver lock is held and must return values between 0 and
+/// `i32::MAX / 2 / PRECISION`. LKH treats SPECIAL distances as symmetric and
+/// rejects them for ATSP, SOP and PCTSP problems.
+///
+/// Problems that only carry sections such as `DEMAND_SECTION` are solved
+/// with an empty `FIXED_EDGES_SECTION` ahead of them, since LKH creates its
+/// nodes only while reading coordinates, weights or edges.
+///
+/// A value out of range ends the solve with [`LkhError::InvalidProblem`]. A
+/// panic in `distance` ends the solve and is resumed once LKH has been left
+/// in a state the next solve can reset. `options` apply as in
+/// [`solve_problem_with_options`].
+pub fn solve_problem_with_distance<F>(
+    problem: &RoutingProblem,
+    parameters: &SearchParameters,
+    options: ProgrammaticSolveOptions,
+    distance: F,
+) -> Result<SolveReport, LkhError>
+where
+    F: Fn(usize, usize) -> i64,
+{
+    problem.validate()?;
+    problem.validate_scaled_weights(parameters)?;
+    if !problem
+        .keyword("EDGE_WEIGHT_TYPE")
+        .is_some_and(|value| value.eq_ignore_ascii_case("SPECIAL"))
+    {
+        return Err(LkhError::InvalidProblem(
+            "a distance function needs EDGE_WEIGHT_TYPE: SPECIAL".to_owned(),
+        ));
+    }
+    let parameter_text = parameters.to_lkh_parameter_file(IN_MEMORY_PROBLEM_FILE_NAME)?;
+    solve_rendered_problem(
+        problem.with_nodes_created().to_tsplib(),
+        parameter_text,
+        &InputTours::from_parameters(parameters)?,
+        &options,
+        &mut IgnoreEvents,
+        Some(&distance),
+    )
+}
+
+/// Problem file name rendered into in-memory parameter text.
+///
+/// LKH requires `PROBLEM_FILE` to be present, but the programmatic path
+/// replaces it with an anonymous pipe before `ReadProblem` runs.
+pub(crate) const IN_MEMORY_PROBLEM_FILE_NAME: &str = "__lkh_rs_in_memory_problem__";
+
+/// Subproblem tour file name appended to in-memory parameter text.
+///
+/// `ReadParameters` rejects `SUBPROBLEM_SIZE` without a tour file, which the
+/// programmatic path then replaces with a pipe like the other tours.
+const IN_MEMORY_SUBPROBLEM_TOUR_FILE_NAME: &str = "__lkh_rs_in_memory_subproblem_tour__";
+
+/// Tours handed to LKH's tour readers alongside a rendered problem.
+///
+/// Parameter text cannot carry tours, so they travel separately and are fed
+/// through the same pipes as the problem itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct InputTours {
+    pub(crate) initial: Option<Vec<usize>>,
+    pub(crate) input: Option<Vec<usize>>,
+    pub(crate) merge: Vec<Vec<usize>>,
+    pub(crate) subproblem: Option<Vec<usize>>,
+}
+
+impl InputTours {
+    /// The tours of `parameters`. LKH cannot partition without a tour to
+    /// start from, and in memory there is no `SUBPROBLEM_TOUR_FILE` to fall
+    /// back on.
+    pub(crate) fn from_parameters(parameters: &SearchParameters) -> Result<Self, LkhError> {
+        if parameters.subproblem.is_some() && parameters.subproblem_tour.is_none() {
+            return Err(LkhError::InvalidSearchParameters(
+                "subproblem requires subproblem_tour".to_owned(),
+            ));
+        }
+        Ok(Self {
+            initial: parameters.initial_tour.clone(),
+            input: parameters.input_tour.clone(),
+            merge: parameters.merge_tours.clone(),
+            subproblem: parameters.subproblem_tour.clone(),
+        })
+    }
+}
+
+/// Solve already-rendered TSPLIB and parameter text through the in-memory path.
+///
+/// Subprocess workers receive the rendered text over their protocol and enter
+/// the solver here, so both sides feed LKH exactly the same input.
+pub(crate) fn solve_rendered_problem(
+    problem_text: String,
+    parameter_text: String,
+    tours: &InputTours,
+    options: &ProgrammaticSolveOptions,
+    observer: &mut dyn SolveObserver,
+    distance: Option<&dyn Fn(usize, usize) -> i64>,
+) -> Result<SolveReport, LkhError> {
+    let lock = SOLVER_LOCK.get_or_init(|| Mutex::new(()));
+    let guard = lock.lock().map_err(|_| LkhError::SolverLockPoisoned)?;
+
+    // SAFETY: The mutex serializes all access to LKH's process-global mutable
+    // state. Tour file names borrowed by C are cleared before they are freed.
+    let result = unsafe {
+        run_lkh_in_memory(
+            problem_text,
+            parameter_text,
+            tours,
+            options,
+            observer,
+            distance,
+        )
+    };
+    // Resume a panic of the distance function only after unlocking, so that
+    // the solver mutex is not poisoned.
+    drop(guard);
+    match DistanceHook::take_failure() {
+        Some(DistanceFailure::Panicked(payload)) => std::panic::resume_unwind(payload),
+        Some(DistanceFailure::OutOfRange { from, to, value }) => {
+            Err(LkhError::InvalidProblem(format!(
+                "distance function returned {value} from node {from} to node {to}, \
+                 outside 0..=INT_MAX / 2 / PRECISION"
+            )))
+        }
+        None => result,
+    }
+}
+
+/// Compute the cost of `tour` on `problem` the way LKH measures it, without
+/// searching.
+///
+/// `tour` lists every node id of the problem once, one-based, and returns to
+/// its first node. Problems with several vehicles are measured over LKH's
+/// depot copies as well, numbered after the last node as in the tours LKH
+/// writes. LKH reads the problem with `parameters`, so that keywords such as
+/// `PRECISION` and `SALESMEN` apply as in a solve, and the cost is the sum of
+/// its distances along the tour. Tours and partitioning settings in
+/// `parameters` are ignored.
+///
+/// This backs `lkh bench` and is not part of the stable API.
+#[doc(hidden)]
+pub fn tour_cost(
+    problem: &RoutingProblem,
+    parameters: &SearchParameters,
+    tour: &[usize],
+) -> Result<i64, LkhError> {
+    problem.validate()?;
+    problem.validate_scaled_weights(parameters)?;
+    problem.validate_native_distances()?;
+    let mut parameters = parameters.clone();
+    parameters.initial_tour = None;
+    parameters.input_tour = None;
+    parameters.merge_tours.clear();
+    parameters.subproblem = None;
+    parameters.subproblem_tour = None;
+    let parameter_text = parameters.to_lkh_parameter_file(IN_MEMORY_PROBLEM_FILE_NAME)?;
 
     let lock = SOLVER_LOCK.get_or_init(|| Mutex::new(()));
     let _guard = lock.lock().map_err(|_| LkhError::SolverLockPoisoned)?;
 
     // SAFETY: The mutex serializes all access to LKH's process-global mutable
-    // state. Programmatic initialization does not hand C any borrowed Rust data.
-    unsafe { run_lkh_in_memory(problem, parameters, &options) }
+    // state.
+    unsafe { measure_tour(problem.to_tsplib(), parameter_text, tour) }
 }
 
 /// Solve an LKH parameter file.
 ///
 /// The underlying LKH C library uses process-global mutable state, so calls are
 /// serialized with a global mutex. The Rust layer validates paths and returns a
-/// `Result`, but malformed inputs that reach LKH's `eprintf` path can still
-/// terminate the process because that behavior is implemented in upstream C.
+/// `Result`; malformed inputs that LKH rejects through `eprintf` are returned as
+/// [`LkhError::Native`] instead of terminating the process.
 pub fn solve_with_options(options: SolveOptions) -> Result<SolveReport, LkhError> {
     let parameter_file = canonical_parameter_file(&options.parameter_file)?;
     let working_directory = options
@@ -194,7 +737,11 @@ impl InMemoryFile {
 
 impl Drop for InMemoryFile {
     fn drop(&mut self) {
+        // Reaching drop without `finish` means LKH may have stopped reading
+        // part-way through. Drain the pipe so the writer cannot block on it,
+        // even if LKH's own `FILE` for the same pipe is still open.
         if let Some(read_fd) = self.read_fd.take() {
+            drain_fd(read_fd);
             close_fd(read_fd);
         }
         if let Some(writer) = self.writer.take() {
@@ -269,6 +816,23 @@ fn write_all_to_fd(fd: libc::c_int, mut bytes: &[u8]) -> io::Result<()> {
     Ok(())
 }
 
+#[cfg(unix)]
+fn drain_fd(fd: libc::c_int) {
+    let mut buffer = [0u8; 8192];
+    loop {
+        // SAFETY: `fd` is a read descriptor owned by the caller, and the
+        // pointer and length come from a live stack buffer.
+        let read = unsafe { libc::read(fd, buffer.as_mut_ptr().cast(), buffer.len()) };
+        if read > 0 {
+            continue;
+        }
+        if read < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        break;
+    }
+}
+
 #[cfg(unix)]
 fn close_fd(fd: libc::c_int) {
     // SAFETY: Closing an fd is safe as long as ownership is not reused. Callers
@@ -304,12 +868,19 @@ fn finish_in_memory_writer(writer: Option<JoinHandle<io::Result<()>>>) -> Result
     }
 }
 
+/// Points the process's stdout at `/dev/null` until dropped.
+///
+/// While a [`NativeOutputCapture`] is active, LKH writes to the capture pipe
+/// instead, so nothing is silenced and the output is kept.
 struct StdoutSilencer {
     saved_fd: Option<libc::c_int>,
 }
 
 impl StdoutSilencer {
     fn new() -> Result<Self, LkhError> {
+        if NATIVE_OUTPUT_CAPTURED.load(Ordering::Relaxed) {
+            return Ok(Self { saved_fd: None });
+        }
         silence_stdout()
     }
 }
@@ -378,6 +949,129 @@ fn silence_stdout() -> Result<StdoutSilencer, LkhError> {
 #[cfg(not(unix))]
 fn restore_stdout(_saved_fd: libc::c_int) {}
 
+/// Pipe that C's `stdout` stream writes to during one in-memory solve.
+///
+/// A reader thread drains the pipe while LKH runs, so a long trace cannot
+/// fill it and block the search.
+struct NativeOutputCapture {
+    reader: Option<JoinHandle<io::Result<Option<String>>>>,
+}
+
+impl NativeOutputCapture {
+    /// Redirect LKH's output as `mode` asks, or return `None` for
+    /// [`NativeOutput::Inherit`].
+    unsafe fn start(mode: NativeOutput) -> Result<Option<Self>, LkhError> {
+        if mode == NativeOutput::Inherit {
+            return Ok(None);
+        }
+        let (read_fd, write_fd) = output_pipe()?;
+        if LKH_rs_RedirectStdout(write_fd) != 0 {
+            close_fd(read_fd);
+            close_fd(write_fd);
+            return Err(LkhError::InMemoryInitialization(
+                "failed to redirect LKH's stdout".to_owned(),
+            ));
+        }
+        NATIVE_OUTPUT_CAPTURED.store(true, Ordering::Relaxed);
+        let reader = std::thread::spawn(move || read_native_output(read_fd, mode));
+        Ok(Some(Self {
+            reader: Some(reader),
+        }))
+    }
+
+    /// Restore C's `stdout` and return the captured text, if `mode` was
+    /// [`NativeOutput::Capture`].
+    fn finish(mut self) -> Result<Option<String>, LkhError> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<Option<String>, LkhError> {
+        let Some(reader) = self.reader.take() else {
+            return Ok(None);
+        };
+        // SAFETY: The redirection was installed by `start` while the solver
+        // mutex is held. Closing the stream ends the reader's input.
+        unsafe { LKH_rs_RestoreStdout() };
+        NATIVE_OUTPUT_CAPTURED.store(false, Ordering::Relaxed);
+        match reader.join() {
+            Ok(Ok(log)) => Ok(log),
+            Ok(Err(source)) => Err(LkhError::InMemoryInitialization(format!(
+                "failed to read LKH's output: {source}"
+            ))),
+            Err(_) => Err(LkhError::InMemoryInitialization(
+                "LKH output reader panicked".to_owned(),
+            )),
+        }
+    }
+}
+
+impl Drop for NativeOutputCapture {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[cfg(unix)]
+fn output_pipe() -> Result<(libc::c_int, libc::c_int), LkhError> {
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for the two descriptors written by `pipe`.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(LkhError::InMemoryInitialization(format!(
+            "failed to create output pipe: {}",
+            io::Error::last_os_error()
+        )));
+    }
+    Ok((fds[0], fds[1]))
+}
+
+#[cfg(not(unix))]
+fn output_pipe() -> Result<(libc::c_int, libc::c_int), LkhError> {
+    Err(LkhError::InMemoryInitialization(
+        "capturing LKH's output is not supported on this platform yet".to_owned(),
+    ))
+}
+
+#[cfg(unix)]
+fn read_native_output(fd: libc::c_int, mode: NativeOutput) -> io::Result<Option<String>> {
+    use std::io::{BufRead, Read};
+    use std::os::unix::io::FromRawFd;
+
+    // SAFETY: `fd` is the read end of a pipe created by `output_pipe`, owned
+    // by this thread from here on.
+    let mut input = io::BufReader::new(unsafe { std::fs::File::from_raw_fd(fd) });
+    let NativeOutput::Log(level) = mode else {
+        let mut log = Vec::new();
+        input.read_to_end(&mut log)?;
+        return Ok(Some(String::from_utf8_lossy(&log).into_owned()));
+    };
+    for line in input.split(b'\n') {
+        let line = line?;
+        log::log!(target: "lkh", level, "{}", String::from_utf8_lossy(&line));
+    }
+    Ok(None)
+}
+
+#[cfg(not(unix))]
+fn read_native_output(_fd: libc::c_int, _mode: NativeOutput) -> io::Result<Option<String>> {
+    Ok(None)
+}
+
+/// `print!` through C's `stdout`, so that traces printed by the Rust search
+/// loop stay in order with LKH's own output and are captured with it.
+macro_rules! native_print {
+    ($($arg:tt)*) => {
+        write_native_stdout(&format!($($arg)*))
+    };
+}
+
+fn write_native_stdout(text: &str) {
+    if let Ok(text) = CString::new(text) {
+        // SAFETY: `text` is a valid C string, and only solver code holding
+        // the mutex prints through C's stdout.
+        unsafe { LKH_rs_Print(text.as_ptr()) };
+    }
+}
+
 struct StructureGuard {
     enabled: bool,
 }
@@ -402,10 +1096,11 @@ unsafe fn run_lkh(
     parameter_file_name: *mut std::os::raw::c_char,
     options: &SolveOptions,
 ) -> Result<SolveReport, LkhError> {
+    let mut outcome = SearchOutcome::new(Instant::now());
     Gain23_Reset();
     reset_parameter_file_run_state();
     ParameterFileName = parameter_file_name;
-    ReadParameters();
+    guarded_read(ReadParameters)?;
     if let Some(trace_level) = options.trace_level {
         TraceLevel = trace_level;
     }
@@ -419,40 +1114,28 @@ unsafe fn run_lkh(
     } else {
         Some(MergeWithTourIPT)
     };
-    ReadProblem();
+    guarded_read(ReadProblem)?;
 
     if SubproblemSize > 0 {
-        if DelaunayPartitioning != 0 {
-            SolveDelaunaySubproblems();
-        } else if KarpPartitioning != 0 {
-            SolveKarpSubproblems();
-        } else if KCenterPartitioning != 0 {
-            SolveKCenterSubproblems();
-        } else if KMeansPartitioning != 0 {
-            SolveKMeansSubproblems();
-        } else if RohePartitioning != 0 {
-            SolveRoheSubproblems();
-        } else if MoorePartitioning != 0 || SierpinskiPartitioning != 0 {
-            SolveSFCSubproblems();
-        } else {
-            SolveTourSegmentSubproblems();
-        }
-        return report_from_globals();
+        solve_subproblems()?;
+        return report_from_globals(outcome);
     }
 
-    AllocateStructures();
     let _structures = StructureGuard::enabled();
+    guarded(AllocateStructures)?;
 
     if ProblemType == Types_TSPTW as i32 {
-        TSPTW_Reduce();
+        guarded(TSPTW_Reduce)?;
     }
     if ProblemType == Types_VRPB as i32 || ProblemType == Types_VRPBTW as i32 {
-        VRPB_Reduce();
+        guarded(VRPB_Reduce)?;
     }
     if ProblemType == Types_PDPTW as i32 {
-        PDPTW_Reduce();
+        guarded(PDPTW_Reduce)?;
     }
-    CreateCandidateSet();
+    let preprocessing_start = GetTime();
+    guarded(CreateCandidateSet)?;
+    outcome.preprocessing_time = lkh_duration(GetTime() - preprocessing_start);
     InitializeStatistics();
 
     if Norm != 0 || Penalty.is_some() {
@@ -468,10 +1151,9 @@ unsafe fn run_lkh(
         RecordBetterTour();
         RecordBestTour();
         CurrentPenalty = PLUS_INFINITY;
-        BestPenalty = if Penalty.is_some() {
-            Penalty.unwrap()()
-        } else {
-            0
+        BestPenalty = match Penalty {
+            Some(penalty) => guarded_gain(penalty)?,
+            None => 0,
         };
         CurrentPenalty = BestPenalty;
         WriteTour(OutputTourFileName, BestTour, BestCost);
@@ -485,12 +1167,15 @@ unsafe fn run_lkh(
         last_time = GetTime();
         if last_time - StartTime >= TotalTimeLimit {
             if TraceLevel >= 1 {
-                print!("*** Time limit exceeded ***\n");
+                println!("*** Time limit exceeded ***");
             }
             Run -= 1;
+            Runs = Run;
+            outcome.termination = TerminationReason::TimeLimit;
             break;
         }
-        let mut cost = FindTour();
+        let mut cost = guarded_gain(FindTour)?;
+        outcome.check_time_limit(last_time);
 
         // Merge population individuals.
         if MaxPopulationSize > 1 && TSPTW_Makespan == 0 {
@@ -533,7 +1218,7 @@ unsafe fn run_lkh(
                         }
                     }
 
-                    print!("\n");
+                    println!();
                 }
                 i += 1;
             }
@@ -552,7 +1237,7 @@ unsafe fn run_lkh(
                 }
             }
         } else if Run > 1 && TSPTW_Makespan == 0 {
-            cost = MergeTourWithBestTour();
+            cost = guarded_gain(MergeTourWithBestTour)?;
         }
         // update better tour
         if CurrentPenalty < BestPenalty || (CurrentPenalty == BestPenalty && cost < BestCost) {
@@ -571,7 +1256,7 @@ unsafe fn run_lkh(
             Optimum = CurrentPenalty;
         }
         if Optimum < old_optimum {
-            print!("*** New OPTIMUM = {:#?} ***\n", Optimum);
+            println!("*** New OPTIMUM = {:#?} ***", Optimum);
 
             if !(&*FirstNode).InputSuc.is_null() {
                 let first_node_ptr = FirstNode;
@@ -580,17 +1265,19 @@ unsafe fn run_lkh(
                     let next = current.Suc;
                     current.InputSuc = current.Suc;
                     current = &mut *next;
-                    if ptr::eq(current, &mut *first_node_ptr) {
+                    if ptr::eq(current, &*first_node_ptr) {
                         break;
                     }
                 }
             }
         }
-        UpdateStatistics(cost, fabs(GetTime() - last_time));
+        let run_time = fabs(GetTime() - last_time);
+        UpdateStatistics(cost, run_time);
+        outcome.record_run(cost, run_time);
         if TraceLevel >= 1 && cost != PLUS_INFINITY {
             print!("*** Run times:{:?}/{:?} ***", Run, Runs);
             StatusReport(cost, last_time, CString::new("").unwrap().into_raw());
-            print!("\n");
+            println!();
         }
 
         if StopAtOptimum != 0 && MaxPopulationSize >= 1 {
@@ -601,6 +1288,7 @@ unsafe fn run_lkh(
             };
             if optimum_reached {
                 Runs = Run;
+                outcome.termination = TerminationReason::OptimumReached;
                 break;
             }
         }
@@ -634,7 +1322,7 @@ unsafe fn run_lkh(
                 current.InitialSuc = current.Suc;
                 current = &mut *next;
 
-                if ptr::eq(current, &mut *first_node_ptr) {
+                if ptr::eq(current, &*first_node_ptr) {
                     break;
                 }
             }
@@ -674,6 +1362,7 @@ unsafe fn run_lkh(
             }
         }
         CurrentPenalty = BestPenalty;
+        outcome.routes = decode_routes()?;
         MTSP_Report(BestPenalty, BestCost);
         MTSP_WriteSolution(MTSPSolutionFileName, BestPenalty, BestCost);
     }
@@ -715,25 +1404,82 @@ unsafe fn run_lkh(
         CurrentPenalty = BestPenalty;
         SOP_Report(BestCost);
     }
-    print!("\n");
+    println!();
 
-    report_from_globals()
+    report_from_globals(outcome)
 }
 
 unsafe fn run_lkh_in_memory(
-    problem: &RoutingProblem,
-    parameters: &SearchParameters,
+    problem_text: String,
+    mut parameter_text: String,
+    tours: &InputTours,
     options: &ProgrammaticSolveOptions,
+    observer: &mut dyn SolveObserver,
+    distance: Option<&dyn Fn(usize, usize) -> i64>,
 ) -> Result<SolveReport, LkhError> {
     // Keep the programmatic path aligned with LKHmain.c: reset state, read
     // parameters, read the problem, then run the same search loop.
+    let started = Instant::now();
+    let capture = NativeOutputCapture::start(options.native_output)?;
     Gain23_Reset();
     reset_programmatic_run_state();
-    read_programmatic_parameters(parameters, options)?;
+    let _cancellation = CancellationHook::install(options.cancellation.as_ref());
+    let _distance = DistanceHook::install(distance);
+    if tours.subproblem.is_some() {
+        parameter_text.push_str(&format!(
+            "SUBPROBLEM_TOUR_FILE = {IN_MEMORY_SUBPROBLEM_TOUR_FILE_NAME}\n"
+        ));
+    }
+    read_programmatic_parameters(parameter_text, options)?;
     let last_time = GetTime();
     StartTime = last_time;
-    read_programmatic_problem(problem)?;
-    run_lkh_search(last_time)
+    let _tour_names = read_programmatic_problem(problem_text, tours)?;
+    let mut report = run_lkh_search(last_time, SearchOutcome::new(started), observer)?;
+    if let Some(capture) = capture {
+        report.native_log = capture.finish()?;
+    }
+    Ok(report)
+}
+
+/// Read a problem like [`run_lkh_in_memory`] and sum `Distance` along `tour`.
+unsafe fn measure_tour(
+    problem_text: String,
+    parameter_text: String,
+    tour: &[usize],
+) -> Result<i64, LkhError> {
+    reset_programmatic_run_state();
+    read_programmatic_parameters(parameter_text, &ProgrammaticSolveOptions::default())?;
+    read_programmatic_problem(problem_text, &InputTours::default())?;
+    let distance = Distance.ok_or_else(|| {
+        LkhError::InvalidProblem("LKH has no distance function for this problem".to_owned())
+    })?;
+    // DimensionSaved counts the depot copies of multi-vehicle problems but not
+    // the doubled node set of asymmetric ones.
+    let dimension = DimensionSaved as usize;
+    let mut visited = vec![false; dimension + 1];
+    for &node in tour {
+        if node == 0 || node > dimension || std::mem::replace(&mut visited[node], true) {
+            return Err(LkhError::InvalidProblem(format!(
+                "tour node {node} is out of range or visited twice"
+            )));
+        }
+    }
+    if tour.len() != dimension {
+        return Err(LkhError::InvalidProblem(format!(
+            "tour visits {} of {dimension} nodes",
+            tour.len()
+        )));
+    }
+    let mut cost: GainType = 0;
+    for (index, &from) in tour.iter().enumerate() {
+        let to = tour[(index + 1) % tour.len()];
+        // Asymmetric problems are read into a doubled node set, where the
+        // arc ends at the copy of `to`.
+        let to = if Asymmetric == 0 { to } else { to + dimension };
+        let (from, to) = (NodeSet.add(from), NodeSet.add(to));
+        cost += GainType::from(guarded_call(move || distance(from, to))?);
+    }
+    Ok(cost)
 }
 
 unsafe fn reset_programmatic_run_state() {
@@ -835,13 +1581,12 @@ unsafe fn reset_parameter_file_run_state() {
 }
 
 unsafe fn read_programmatic_parameters(
-    parameters: &SearchParameters,
+    parameter_text: String,
     options: &ProgrammaticSolveOptions,
 ) -> Result<(), LkhError> {
     // The public model is filesystem-free. We still render parameter text and
     // feed it through LKH's unchanged parser so native defaults and keyword
     // handling remain in one place.
-    let parameter_text = parameters.to_lkh_parameter_file("__lkh_rs_in_memory_problem__")?;
     let parameter_file = InMemoryFile::new("parameters", parameter_text)?;
     let parameter_name =
         CString::new(parameter_file.path()).map_err(|source| LkhError::CString {
@@ -849,11 +1594,13 @@ unsafe fn read_programmatic_parameters(
             source,
         })?;
     ParameterFileName = parameter_name.as_ptr() as *mut c_char;
-    {
+    let result = {
         let _silencer = StdoutSilencer::new()?;
-        ReadParameters();
-    }
+        guarded_read(ReadParameters)
+    };
     ParameterFileName = ptr::null_mut();
+    // On failure the pipe is drained when `parameter_file` is dropped.
+    result?;
     parameter_file.finish()?;
     MaxMatrixDimension = options.max_matrix_dimension;
     if let Some(trace_level) = options.trace_level_override {
@@ -869,24 +1616,332 @@ unsafe fn read_programmatic_parameters(
     Ok(())
 }
 
-unsafe fn read_programmatic_problem(problem: &RoutingProblem) -> Result<(), LkhError> {
+unsafe fn read_programmatic_problem(
+    problem_text: String,
+    tours: &InputTours,
+) -> Result<TourFileNames, LkhError> {
     // Feed generated TSPLIB text through LKH's existing `fopen`-based parser.
     // On Unix-like native targets the path points at an anonymous pipe under
     // `/dev/fd`, so no temporary problem file is created.
-    let problem_text = problem.to_tsplib();
     let problem_file = InMemoryFile::new("problem", problem_text)?;
     let problem_name = CString::new(problem_file.path()).map_err(|source| LkhError::CString {
         context: "programmatic problem path",
         source,
     })?;
+    // `ReadProblem` reads the tour files right after the problem.
+    let tour_files = InMemoryTours::install(tours)?;
     ProblemFileName = problem_name.as_ptr() as *mut c_char;
-    {
+    let result = {
         let _silencer = StdoutSilencer::new()?;
-        ReadProblem();
-    }
+        guarded_read(ReadProblem)
+    };
     ProblemFileName = ptr::null_mut();
+    result?;
     problem_file.finish()?;
-    Ok(())
+    tour_files.finish()
+}
+
+/// Pipes for the tours of one solve, installed as LKH's tour file names.
+struct InMemoryTours {
+    files: Vec<InMemoryFile>,
+    names: TourFileNames,
+}
+
+impl InMemoryTours {
+    unsafe fn install(tours: &InputTours) -> Result<Self, LkhError> {
+        let mut files = Vec::new();
+        let mut names = TourFileNames::default();
+        if let Some(tour) = &tours.initial {
+            InitialTourFileName = names.add(&mut files, "initial tour", tour)?;
+        }
+        if let Some(tour) = &tours.input {
+            InputTourFileName = names.add(&mut files, "input tour", tour)?;
+        }
+        if !tours.merge.is_empty() {
+            for tour in &tours.merge {
+                let name = names.add(&mut files, "merge tour", tour)?;
+                names.merge.push(name);
+            }
+            MergeTourFileName = names.merge.as_mut_ptr();
+            MergeTourFiles = names.merge.len() as c_int;
+        }
+        if let Some(tour) = &tours.subproblem {
+            SubproblemTourFileName = names.add(&mut files, "subproblem tour", tour)?;
+        }
+        Ok(Self { files, names })
+    }
+
+    fn finish(self) -> Result<TourFileNames, LkhError> {
+        for file in self.files {
+            file.finish()?;
+        }
+        Ok(self.names)
+    }
+}
+
+/// Owns the tour file names LKH points at during a solve.
+///
+/// `ReadProblem` opens the tours by name, and candidate set generation checks
+/// `InitialTourFileName` again, so the names must outlive the search. Dropping
+/// them clears the globals first.
+#[derive(Default)]
+struct TourFileNames {
+    names: Vec<CString>,
+    merge: Vec<*mut c_char>,
+}
+
+impl TourFileNames {
+    fn add(
+        &mut self,
+        files: &mut Vec<InMemoryFile>,
+        label: &'static str,
+        tour: &[usize],
+    ) -> Result<*mut c_char, LkhError> {
+        let file = InMemoryFile::new(label, render_tour(tour))?;
+        let name = CString::new(file.path()).map_err(|source| LkhError::CString {
+            context: "programmatic tour path",
+            source,
+        })?;
+        files.push(file);
+        let pointer = name.as_ptr() as *mut c_char;
+        self.names.push(name);
+        Ok(pointer)
+    }
+}
+
+impl Drop for TourFileNames {
+    fn drop(&mut self) {
+        if self.names.is_empty() {
+            return;
+        }
+        // SAFETY: Tour names are only installed while the solver mutex is
+        // held, and the solve that used them has finished.
+        unsafe {
+            InitialTourFileName = ptr::null_mut();
+            InputTourFileName = ptr::null_mut();
+            MergeTourFileName = ptr::null_mut();
+            SubproblemTourFileName = ptr::null_mut();
+        }
+    }
+}
+
+/// Render a tour in the TSPLIB `TOUR_SECTION` format read by `ReadTour`.
+fn render_tour(tour: &[usize]) -> String {
+    let mut text = format!("DIMENSION : {}\nTOUR_SECTION\n", tour.len());
+    for node in tour {
+        text.push_str(&node.to_string());
+        text.push('\n');
+    }
+    text.push_str("-1\nEOF\n");
+    text
+}
+
+/// Installs LKH's stop hook for the duration of one in-memory solve.
+struct CancellationHook;
+
+impl CancellationHook {
+    unsafe fn install(token: Option<&CancellationToken>) -> Self {
+        if let Ok(mut active) = ACTIVE_CANCELLATION.lock() {
+            *active = token.cloned();
+        }
+        LKH_rs_StopHook = token.map(|_| stop_requested as unsafe extern "C" fn() -> c_int);
+        Self
+    }
+}
+
+impl Drop for CancellationHook {
+    fn drop(&mut self) {
+        // SAFETY: The hook is only installed and removed while the solver
+        // mutex is held.
+        unsafe { LKH_rs_StopHook = None };
+        if let Ok(mut active) = ACTIVE_CANCELLATION.lock() {
+            *active = None;
+        }
+    }
+}
+
+extern "C" fn stop_requested() -> c_int {
+    c_int::from(cancellation_requested())
+}
+
+/// Why the distance function of the current solve could not provide a
+/// distance.
+enum DistanceFailure {
+    Panicked(Box<dyn Any + Send>),
+    OutOfRange { from: usize, to: usize, value: i64 },
+}
+
+thread_local! {
+    /// The distance function of the solve running on this thread. LKH calls
+    /// back on the thread that entered it, so no locking is needed.
+    static ACTIVE_DISTANCE: Cell<Option<*const DistanceFunction>> = const { Cell::new(None) };
+    /// The first failure of [`ACTIVE_DISTANCE`], kept until the solve ends.
+    static DISTANCE_FAILURE: RefCell<Option<DistanceFailure>> = const { RefCell::new(None) };
+}
+
+type DistanceFunction = dyn Fn(usize, usize) -> i64 + 'static;
+
+/// Installs LKH's SPECIAL distance hook for the duration of one in-memory
+/// solve.
+struct DistanceHook;
+
+impl DistanceHook {
+    unsafe fn install(distance: Option<&dyn Fn(usize, usize) -> i64>) -> Self {
+        // SAFETY: The lifetime is only erased while the hook is installed,
+        // and `Drop` removes it before the borrow ends.
+        let distance = distance.map(|distance| {
+            std::mem::transmute::<&dyn Fn(usize, usize) -> i64, &DistanceFunction>(distance)
+                as *const DistanceFunction
+        });
+        ACTIVE_DISTANCE.set(distance);
+        DISTANCE_FAILURE.take();
+        LKH_rs_DistanceHook = distance
+            .map(|_| special_distance as unsafe extern "C" fn(c_int, c_int, *mut c_int) -> c_int);
+        Self
+    }
+
+    fn take_failure() -> Option<DistanceFailure> {
+        DISTANCE_FAILURE.take()
+    }
+}
+
+impl Drop for DistanceHook {
+    fn drop(&mut self) {
+        // SAFETY: The hook is only installed and removed while the solver
+        // mutex is held.
+        unsafe { LKH_rs_DistanceHook = None };
+        ACTIVE_DISTANCE.set(None);
+    }
+}
+
+/// `LKH_rs_DistanceHook`: returns 0 with the distance stored, or 1 after
+/// recording why there is none. A panic must not unwind into C, so it is
+/// caught here and resumed by [`solve_rendered_problem`].
+extern "C" fn special_distance(from: c_int, to: c_int, distance: *mut c_int) -> c_int {
+    let Some(function) = ACTIVE_DISTANCE.get() else {
+        return 1;
+    };
+    let (from, to) = (from as usize, to as usize);
+    // SAFETY: The pointer is valid while the hook is installed.
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| unsafe { (*function)(from, to) }));
+    // SAFETY: Precision is only written by ReadParameters under the solver
+    // mutex, which the caller of this hook holds.
+    let limit = i64::from(i32::MAX / 2 / unsafe { Precision }.max(1));
+    let failure = match result {
+        Ok(value) if (0..=limit).contains(&value) => {
+            // SAFETY: Distance_SPECIAL passes a pointer to a local int.
+            unsafe { *distance = value as c_int };
+            return 0;
+        }
+        Ok(value) => DistanceFailure::OutOfRange { from, to, value },
+        Err(payload) => DistanceFailure::Panicked(payload),
+    };
+    DISTANCE_FAILURE.with_borrow_mut(|first| {
+        first.get_or_insert(failure);
+    });
+    1
+}
+
+fn cancellation_requested() -> bool {
+    ACTIVE_CANCELLATION
+        .lock()
+        .map(|active| active.as_ref().is_some_and(CancellationToken::is_cancelled))
+        .unwrap_or(false)
+}
+
+/// Call an LKH entry point with `eprintf` turned into [`LkhError::Native`].
+///
+/// The C shim jumps straight back into `LKH_rs_Guard`, so only C frames and
+/// the trampolines of [`guarded_call`] are skipped. Anything the interrupted call allocated is released by the next
+/// `FreeStructures`.
+unsafe fn guarded(function: unsafe extern "C" fn()) -> Result<(), LkhError> {
+    if LKH_rs_Guard(Some(function)) == 0 {
+        Ok(())
+    } else {
+        Err(native_error())
+    }
+}
+
+unsafe fn guarded_gain(function: unsafe extern "C" fn() -> GainType) -> Result<GainType, LkhError> {
+    let mut result = 0;
+    if LKH_rs_GuardGain(Some(function), &mut result) == 0 {
+        Ok(result)
+    } else {
+        Err(native_error())
+    }
+}
+
+thread_local! {
+    /// The call and result slot of the innermost [`guarded_call`] on this
+    /// thread.
+    static GUARDED_CALL: Cell<*const ()> = const { Cell::new(ptr::null()) };
+}
+
+/// Like [`guarded`], for an LKH call that takes arguments or returns a value.
+///
+/// `call` runs inside a trampoline that `LKH_rs_Guard` calls. Both are `Copy`
+/// and own nothing that needs dropping, so an `eprintf` jump may skip their
+/// frames.
+unsafe fn guarded_call<T, F>(call: F) -> Result<T, LkhError>
+where
+    T: Copy + Default,
+    F: Fn() -> T + Copy,
+{
+    extern "C" fn trampoline<T: Copy, F: Fn() -> T + Copy>() {
+        // SAFETY: `guarded_call` points GUARDED_CALL at its state for the
+        // duration of the guard.
+        let (call, result) = unsafe { &*GUARDED_CALL.get().cast::<(F, Cell<T>)>() };
+        result.set(call());
+    }
+    let state = (call, Cell::new(T::default()));
+    let saved = GUARDED_CALL.replace(ptr::addr_of!(state).cast());
+    let outcome = guarded(trampoline::<T, F>);
+    GUARDED_CALL.set(saved);
+    outcome.map(|()| state.1.get())
+}
+
+/// Like [`guarded`], for LKH's input readers.
+///
+/// A reader that fails part-way leaves its `FILE` open, so close every input
+/// stream LKH may still hold before returning the error.
+unsafe fn guarded_read(reader: unsafe extern "C" fn()) -> Result<(), LkhError> {
+    ParameterFile = ptr::null_mut();
+    ProblemFile = ptr::null_mut();
+    InitialTourFile = ptr::null_mut();
+    InputTourFile = ptr::null_mut();
+    SubproblemTourFile = ptr::null_mut();
+    guarded(reader).inspect_err(|_| close_native_input_files())
+}
+
+unsafe fn close_native_input_files() {
+    for file in [
+        ptr::addr_of_mut!(ParameterFile),
+        ptr::addr_of_mut!(ProblemFile),
+        ptr::addr_of_mut!(InitialTourFile),
+        ptr::addr_of_mut!(InputTourFile),
+        ptr::addr_of_mut!(SubproblemTourFile),
+    ] {
+        if !(*file).is_null() {
+            libc::fclose((*file).cast());
+            *file = ptr::null_mut();
+        }
+    }
+    if !MergeTourFile.is_null() {
+        for i in 0..MergeTourFiles.max(0) as usize {
+            let file = MergeTourFile.add(i);
+            if !(*file).is_null() {
+                libc::fclose((*file).cast());
+                *file = ptr::null_mut();
+            }
+        }
+    }
+    libc::free(LastLine.cast());
+    LastLine = ptr::null_mut();
+}
+
+unsafe fn native_error() -> LkhError {
+    let message = CStr::from_ptr(LKH_rs_ErrorMessage());
+    LkhError::Native(message.to_string_lossy().into_owned())
 }
 
 unsafe fn free_and_reset_problem_globals() {
@@ -946,39 +2001,114 @@ unsafe fn reset_file_globals() {
     MergeTourFiles = 0;
 }
 
-unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
-    if SubproblemSize > 0 {
-        if DelaunayPartitioning != 0 {
-            SolveDelaunaySubproblems();
-        } else if KarpPartitioning != 0 {
-            SolveKarpSubproblems();
-        } else if KCenterPartitioning != 0 {
-            SolveKCenterSubproblems();
-        } else if KMeansPartitioning != 0 {
-            SolveKMeansSubproblems();
-        } else if RohePartitioning != 0 {
-            SolveRoheSubproblems();
-        } else if MoorePartitioning != 0 || SierpinskiPartitioning != 0 {
-            SolveSFCSubproblems();
-        } else {
-            SolveTourSegmentSubproblems();
+/// Run LKH's subproblem partitioning, as `LKHmain` does when
+/// `SUBPROBLEM_SIZE` is set, and record the improved tour as the best tour.
+unsafe fn solve_subproblems() -> Result<(), LkhError> {
+    // Partitioning reports its progress even at trace level 0, so it is
+    // silenced then, unless the solve captures or logs LKH's output: the
+    // silencer leaves the capture pipe in place and the progress is kept.
+    let _silencer = if TraceLevel == 0 {
+        Some(StdoutSilencer::new()?)
+    } else {
+        None
+    };
+    if DelaunayPartitioning != 0 {
+        guarded(SolveDelaunaySubproblems)?;
+    } else if KarpPartitioning != 0 {
+        guarded(SolveKarpSubproblems)?;
+    } else if KCenterPartitioning != 0 {
+        guarded(SolveKCenterSubproblems)?;
+    } else if KMeansPartitioning != 0 {
+        guarded(SolveKMeansSubproblems)?;
+    } else if RohePartitioning != 0 {
+        guarded(SolveRoheSubproblems)?;
+    } else if MoorePartitioning != 0 || SierpinskiPartitioning != 0 {
+        guarded(SolveSFCSubproblems)?;
+    } else {
+        guarded(SolveTourSegmentSubproblems)?;
+    }
+    record_subproblem_tour()
+}
+
+/// Copy the tour left in the `SubproblemSuc` links into `BestTour`.
+///
+/// The partitioning functions only record tours that improve on the known
+/// optimum, and reuse `BestCost` for each subproblem, so the full tour and
+/// its cost are recomputed here the way `SolveTourSegmentSubproblems` does.
+unsafe fn record_subproblem_tour() -> Result<(), LkhError> {
+    let dimension = DimensionSaved as usize;
+    if NodeSet.is_null() || BestTour.is_null() || dimension == 0 {
+        return Ok(());
+    }
+    let first = NodeSet.add(1);
+    let distance = Distance.ok_or_else(|| {
+        LkhError::InMemoryInitialization(
+            "LKH has no distance function after solving subproblems".to_owned(),
+        )
+    })?;
+    let mut cost: GainType = 0;
+    let mut tour = Vec::with_capacity(dimension);
+    let mut node = first;
+    loop {
+        let next = (*node).SubproblemSuc;
+        if !ptr::eq((*node).FixedTo1, next) && !ptr::eq((*node).FixedTo2, next) {
+            cost += GainType::from(guarded_call(move || distance(node, next))?);
+        }
+        if (*node).Id as usize <= dimension {
+            tour.push((*node).Id);
+        }
+        node = next;
+        if ptr::eq(node, first) {
+            break;
         }
-        return report_from_globals();
+    }
+    // In the doubled ATSP graph a node is followed by its twin in the
+    // forward direction.
+    if ProblemType == Types_ATSP as i32
+        && (*(*first).SubproblemSuc).Id as usize != (*first).Id as usize + dimension
+    {
+        tour[1..].reverse();
+    }
+    for (index, id) in tour.into_iter().enumerate() {
+        *BestTour.add(index + 1) = id;
+    }
+    *BestTour = *BestTour.add(1);
+    BestCost = cost;
+    BestPenalty = 0;
+    Ok(())
+}
+
+unsafe fn run_lkh_search(
+    mut last_time: f64,
+    mut outcome: SearchOutcome,
+    observer: &mut dyn SolveObserver,
+) -> Result<SolveReport, LkhError> {
+    if SubproblemSize > 0 {
+        solve_subproblems()?;
+        return report_from_globals(outcome);
     }
 
-    AllocateStructures();
     let _structures = StructureGuard::enabled();
+    guarded(AllocateStructures)?;
 
     if ProblemType == Types_TSPTW as i32 {
-        TSPTW_Reduce();
+        guarded(TSPTW_Reduce)?;
     }
     if ProblemType == Types_VRPB as i32 || ProblemType == Types_VRPBTW as i32 {
-        VRPB_Reduce();
+        guarded(VRPB_Reduce)?;
     }
     if ProblemType == Types_PDPTW as i32 {
-        PDPTW_Reduce();
+        guarded(PDPTW_Reduce)?;
     }
-    CreateCandidateSet();
+    let preprocessing_start = GetTime();
+    guarded(CreateCandidateSet)?;
+    outcome.preprocessing_time = lkh_duration(GetTime() - preprocessing_start);
+    observer.on_event(&SolveEvent::LowerBoundComputed {
+        lower_bound: LowerBound,
+    });
+    observer.on_event(&SolveEvent::CandidateSetCreated {
+        elapsed: outcome.preprocessing_time,
+    });
     InitializeStatistics();
 
     if Norm != 0 || Penalty.is_some() {
@@ -993,10 +2123,9 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
         RecordBetterTour();
         RecordBestTour();
         CurrentPenalty = PLUS_INFINITY;
-        BestPenalty = if Penalty.is_some() {
-            Penalty.unwrap()()
-        } else {
-            0
+        BestPenalty = match Penalty {
+            Some(penalty) => guarded_gain(penalty)?,
+            None => 0,
         };
         CurrentPenalty = BestPenalty;
         Runs = 0;
@@ -1007,32 +2136,48 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
         last_time = GetTime();
         if last_time - StartTime >= TotalTimeLimit {
             if TraceLevel >= 1 {
-                print!("*** Time limit exceeded ***\n");
+                native_print!("*** Time limit exceeded ***\n");
             }
             Run -= 1;
+            Runs = Run;
+            outcome.termination = TerminationReason::TimeLimit;
             break;
         }
-        let mut cost = FindTour();
+        if cancellation_requested() {
+            Run -= 1;
+            Runs = Run;
+            outcome.termination = TerminationReason::Cancelled;
+            break;
+        }
+        let mut cost = guarded_gain(FindTour)?;
+        outcome.check_time_limit(last_time);
 
         if MaxPopulationSize > 1 && TSPTW_Makespan == 0 {
             let mut i = 0;
             while i < PopulationSize {
                 let old_penalty: GainType = CurrentPenalty;
                 let old_cost: GainType = cost;
-                cost = MergeTourWithIndividual(i);
-                if TraceLevel >= 1
-                    && (CurrentPenalty < old_penalty
-                        || (CurrentPenalty == old_penalty && cost < old_cost))
-                {
+                cost = guarded_call(move || MergeTourWithIndividual(i))?;
+                let improved = CurrentPenalty < old_penalty
+                    || (CurrentPenalty == old_penalty && cost < old_cost);
+                if improved {
+                    observer.on_event(&SolveEvent::MergedWithIndividual {
+                        run: Run,
+                        individual: i + 1,
+                        cost,
+                        penalty: CurrentPenalty,
+                    });
+                }
+                if TraceLevel >= 1 && improved {
                     if CurrentPenalty != 0 {
-                        print!(
+                        native_print!(
                             "  Merged with {}: Cost = {}_{}",
                             i + 1,
                             CurrentPenalty,
                             cost
                         );
                     } else {
-                        print!("  Merged with {}: Cost = {}", i + 1, cost);
+                        native_print!("  Merged with {}: Cost = {}", i + 1, cost);
                     }
                     if Optimum != MINUS_INFINITY && Optimum != 0 {
                         if OptimizePenalty != 0 {
@@ -1041,37 +2186,45 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
                             } else {
                                 1.0
                             };
-                            print!(
+                            native_print!(
                                 ", Gap = {:0.4}%",
                                 sign * (CurrentPenalty - Optimum) as f64 / Optimum as f64 * 100.0
                             );
                         } else {
-                            print!(
+                            native_print!(
                                 ", Gap = {:0.4}%",
                                 (cost - Optimum) as f64 / Optimum as f64 * 100.0
                             );
                         }
                     }
-                    print!("\n");
+                    native_print!("\n");
                 }
                 i += 1;
             }
             if HasFitness(CurrentPenalty, cost) == 0 {
                 if PopulationSize < MaxPopulationSize {
                     AddToPopulation(CurrentPenalty, cost);
+                    observer.on_event(&SolveEvent::PopulationUpdated {
+                        run: Run,
+                        size: PopulationSize,
+                    });
                     if TraceLevel >= 1 {
                         PrintPopulation();
                     }
                 } else if smaller_fitness(CurrentPenalty, cost, (PopulationSize - 1) as isize) {
                     let replacement = ReplacementIndividual(CurrentPenalty, cost);
                     ReplaceIndividualWithTour(replacement, CurrentPenalty, cost);
+                    observer.on_event(&SolveEvent::PopulationUpdated {
+                        run: Run,
+                        size: PopulationSize,
+                    });
                     if TraceLevel >= 1 {
                         PrintPopulation();
                     }
                 }
             }
         } else if Run > 1 && TSPTW_Makespan == 0 {
-            cost = MergeTourWithBestTour();
+            cost = guarded_gain(MergeTourWithBestTour)?;
         }
 
         if CurrentPenalty < BestPenalty || (CurrentPenalty == BestPenalty && cost < BestCost) {
@@ -1079,6 +2232,11 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
             BestCost = cost;
             RecordBetterTour();
             RecordBestTour();
+            observer.on_event(&SolveEvent::NewBest {
+                run: Run,
+                cost,
+                penalty: CurrentPenalty,
+            });
         }
 
         let old_optimum = Optimum;
@@ -1091,7 +2249,7 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
         }
         if Optimum < old_optimum && !FirstNode.is_null() && !(*FirstNode).InputSuc.is_null() {
             if TraceLevel >= 1 {
-                print!("*** New OPTIMUM = {:#?} ***\n", Optimum);
+                native_print!("*** New OPTIMUM = {:#?} ***\n", Optimum);
             }
             let first_node_ptr = FirstNode;
             let mut current = FirstNode;
@@ -1105,12 +2263,20 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
             }
         }
 
-        UpdateStatistics(cost, fabs(GetTime() - last_time));
+        let run_time = fabs(GetTime() - last_time);
+        UpdateStatistics(cost, run_time);
+        outcome.record_run(cost, run_time);
+        observer.on_event(&SolveEvent::RunFinished {
+            run: Run,
+            cost,
+            penalty: CurrentPenalty,
+            elapsed: lkh_duration(run_time),
+        });
         if TraceLevel >= 1 && cost != PLUS_INFINITY {
-            print!("Run {}: ", Run);
+            native_print!("Run {}: ", Run);
             let empty = CString::new("").unwrap();
             StatusReport(cost, last_time, empty.as_ptr() as *mut c_char);
-            print!("\n");
+            native_print!("\n");
         }
         if StopAtOptimum != 0 && MaxPopulationSize >= 1 {
             let optimum_reached = if OptimizePenalty != 0 {
@@ -1120,9 +2286,17 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
             };
             if optimum_reached {
                 Runs = Run;
+                outcome.termination = TerminationReason::OptimumReached;
                 break;
             }
         }
+        if cancellation_requested() {
+            // FindTour may have stopped early, but its best tour has already
+            // been merged into the result above.
+            Runs = Run;
+            outcome.termination = TerminationReason::Cancelled;
+            break;
+        }
 
         IsChild = 0;
         if PopulationSize >= 2
@@ -1145,7 +2319,7 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
             let mut current = FirstNode;
             loop {
                 if ProblemType != Types_HCP as i32 && ProblemType != Types_HPP as i32 {
-                    let d = C.unwrap()(current, (*current).Suc);
+                    let d = guarded_call(move || C.unwrap()(current, (*current).Suc))?;
                     AddCandidate(current, (*current).Suc, d, i32::MAX);
                     AddCandidate((*current).Suc, current, d, i32::MAX);
                 }
@@ -1198,8 +2372,9 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
             }
         }
         CurrentPenalty = BestPenalty;
+        outcome.routes = decode_routes()?;
         if TraceLevel >= 1 {
-            MTSP_Report(BestPenalty, BestCost);
+            guarded_call(|| MTSP_Report(BestPenalty, BestCost))?;
         }
     }
 
@@ -1207,7 +2382,53 @@ unsafe fn run_lkh_search(mut last_time: f64) -> Result<SolveReport, LkhError> {
         CurrentPenalty = BestPenalty;
         SOP_Report(BestCost);
     }
-    report_from_globals()
+    report_from_globals(outcome)
+}
+
+/// What the search loop learned beyond what LKH keeps in its globals.
+struct SearchOutcome {
+    started: Instant,
+    termination: TerminationReason,
+    preprocessing_time: Duration,
+    run_results: Vec<RunResult>,
+    routes: Vec<Route>,
+}
+
+impl SearchOutcome {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            termination: TerminationReason::RunsExhausted,
+            preprocessing_time: Duration::ZERO,
+            run_results: Vec::new(),
+            routes: Vec::new(),
+        }
+    }
+
+    /// Note whether the run that started at `run_start` was cut short by
+    /// `TIME_LIMIT` or `TOTAL_TIME_LIMIT`, which `FindTour` checks the same
+    /// way between trials. A later run that completes does not clear it.
+    unsafe fn check_time_limit(&mut self, run_start: f64) {
+        let now = GetTime();
+        if now - run_start >= TimeLimit || now - StartTime >= TotalTimeLimit {
+            self.termination = TerminationReason::TimeLimit;
+        }
+    }
+
+    unsafe fn record_run(&mut self, cost: GainType, run_time: f64) {
+        self.run_results.push(RunResult {
+            run: Run,
+            cost,
+            penalty: CurrentPenalty,
+            trials: Trial,
+            time: lkh_duration(run_time),
+        });
+    }
+}
+
+/// Convert a difference of LKH `GetTime` values to a `Duration`.
+fn lkh_duration(seconds: f64) -> Duration {
+    Duration::try_from_secs_f64(seconds).unwrap_or_default()
 }
 
 unsafe fn should_report_special_solution() -> bool {
@@ -1245,7 +2466,10 @@ unsafe fn should_report_special_solution() -> bool {
         || ProblemType == Types_VRPPD as i32
 }
 
-unsafe fn report_from_globals() -> Result<SolveReport, LkhError> {
+unsafe fn report_from_globals(outcome: SearchOutcome) -> Result<SolveReport, LkhError> {
+    if outcome.termination == TerminationReason::Cancelled && BestCost == PLUS_INFINITY {
+        return Err(LkhError::Cancelled);
+    }
     let dimension = DimensionSaved;
     let tour = if BestTour.is_null() || dimension <= 0 {
         Vec::new()
@@ -1259,13 +2483,127 @@ unsafe fn report_from_globals() -> Result<SolveReport, LkhError> {
 
     Ok(SolveReport {
         best_cost: BestCost,
+        best_cost_unscaled: BestCost as f64 / f64::from(Scale.unsigned_abs().max(1)),
         best_penalty: BestPenalty,
         runs: Runs,
         dimension,
         tour,
+        termination: outcome.termination,
+        lower_bound: LowerBound,
+        lower_bound_gap: lower_bound_gap(),
+        optimum: (Optimum != MINUS_INFINITY).then_some(Optimum),
+        gap: optimum_gap(),
+        run_results: outcome.run_results,
+        preprocessing_time: outcome.preprocessing_time,
+        wall_time: outcome.started.elapsed(),
+        native_log: None,
+        routes: outcome.routes,
     })
 }
 
+/// Split `BestTour` into routes, walking from the depot like
+/// `MTSP_WriteSolution` and timing each route like `Penalty_CVRPTW`.
+unsafe fn decode_routes() -> Result<Vec<Route>, LkhError> {
+    let dimension = DimensionSaved as usize;
+    if Salesmen <= 1 || Depot.is_null() || BestTour.is_null() || dimension == 0 {
+        return Ok(Vec::new());
+    }
+    let tour = std::slice::from_raw_parts(BestTour.add(1), dimension);
+    let Some(mut position) = tour.iter().position(|&id| id == (*Depot).Id) else {
+        return Ok(Vec::new());
+    };
+    let mut routes = Vec::new();
+    let mut node = Depot;
+    loop {
+        let vehicle = (*node).DepotId as usize;
+        let mut nodes = vec![(*Depot).Id];
+        let mut cost: GainType = 0;
+        let mut load = 0;
+        let departure = (*node).Earliest.max(0.0);
+        let mut time = departure;
+        let mut first: *mut Node = ptr::null_mut();
+        let mut last: *mut Node = ptr::null_mut();
+        loop {
+            if (*node).Id <= Dim && !ptr::eq(node, Depot) {
+                nodes.push((*node).Id);
+                load += i64::from((*node).Demand);
+                time = time.max((*node).Earliest) + (*node).ServiceTime;
+                if first.is_null() {
+                    first = node;
+                }
+                last = node;
+            }
+            position = (position + 1) % dimension;
+            let next = NodeSet.add(tour[position] as usize);
+            let edge = edge_cost(node, next)?;
+            cost += edge;
+            time += (edge / GainType::from(Precision)) as f64;
+            node = next;
+            if (*node).DepotId != 0 {
+                break;
+            }
+        }
+        if ProblemType == Types_GCTSP as i32 && !ptr::eq(first, last) {
+            let closing = guarded_call(move || C.unwrap()(first, last))?;
+            cost += GainType::from(closing - (*first).Pi - (*last).Pi);
+        }
+        if ProblemType == Types_MSCTSP as i32 {
+            cost = -cost;
+        }
+        if (*node).DepotId > ExternalSalesmen {
+            nodes.push((*Depot).Id);
+        }
+        routes.push(Route {
+            vehicle,
+            nodes,
+            cost: cost / GainType::from(Precision),
+            load,
+            duration: time - departure,
+        });
+        if ptr::eq(node, Depot) {
+            return Ok(routes);
+        }
+    }
+}
+
+/// Cost of travelling from `from` to `to`, both numbered within
+/// `DimensionSaved`. Asymmetric problems are solved on a doubled node set,
+/// where that arc is the edge from `from` to the copy of `to`.
+unsafe fn edge_cost(from: *mut Node, to: *mut Node) -> Result<GainType, LkhError> {
+    let to = if Dimension == DimensionSaved {
+        to
+    } else {
+        NodeSet.add(((*to).Id + DimensionSaved) as usize)
+    };
+    let cost = guarded_call(move || C.unwrap()(from, to))?;
+    Ok(GainType::from(cost - (*from).Pi - (*to).Pi))
+}
+
+unsafe fn lower_bound_gap() -> Option<f64> {
+    if LowerBound <= 0.0 || BestCost == PLUS_INFINITY || BestCost <= 0 {
+        return None;
+    }
+    Some(100.0 * (BestCost as f64 - LowerBound) / BestCost as f64)
+}
+
+/// Gap to the known optimum, following LKH's `StatusReport`.
+unsafe fn optimum_gap() -> Option<f64> {
+    let value = if OptimizePenalty != 0 {
+        BestPenalty
+    } else {
+        BestCost
+    };
+    if Optimum == MINUS_INFINITY || Optimum == 0 || value == PLUS_INFINITY {
+        return None;
+    }
+    let sign = if ProblemType == Types_MSCTSP as i32 {
+        -1.0
+    } else {
+        1.0
+    };
+    Some(sign * 100.0 * (value as f64 - Optimum as f64) / Optimum as f64)
+}
+
 unsafe fn smaller_fitness(penalty: GainType, cost: GainType, i: isize) -> bool {
     (penalty < *PenaltyFitness.offset(i))
         || (penalty == *PenaltyFitness.offset(i) && cost < *Fitness.offset(i))
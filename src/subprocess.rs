@@ -0,0 +1,832 @@
+//! Out-of-process solving for crash isolation.
+//!
+//! The in-process solver turns LKH's `eprintf()` diagnostics into
+//! `LkhError::Native`, but the C core can still crash or call `exit` from
+//! paths that are not guarded. `SubprocessBackend` moves each solve into a
+//! child worker instead. Parent and child exchange length-prefixed messages
+//! over the child's stdin/stdout, and a child that dies before answering
+//! surfaces as `LkhError::NativeAbort`.
+//!
+//! The in-process functions in [`crate::solver`] remain the default. The worker
+//! side is the hidden `lkh worker` subcommand, or [`run_worker`] for
+//! applications that embed it in their own binary.
+
+use crate::error::LkhError;
+use crate::observer::IgnoreEvents;
+use crate::problem::{RoutingProblem, SearchParameters};
+use crate::solver::{
+    self, InputTours, NativeOutput, ProgrammaticSolveOptions, Route, RunResult, SolveOptions,
+    SolveReport, TerminationReason,
+};
+use std::ffi::OsString;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Version of the parent/worker protocol spoken by this release.
+///
+/// Both sides reject messages carrying a different version. The version only
+/// changes between releases, so the worker binary must be built from the same
+/// sources as the library that starts it.
+pub const WORKER_PROTOCOL_VERSION: u32 = 1;
+
+const PROTOCOL_MAGIC: &str = "LKH-RS-WORKER";
+const END_MARKER: &str = "END";
+
+/// Runs solves in a child worker process.
+///
+/// Each call spawns a fresh worker, sends one request, and waits for the
+/// report. LKH state never leaks between calls, and a native abort in the
+/// child is returned as `LkhError::NativeAbort` with the child's stderr.
+#[derive(Debug, Clone)]
+pub struct SubprocessBackend {
+    program: PathBuf,
+    args: Vec<OsString>,
+}
+
+impl SubprocessBackend {
+    /// Use `<program> worker` as the child command.
+    ///
+    /// `program` is usually the `lkh` binary installed with this crate.
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self::with_args(program, ["worker"])
+    }
+
+    /// Use a custom child command that ends up calling [`run_worker`].
+    pub fn with_args<I, S>(program: impl Into<PathBuf>, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        Self {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Return the worker program.
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Solve a programmatic problem in a worker process.
+    pub fn solve_problem(
+        &self,
+        problem: &RoutingProblem,
+        parameters: &SearchParameters,
+    ) -> Result<SolveReport, LkhError> {
+        self.solve_problem_with_options(problem, parameters, ProgrammaticSolveOptions::default())
+    }
+
+    /// Solve a programmatic problem in a worker process with native options.
+    pub fn solve_problem_with_options(
+        &self,
+        problem: &RoutingProblem,
+        parameters: &SearchParameters,
+        options: ProgrammaticSolveOptions,
+    ) -> Result<SolveReport, LkhError> {
+        self.run(&WorkerRequest::problem(problem, parameters, options)?)
+    }
+
+    /// Solve an LKH parameter file in a worker process.
+    pub fn solve_parameter_file(&self, path: impl AsRef<Path>) -> Result<SolveReport, LkhError> {
+        self.solve_with_options(SolveOptions::new(path.as_ref().to_path_buf()))
+    }
+
+    /// Solve an LKH parameter file in a worker process with explicit options.
+    ///
+    /// Relative paths are resolved by the worker, which inherits the current
+    /// working directory of the parent.
+    pub fn solve_with_options(&self, options: SolveOptions) -> Result<SolveReport, LkhError> {
+        self.run(&WorkerRequest::ParameterFile(options))
+    }
+
+    fn run(&self, request: &WorkerRequest) -> Result<SolveReport, LkhError> {
+        let mut worker = WorkerProcess::spawn(self)?;
+        let result = worker.solve(request, None);
+        worker.shutdown();
+        result
+    }
+}
+
+/// A running worker child that serves requests until its stdin is closed.
+///
+/// A reader thread forwards responses over a channel so that callers can wait
+/// with a timeout, and a second thread collects stderr for
+/// `LkhError::NativeAbort`.
+pub(crate) struct WorkerProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    responses: Receiver<Result<Option<Message>, LkhError>>,
+    stdout_reader: Option<JoinHandle<()>>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+    stderr_reader: Option<JoinHandle<()>>,
+    exited: bool,
+}
+
+impl WorkerProcess {
+    pub(crate) fn spawn(backend: &SubprocessBackend) -> Result<Self, LkhError> {
+        let mut child = Command::new(&backend.program)
+            .args(&backend.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|source| LkhError::WorkerSpawn {
+                program: backend.program.clone(),
+                source,
+            })?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let stderr = Arc::new(Mutex::new(Vec::new()));
+        let stderr_reader = child
+            .stderr
+            .take()
+            .map(|output| spawn_stderr_reader(output, Arc::clone(&stderr)));
+        let (sender, responses) = mpsc::channel();
+        let stdout_reader = stdout.map(|stdout| {
+            thread::spawn(move || {
+                let mut input = BufReader::new(stdout);
+                loop {
+                    let response = Message::read_from(&mut input);
+                    let done = !matches!(response, Ok(Some(_)));
+                    if sender.send(response).is_err() || done {
+                        break;
+                    }
+                }
+            })
+        });
+        Ok(Self {
+            child,
+            stdin,
+            responses,
+            stdout_reader,
+            stderr,
+            stderr_reader,
+            exited: false,
+        })
+    }
+
+    /// Whether the child can still accept requests.
+    pub(crate) fn is_alive(&self) -> bool {
+        !self.exited
+    }
+
+    /// Send one request and wait for its response.
+    ///
+    /// When `timeout` elapses the child is killed and
+    /// `LkhError::WorkerTimeout` is returned. A child that dies without
+    /// answering is reported as `LkhError::NativeAbort`. In both cases the
+    /// process is no longer alive afterwards.
+    pub(crate) fn solve(
+        &mut self,
+        request: &WorkerRequest,
+        timeout: Option<Duration>,
+    ) -> Result<SolveReport, LkhError> {
+        let message = request.to_message()?;
+        self.clear_stderr();
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| LkhError::WorkerProtocol("worker stdin is not available".to_owned()))?;
+        let written = message.write_to(stdin);
+
+        let response = match (&written, timeout) {
+            (Err(_), _) => None,
+            (Ok(()), Some(timeout)) => match self.responses.recv_timeout(timeout) {
+                Ok(response) => Some(response),
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.child.kill();
+                    self.wait();
+                    return Err(LkhError::WorkerTimeout { timeout });
+                }
+                Err(RecvTimeoutError::Disconnected) => None,
+            },
+            (Ok(()), None) => self.responses.recv().ok(),
+        };
+        if let Some(Ok(Some(response))) = response {
+            return decode_response(&response).map(|report| request.forward_native_log(report));
+        }
+
+        // No usable response: the worker is either dead or out of sync.
+        let status = self.wait();
+        match (status, written, response) {
+            (Some(status), _, _) if !status.success() => Err(LkhError::NativeAbort {
+                exit_code: status.code(),
+                stderr: self.take_stderr(),
+            }),
+            (_, Err(source), _) => Err(LkhError::WorkerIo(source)),
+            (_, _, Some(Err(err))) => Err(err),
+            _ => Err(LkhError::WorkerProtocol(
+                "worker exited without sending a response".to_owned(),
+            )),
+        }
+    }
+
+    /// Close stdin and wait for the child to exit.
+    pub(crate) fn shutdown(mut self) {
+        self.wait();
+    }
+
+    fn wait(&mut self) -> Option<ExitStatus> {
+        // Closing stdin tells the worker that no further requests follow.
+        self.stdin = None;
+        self.exited = true;
+        let status = self.child.wait().ok();
+        if let Some(reader) = self.stdout_reader.take() {
+            let _ = reader.join();
+        }
+        if let Some(reader) = self.stderr_reader.take() {
+            let _ = reader.join();
+        }
+        status
+    }
+
+    fn clear_stderr(&self) {
+        if let Ok(mut stderr) = self.stderr.lock() {
+            stderr.clear();
+        }
+    }
+
+    fn take_stderr(&self) -> String {
+        self.stderr
+            .lock()
+            .map(|mut stderr| String::from_utf8_lossy(&std::mem::take(&mut *stderr)).into_owned())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for WorkerProcess {
+    fn drop(&mut self) {
+        if !self.exited {
+            self.wait();
+        }
+    }
+}
+
+/// Serve solve requests from stdin until it is closed.
+///
+/// This is the child side of [`SubprocessBackend`]. Before reading the first
+/// request the worker moves the process stdout to stderr, so LKH trace output
+/// cannot corrupt protocol messages.
+pub fn run_worker() -> Result<(), LkhError> {
+    let mut output = protocol_output()?;
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    while let Some(message) = Message::read_from(&mut input)? {
+        let response = WorkerRequest::from_message(&message).and_then(handle_request);
+        encode_response(&response)
+            .write_to(&mut output)
+            .map_err(LkhError::WorkerIo)?;
+    }
+    Ok(())
+}
+
+fn handle_request(request: WorkerRequest) -> Result<SolveReport, LkhError> {
+    match request {
+        WorkerRequest::Problem {
+            problem_text,
+            parameter_text,
+            tours,
+            options,
+        } => solver::solve_rendered_problem(
+            problem_text,
+            parameter_text,
+            &tours,
+            &options,
+            &mut IgnoreEvents,
+            None,
+        ),
+        WorkerRequest::ParameterFile(options) => solver::solve_with_options(options),
+    }
+}
+
+#[cfg(unix)]
+fn protocol_output() -> Result<Box<dyn Write>, LkhError> {
+    use std::os::unix::io::FromRawFd;
+
+    // SAFETY: These calls only rearrange this process's own stdout/stderr
+    // descriptors. The duplicated descriptor is owned by the returned `File`.
+    unsafe {
+        libc::fflush(std::ptr::null_mut());
+        let protocol_fd = libc::dup(libc::STDOUT_FILENO);
+        if protocol_fd < 0 {
+            return Err(LkhError::WorkerIo(io::Error::last_os_error()));
+        }
+        if libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            let source = io::Error::last_os_error();
+            libc::close(protocol_fd);
+            return Err(LkhError::WorkerIo(source));
+        }
+        Ok(Box::new(std::fs::File::from_raw_fd(protocol_fd)))
+    }
+}
+
+#[cfg(not(unix))]
+fn protocol_output() -> Result<Box<dyn Write>, LkhError> {
+    Ok(Box::new(io::stdout()))
+}
+
+fn spawn_stderr_reader(
+    mut stderr: impl Read + Send + 'static,
+    buffer: Arc<Mutex<Vec<u8>>>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match stderr.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => match buffer.lock() {
+                    Ok(mut buffer) => buffer.extend_from_slice(&chunk[..read]),
+                    Err(_) => break,
+                },
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// One solve request as understood by the worker.
+pub(crate) enum WorkerRequest {
+    Problem {
+        problem_text: String,
+        parameter_text: String,
+        tours: InputTours,
+        options: ProgrammaticSolveOptions,
+    },
+    ParameterFile(SolveOptions),
+}
+
+impl WorkerRequest {
+    pub(crate) fn problem(
+        problem: &RoutingProblem,
+        parameters: &SearchParameters,
+        options: ProgrammaticSolveOptions,
+    ) -> Result<Self, LkhError> {
+        problem.validate()?;
+        problem.validate_scaled_weights(parameters)?;
+        problem.validate_native_distances()?;
+        Ok(Self::Problem {
+            problem_text: problem.to_tsplib(),
+            parameter_text: parameters
+                .to_lkh_parameter_file(solver::IN_MEMORY_PROBLEM_FILE_NAME)?,
+            tours: InputTours::from_parameters(parameters)?,
+            options,
+        })
+    }
+
+    fn to_message(&self) -> Result<Message, LkhError> {
+        match self {
+            Self::Problem {
+                problem_text,
+                parameter_text,
+                tours,
+                options,
+            } => {
+                let mut message = Message::new("PROBLEM")
+                    .field("problem", problem_text)
+                    .field("parameters", parameter_text)
+                    .field("max_matrix_dimension", options.max_matrix_dimension);
+                if let Some(trace_level) = options.trace_level_override {
+                    message = message.field("trace_level", trace_level);
+                }
+                // The worker always captures; `forward_native_log` logs the
+                // text on this side, where the application's logger lives.
+                if options.native_output != NativeOutput::Inherit {
+                    message = message.field("capture_native_output", 1);
+                }
+                if let Some(tour) = &tours.initial {
+                    message = message.field("initial_tour", join_numbers(tour));
+                }
+                if let Some(tour) = &tours.input {
+                    message = message.field("input_tour", join_numbers(tour));
+                }
+                if !tours.merge.is_empty() {
+                    let lines: Vec<_> = tours.merge.iter().map(|tour| join_numbers(tour)).collect();
+                    message = message.field("merge_tours", lines.join("\n"));
+                }
+                if let Some(tour) = &tours.subproblem {
+                    message = message.field("subproblem_tour", join_numbers(tour));
+                }
+                Ok(message)
+            }
+            Self::ParameterFile(options) => {
+                let mut message = Message::new("PARAMETER_FILE")
+                    .field("parameter_file", utf8_path(&options.parameter_file)?)
+                    .field("max_matrix_dimension", options.max_matrix_dimension);
+                if let Some(trace_level) = options.trace_level {
+                    message = message.field("trace_level", trace_level);
+                }
+                if let Some(working_directory) = &options.working_directory {
+                    message = message.field("working_directory", utf8_path(working_directory)?);
+                }
+                Ok(message)
+            }
+        }
+    }
+
+    /// Send the output a worker captured for [`NativeOutput::Log`] to this
+    /// process's logger.
+    fn forward_native_log(&self, mut report: SolveReport) -> SolveReport {
+        if let Self::Problem { options, .. } = self {
+            if let NativeOutput::Log(level) = options.native_output {
+                for line in report.native_log.take().unwrap_or_default().lines() {
+                    log::log!(target: "lkh", level, "{line}");
+                }
+            }
+        }
+        report
+    }
+
+    fn from_message(message: &Message) -> Result<Self, LkhError> {
+        match message.kind.as_str() {
+            "PROBLEM" => Ok(Self::Problem {
+                problem_text: message.require("problem")?.to_owned(),
+                parameter_text: message.require("parameters")?.to_owned(),
+                tours: InputTours {
+                    initial: message.get("initial_tour").map(parse_numbers).transpose()?,
+                    input: message.get("input_tour").map(parse_numbers).transpose()?,
+                    merge: message
+                        .get("merge_tours")
+                        .map(|text| text.lines().map(parse_numbers).collect())
+                        .transpose()?
+                        .unwrap_or_default(),
+                    subproblem: message
+                        .get("subproblem_tour")
+                        .map(parse_numbers)
+                        .transpose()?,
+                },
+                options: ProgrammaticSolveOptions {
+                    trace_level_override: message.parse_optional("trace_level")?,
+                    max_matrix_dimension: message.parse("max_matrix_dimension")?,
+                    cancellation: None,
+                    native_output: if message.get("capture_native_output").is_some() {
+                        NativeOutput::Capture
+                    } else {
+                        NativeOutput::Inherit
+                    },
+                },
+            }),
+            "PARAMETER_FILE" => {
+                let mut options = SolveOptions::new(message.require("parameter_file")?);
+                options.trace_level = message.parse_optional("trace_level")?;
+                options.max_matrix_dimension = message.parse("max_matrix_dimension")?;
+                options.working_directory = message.get("working_directory").map(PathBuf::from);
+                Ok(Self::ParameterFile(options))
+            }
+            other => Err(LkhError::WorkerProtocol(format!(
+                "unknown request kind {other:?}"
+            ))),
+        }
+    }
+}
+
+fn utf8_path(path: &Path) -> Result<&str, LkhError> {
+    path.to_str()
+        .ok_or_else(|| LkhError::NonUtf8Path(path.to_path_buf()))
+}
+
+fn encode_response(response: &Result<SolveReport, LkhError>) -> Message {
+    match response {
+        Ok(report) => {
+            let message = Message::new("REPORT")
+                .field("best_cost", report.best_cost)
+                .field("best_cost_unscaled", report.best_cost_unscaled)
+                .field("best_penalty", report.best_penalty)
+                .field("runs", report.runs)
+                .field("dimension", report.dimension)
+                .field("tour", join_numbers(&report.tour))
+                .field("termination", report.termination.as_str())
+                .field("lower_bound", report.lower_bound)
+                .field("optimum", optional(report.optimum))
+                .field("gap", optional(report.gap))
+                .field("lower_bound_gap", optional(report.lower_bound_gap))
+                .field("run_results", encode_run_results(&report.run_results))
+                .field("routes", encode_routes(report.routes()))
+                .field("preprocessing_time", report.preprocessing_time.as_nanos())
+                .field("wall_time", report.wall_time.as_nanos());
+            match &report.native_log {
+                Some(native_log) => message.field("native_log", native_log),
+                None => message,
+            }
+        }
+        Err(err) => {
+            let (kind, message) = match err {
+                LkhError::InvalidProblem(message) => ("invalid_problem", message.clone()),
+                LkhError::InvalidSearchParameters(message) => {
+                    ("invalid_search_parameters", message.clone())
+                }
+                LkhError::UnsupportedProgrammaticParameter(message) => {
+                    ("unsupported_programmatic_parameter", message.clone())
+                }
+                LkhError::InMemoryInitialization(message) => {
+                    ("in_memory_initialization", message.clone())
+                }
+                LkhError::ParameterFileNotFound(path) => {
+                    ("parameter_file_not_found", path.display().to_string())
+                }
+                LkhError::MissingBestTour => ("missing_best_tour", String::new()),
+                LkhError::Cancelled => ("cancelled", String::new()),
+                LkhError::Native(message) => ("native", message.clone()),
+                other => ("other", other.to_string()),
+            };
+            Message::new("ERROR")
+                .field("kind", kind)
+                .field("message", message)
+        }
+    }
+}
+
+fn decode_response(message: &Message) -> Result<SolveReport, LkhError> {
+    match message.kind.as_str() {
+        "REPORT" => Ok(SolveReport {
+            best_cost: message.parse("best_cost")?,
+            best_cost_unscaled: message.parse("best_cost_unscaled")?,
+            best_penalty: message.parse("best_penalty")?,
+            runs: message.parse("runs")?,
+            dimension: message.parse("dimension")?,
+            tour: parse_numbers(message.require("tour")?)?,
+            termination: parse_termination(message.require("termination")?)?,
+            lower_bound: message.parse("lower_bound")?,
+            lower_bound_gap: parse_optional_value(message.require("lower_bound_gap")?)?,
+            optimum: parse_optional_value(message.require("optimum")?)?,
+            gap: parse_optional_value(message.require("gap")?)?,
+            run_results: decode_run_results(message.require("run_results")?)?,
+            preprocessing_time: message.parse_duration("preprocessing_time")?,
+            wall_time: message.parse_duration("wall_time")?,
+            native_log: message.get("native_log").map(str::to_owned),
+            routes: decode_routes(message.require("routes")?)?,
+        }),
+        "ERROR" => {
+            let text = message.require("message")?.to_owned();
+            Err(match message.require("kind")? {
+                "invalid_problem" => LkhError::InvalidProblem(text),
+                "invalid_search_parameters" => LkhError::InvalidSearchParameters(text),
+                "unsupported_programmatic_parameter" => {
+                    LkhError::UnsupportedProgrammaticParameter(text)
+                }
+                "in_memory_initialization" => LkhError::InMemoryInitialization(text),
+                "parameter_file_not_found" => LkhError::ParameterFileNotFound(text.into()),
+                "missing_best_tour" => LkhError::MissingBestTour,
+                "cancelled" => LkhError::Cancelled,
+                "native" => LkhError::Native(text),
+                _ => LkhError::WorkerFailed(text),
+            })
+        }
+        other => Err(LkhError::WorkerProtocol(format!(
+            "unknown response kind {other:?}"
+        ))),
+    }
+}
+
+/// Render an optional value as the value itself or an empty string.
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn parse_optional_value<T: FromStr>(text: &str) -> Result<Option<T>, LkhError> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse()
+        .map(Some)
+        .map_err(|_| LkhError::WorkerProtocol(format!("invalid value {text:?}")))
+}
+
+/// One `run cost penalty trials time_nanos` line per run.
+fn encode_run_results(results: &[RunResult]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            format!(
+                "{} {} {} {} {}\n",
+                result.run,
+                result.cost,
+                result.penalty,
+                result.trials,
+                result.time.as_nanos()
+            )
+        })
+        .collect()
+}
+
+fn decode_run_results(text: &str) -> Result<Vec<RunResult>, LkhError> {
+    text.lines()
+        .map(|line| {
+            let mut values = line.split_ascii_whitespace();
+            let mut next = || {
+                values
+                    .next()
+                    .ok_or_else(|| LkhError::WorkerProtocol(format!("invalid run result {line:?}")))
+            };
+            Ok(RunResult {
+                run: parse_number(next()?)?,
+                cost: parse_number(next()?)?,
+                penalty: parse_number(next()?)?,
+                trials: parse_number(next()?)?,
+                time: Duration::from_nanos(parse_number(next()?)?),
+            })
+        })
+        .collect()
+}
+
+/// One `vehicle cost load duration node...` line per route.
+fn encode_routes(routes: &[Route]) -> String {
+    routes
+        .iter()
+        .map(|route| {
+            format!(
+                "{} {} {} {} {}\n",
+                route.vehicle,
+                route.cost,
+                route.load,
+                route.duration,
+                join_numbers(&route.nodes)
+            )
+        })
+        .collect()
+}
+
+fn decode_routes(text: &str) -> Result<Vec<Route>, LkhError> {
+    text.lines()
+        .map(|line| {
+            let mut values = line.split_ascii_whitespace();
+            let mut next = || {
+                values
+                    .next()
+                    .ok_or_else(|| LkhError::WorkerProtocol(format!("invalid route {line:?}")))
+            };
+            Ok(Route {
+                vehicle: parse_number(next()?)?,
+                cost: parse_number(next()?)?,
+                load: parse_number(next()?)?,
+                duration: parse_number(next()?)?,
+                nodes: values.map(parse_number).collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
+}
+
+fn parse_termination(name: &str) -> Result<TerminationReason, LkhError> {
+    match name {
+        "runs_exhausted" => Ok(TerminationReason::RunsExhausted),
+        "time_limit" => Ok(TerminationReason::TimeLimit),
+        "optimum_reached" => Ok(TerminationReason::OptimumReached),
+        "cancelled" => Ok(TerminationReason::Cancelled),
+        other => Err(LkhError::WorkerProtocol(format!(
+            "unknown termination reason {other:?}"
+        ))),
+    }
+}
+
+fn join_numbers<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_numbers<T: FromStr>(text: &str) -> Result<Vec<T>, LkhError> {
+    text.split_ascii_whitespace().map(parse_number).collect()
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T, LkhError> {
+    value
+        .parse()
+        .map_err(|_| LkhError::WorkerProtocol(format!("invalid number {value:?}")))
+}
+
+/// A framed protocol message.
+///
+/// The wire format is a `LKH-RS-WORKER <version> <KIND>` header line, then one
+/// `<name> <byte length>` line per field followed by exactly that many bytes
+/// and a newline, then `END`. Length prefixes let problem and parameter text
+/// travel verbatim without escaping.
+pub(crate) struct Message {
+    kind: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Message {
+    fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.to_owned(),
+            fields: Vec::new(),
+        }
+    }
+
+    fn field(mut self, name: &str, value: impl ToString) -> Self {
+        self.fields.push((name.to_owned(), value.to_string()));
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn require(&self, name: &str) -> Result<&str, LkhError> {
+        self.get(name).ok_or_else(|| {
+            LkhError::WorkerProtocol(format!("{} message is missing {name:?}", self.kind))
+        })
+    }
+
+    fn parse<T: FromStr>(&self, name: &str) -> Result<T, LkhError> {
+        let value = self.require(name)?;
+        value
+            .parse()
+            .map_err(|_| LkhError::WorkerProtocol(format!("invalid value {value:?} for {name:?}")))
+    }
+
+    fn parse_optional<T: FromStr>(&self, name: &str) -> Result<Option<T>, LkhError> {
+        self.get(name).map(|_| self.parse(name)).transpose()
+    }
+
+    fn parse_duration(&self, name: &str) -> Result<Duration, LkhError> {
+        self.parse(name).map(Duration::from_nanos)
+    }
+
+    fn write_to(&self, output: &mut impl Write) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        writeln!(
+            &mut buffer,
+            "{PROTOCOL_MAGIC} {WORKER_PROTOCOL_VERSION} {}",
+            self.kind
+        )?;
+        for (name, value) in &self.fields {
+            writeln!(&mut buffer, "{name} {}", value.len())?;
+            buffer.extend_from_slice(value.as_bytes());
+            buffer.push(b'\n');
+        }
+        writeln!(&mut buffer, "{END_MARKER}")?;
+        output.write_all(&buffer)?;
+        output.flush()
+    }
+
+    /// Read one message, or `None` if the stream ends before a header.
+    fn read_from(input: &mut impl BufRead) -> Result<Option<Self>, LkhError> {
+        let Some(header) = read_line(input)? else {
+            return Ok(None);
+        };
+        let mut parts = header.split(' ');
+        if parts.next() != Some(PROTOCOL_MAGIC) {
+            return Err(LkhError::WorkerProtocol(format!(
+                "unexpected header {header:?}"
+            )));
+        }
+        let version = parts.next().and_then(|value| value.parse::<u32>().ok());
+        if version != Some(WORKER_PROTOCOL_VERSION) {
+            return Err(LkhError::WorkerProtocol(format!(
+                "unsupported protocol version in {header:?}, expected {WORKER_PROTOCOL_VERSION}"
+            )));
+        }
+        let kind = parts
+            .next()
+            .ok_or_else(|| LkhError::WorkerProtocol(format!("header {header:?} has no kind")))?;
+        let mut message = Self::new(kind);
+        loop {
+            let line = read_line(input)?.ok_or_else(|| {
+                LkhError::WorkerProtocol("stream ended inside a message".to_owned())
+            })?;
+            if line == END_MARKER {
+                return Ok(Some(message));
+            }
+            let (name, length) = line
+                .split_once(' ')
+                .and_then(|(name, length)| Some((name, length.parse::<usize>().ok()?)))
+                .ok_or_else(|| LkhError::WorkerProtocol(format!("invalid field line {line:?}")))?;
+            let mut value = vec![0; length + 1];
+            input.read_exact(&mut value).map_err(LkhError::WorkerIo)?;
+            if value.pop() != Some(b'\n') {
+                return Err(LkhError::WorkerProtocol(format!(
+                    "field {name:?} is not newline terminated"
+                )));
+            }
+            let value = String::from_utf8(value).map_err(|_| {
+                LkhError::WorkerProtocol(format!("field {name:?} is not valid UTF-8"))
+            })?;
+            message.fields.push((name.to_owned(), value));
+        }
+    }
+}
+
+fn read_line(input: &mut impl BufRead) -> Result<Option<String>, LkhError> {
+    let mut line = String::new();
+    let read = input.read_line(&mut line).map_err(LkhError::WorkerIo)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+    }
+    Ok(Some(line))
+}
@@ -17,3 +17,341 @@ fn cli_solves_tiny_fixture() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("Best cost:"), "stdout:\n{stdout}");
 }
+
+#[test]
+fn cli_solves_problem_file_with_flags() {
+    let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+        .args([
+            "solve",
+            "tests/fixtures/tiny.tsp",
+            "--runs",
+            "2",
+            "--seed",
+            "7",
+        ])
+        .args(["--time-limit", "5", "--max-trials", "10"])
+        .args(["--lkh", "MOVE_TYPE=3", "--lkh", "KICKS = 2"])
+        .output()
+        .expect("run lkh binary");
+
+    assert!(
+        output.status.success(),
+        "status: {:?}\nstderr:\n{}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Best cost: 4"), "stdout:\n{stdout}");
+    assert!(stdout.contains("Runs: 2"), "stdout:\n{stdout}");
+
+    for bad in [
+        ["--lkh", "MOVE_TYPE"],
+        ["--lkh", "MOVE_TYPE=1"],
+        ["--lkh", "TOUR_FILE=out.tour"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+            .args(["solve", "tests/fixtures/tiny.tsp"])
+            .args(bad)
+            .output()
+            .expect("run lkh binary");
+        assert!(!output.status.success(), "{bad:?} should fail");
+    }
+}
+
+#[test]
+fn cli_writes_machine_readable_reports() {
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+            .args(["solve", "tests/fixtures/tiny.tsp"])
+            .args(args)
+            .output()
+            .expect("run lkh binary");
+        assert!(
+            output.status.success(),
+            "stderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("utf-8 output")
+    };
+
+    let json: serde_json::Value =
+        serde_json::from_str(&run(&["--output-format", "json"])).expect("valid json");
+    assert_eq!(json["best_cost"], 4);
+    assert_eq!(json["tour"].as_array().map(Vec::len), Some(4));
+    assert_eq!(json["termination"], "runs_exhausted");
+    assert_eq!(json["run_results"][0]["run"], 1);
+    assert!(json["native_log"].is_null());
+    assert!(json["mtsp_solution"].is_null());
+    // The report is the serde form of SolveReport.
+    let report: lkh_rs::SolveReport =
+        serde_json::from_value(json.clone()).expect("deserialize report");
+    assert_eq!(report.best_cost, 4);
+
+    let tour = run(&["--output-format", "tour"]);
+    assert!(tour.starts_with("NAME : tiny.tour\n"), "tour:\n{tour}");
+    assert!(tour.contains("TOUR_SECTION\n"), "tour:\n{tour}");
+    assert!(tour.ends_with("-1\nEOF\n"), "tour:\n{tour}");
+
+    let csv = run(&["--output-format", "csv"]);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "route,position,node");
+    assert_eq!(lines.len(), 5, "csv:\n{csv}");
+    assert!(lines[1].starts_with("1,1,"), "csv:\n{csv}");
+
+    let path = std::env::temp_dir().join(format!("lkh-rs-cli-{}.tour", std::process::id()));
+    let stdout = run(&[
+        "--output-format",
+        "tour",
+        "--output",
+        path.to_str().unwrap(),
+    ]);
+    assert!(stdout.is_empty(), "stdout:\n{stdout}");
+    let written = std::fs::read_to_string(&path).expect("read written tour");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(written, tour);
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_keeps_lkh_progress_out_of_piped_reports() {
+    let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+        .args([
+            "--par",
+            "tests/fixtures/tiny.par",
+            "--output-format",
+            "json",
+        ])
+        .output()
+        .expect("run lkh binary");
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("only json");
+    assert_eq!(json["best_cost"], 4);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("PROBLEM_FILE"), "stderr:\n{stderr}");
+}
+
+#[test]
+fn cli_benchmarks_a_directory_against_optimal_tours() {
+    let dir = std::env::temp_dir().join(format!("lkh-rs-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    std::fs::copy("tests/fixtures/tiny.tsp", dir.join("tiny.tsp")).expect("copy problem");
+    std::fs::write(
+        dir.join("tiny.opt.tour"),
+        "NAME : tiny.opt.tour\nTYPE : TOUR\nDIMENSION : 4\nTOUR_SECTION\n1 2 3 4\n-1\nEOF\n",
+    )
+    .expect("write optimal tour");
+    std::fs::write(dir.join("notes.txt"), "not a problem\n").expect("write notes");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+            .arg("bench")
+            .arg(&dir)
+            .args(["--params", "tests/fixtures/tiny.par", "--seeds", "1..3"])
+            .args(args)
+            .output()
+            .expect("run lkh binary");
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+        )
+    };
+
+    let (success, json) = run(&["--repeat", "2", "--output-format", "json"]);
+    assert!(success, "stdout:\n{json}");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("valid json");
+    let instances = json.as_array().expect("one row per instance");
+    assert_eq!(instances.len(), 1);
+    assert_eq!(instances[0]["instance"], "tiny");
+    assert_eq!(instances[0]["costs"].as_array().map(Vec::len), Some(6));
+    assert_eq!(instances[0]["best"], 4);
+    assert_eq!(instances[0]["optimum"], 4);
+    assert_eq!(instances[0]["gap"], 0.0);
+    let time: std::time::Duration =
+        serde_json::from_value(instances[0]["time"].clone()).expect("average time");
+    assert!(time > std::time::Duration::ZERO);
+
+    let (success, csv) = run(&["--output-format", "csv"]);
+    assert!(success, "stdout:\n{csv}");
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "instance,dimension,solves,best,average,optimum,gap,time,errors"
+    );
+    assert!(lines[1].starts_with("tiny,4,3,4,4,4,0,"), "csv:\n{csv}");
+
+    let (success, text) = run(&[]);
+    assert!(success);
+    assert!(text.starts_with("Instance"), "text:\n{text}");
+    assert!(!run(&["--output-format", "tour"]).0);
+    assert!(!run(&["--seeds", "3..1"]).0);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn cli_reports_unmeasurable_optimal_tours_per_instance() {
+    let dir = std::env::temp_dir().join(format!("lkh-rs-bench-bad-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    std::fs::copy("tests/fixtures/tiny.tsp", dir.join("tiny.tsp")).expect("copy problem");
+    std::fs::write(
+        dir.join("tiny.opt.tour"),
+        "TOUR_SECTION\n1 2 2 4\n-1\nEOF\n",
+    )
+    .expect("write tour");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+            .arg("bench")
+            .arg(&dir)
+            .args(args)
+            .output()
+            .expect("run lkh binary");
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        assert!(output.status.success(), "stdout:\n{stdout}");
+        stdout
+    };
+
+    let json = run(&["--output-format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&json).expect("valid json");
+    let instance = &json.as_array().expect("one row per instance")[0];
+    assert_eq!(instance["best"], 4);
+    assert!(instance["optimum"].is_null());
+    let errors = instance["errors"].as_array().expect("errors");
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0]
+            .as_str()
+            .is_some_and(|error| error.contains(".opt.tour")),
+        "errors: {errors:?}"
+    );
+
+    // The OPTIMUM of --params stands in for the tour.
+    let json = run(&[
+        "--params",
+        "tests/fixtures/tiny.par",
+        "--output-format",
+        "json",
+    ]);
+    let json: serde_json::Value = serde_json::from_str(&json).expect("valid json");
+    assert_eq!(json[0]["optimum"], 4);
+
+    let text = run(&[]);
+    assert!(
+        text.contains("tiny: cannot measure the .opt.tour"),
+        "text:\n{text}"
+    );
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn cli_benchmarks_past_failed_solves() {
+    let dir = std::env::temp_dir().join(format!("lkh-rs-bench-failed-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    std::fs::copy("tests/fixtures/tiny.tsp", dir.join("tiny.tsp")).expect("copy problem");
+    // Two vehicles cannot carry the demand, which LKH only finds when solving.
+    std::fs::write(
+        dir.join("overloaded.vrp"),
+        "NAME: over, \"loaded\"\nTYPE: CVRP\nDIMENSION: 4\nVEHICLES: 2\nCAPACITY: 1\n\
+         EDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 0 1\n3 1 0\n4 1 1\n\
+         DEMAND_SECTION\n1 0\n2 1\n3 1\n4 1\nDEPOT_SECTION\n1\n-1\nEOF\n",
+    )
+    .expect("write problem");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+            .arg("bench")
+            .arg(&dir)
+            .args(["--seeds", "1,2"])
+            .args(args)
+            .output()
+            .expect("run lkh binary");
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+        )
+    };
+
+    let (success, json) = run(&["--output-format", "json"]);
+    assert!(success, "stdout:\n{json}");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("valid json");
+    let instances = json.as_array().expect("one row per instance");
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0]["instance"], "over, \"loaded\"");
+    assert_eq!(instances[0]["costs"].as_array().map(Vec::len), Some(0));
+    assert!(instances[0]["best"].is_null());
+    assert_eq!(instances[0]["errors"].as_array().map(Vec::len), Some(2));
+    assert_eq!(instances[1]["instance"], "tiny");
+    assert_eq!(instances[1]["best"], 4);
+
+    let (success, csv) = run(&["--output-format", "csv"]);
+    assert!(success, "stdout:\n{csv}");
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(
+        lines[1].starts_with(r#""over, ""loaded""",4,0,,,,,,"seed 1: "#),
+        "csv:\n{csv}"
+    );
+    assert!(lines[2].starts_with("tiny,4,2,4,4,,,"), "csv:\n{csv}");
+
+    let (success, text) = run(&[]);
+    assert!(success, "text:\n{text}");
+    assert!(text.contains("seed 2: "), "text:\n{text}");
+
+    assert!(!run(&["--repeat", "0"]).0);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn cli_benchmarks_against_the_length_of_a_non_optimal_tour() {
+    let dir = std::env::temp_dir().join(format!("lkh-rs-bench-spiral-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    let points: Vec<(f64, f64)> = (0..30)
+        .map(|i| {
+            let angle = f64::from(i) * 2.399_963;
+            let radius = f64::from(i).sqrt() * 100.0;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+    let problem = lkh_rs::RoutingProblem::named_euclidean_2d("spiral", points.clone())
+        .expect("build problem");
+    std::fs::write(dir.join("spiral.tsp"), problem.to_tsplib()).expect("write problem");
+    // Visiting the points in index order zigzags across the spiral.
+    let order: Vec<String> = (1..=points.len()).map(|node| node.to_string()).collect();
+    std::fs::write(
+        dir.join("spiral.opt.tour"),
+        format!("TOUR_SECTION\n{}\n-1\nEOF\n", order.join("\n")),
+    )
+    .expect("write tour");
+    let length: i64 = (0..points.len())
+        .map(|i| {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % points.len()];
+            ((x1 - x2).hypot(y1 - y2) + 0.5) as i64
+        })
+        .sum();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_lkh"))
+        .arg("bench")
+        .arg(&dir)
+        .args([
+            "--params",
+            "tests/fixtures/tiny.par",
+            "--output-format",
+            "json",
+        ])
+        .output()
+        .expect("run lkh binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout:\n{stdout}");
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("valid json");
+    let instance = &json.as_array().expect("one row per instance")[0];
+    assert_eq!(instance["optimum"], length);
+    let best = instance["best"].as_i64().expect("best cost");
+    assert!(best < length, "best {best}, tour {length}");
+    let gap = instance["gap"].as_f64().expect("gap");
+    assert!(gap < 0.0 && gap > -100.0, "gap {gap}");
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,257 @@
+use lkh_rs::{
+    solve_problem, BwtspParameters, CandidateSetType, InitialTourAlgorithm, LkhError,
+    MtspObjective, MtspSettings, Partitioning, PatchingMode, ProblemEntry, ProblemKind,
+    Recombination, RoutingProblem, SearchParameters, SubproblemSettings,
+};
+use std::path::PathBuf;
+
+#[test]
+fn round_trips_problems_through_tsplib_text() {
+    let problems = [
+        RoutingProblem::named_euclidean_2d("square", [(0.0, 0.0), (0.0, 1.5), (1.0, 1.0)])
+            .expect("build euclidean problem"),
+        RoutingProblem::named("tiny_cvrp", ProblemKind::Cvrp, 4)
+            .expect("build base problem")
+            .with_keyword("CAPACITY", "3")
+            .expect("add capacity")
+            .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")
+            .expect("add weight type")
+            .with_keyword("EDGE_WEIGHT_FORMAT", "FULL_MATRIX")
+            .expect("add weight format")
+            .with_section(
+                "EDGE_WEIGHT_SECTION",
+                ["0 1 1 2", "1 0 2 1", "1 2 0 1", "2 1 1 0"],
+            )
+            .expect("add matrix")
+            .with_section("DEPOT_SECTION", ["1", "-1"])
+            .expect("add depot"),
+        RoutingProblem::custom_type("patched", "MY-TSP", 2).expect("build custom problem"),
+    ];
+
+    for problem in problems {
+        let parsed = RoutingProblem::from_tsplib_str(&problem.to_tsplib()).expect("parse");
+        assert_eq!(parsed, problem);
+    }
+}
+
+#[test]
+fn parses_tsplib_files_in_lkh_syntax() {
+    let problem = RoutingProblem::from_tsplib_path("tests/fixtures/tiny.tsp").expect("parse");
+    assert_eq!(problem.name(), "tiny");
+    assert_eq!(problem.kind(), ProblemKind::Tsp);
+    assert_eq!(problem.dimension(), 4);
+    assert_eq!(
+        problem.entries()[0],
+        ProblemEntry::Keyword {
+            key: "EDGE_WEIGHT_TYPE".to_owned(),
+            value: "EUC_2D".to_owned(),
+        }
+    );
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve parsed");
+    assert_eq!(report.best_cost, 4);
+
+    let text = "type = cvrp\r\n\r\ndimension 2\r\nedge_weight_type : EUC_2D\r\nNODE_COORD_SECTION\r\n 1 0 0\r\n 2 3 4\r\nEOF\r\nignored";
+    let problem = RoutingProblem::from_tsplib_str(text).expect("parse loose syntax");
+    assert_eq!(problem.kind(), ProblemKind::Cvrp);
+    assert_eq!(
+        problem.entries()[1],
+        ProblemEntry::Section {
+            key: "NODE_COORD_SECTION".to_owned(),
+            lines: vec![" 1 0 0".to_owned(), " 2 3 4".to_owned()],
+        }
+    );
+}
+
+#[test]
+fn reports_tsplib_errors_with_line_numbers() {
+    let cases = [
+        ("TYPE: TSP\nDIMENSION: two\n", 2),
+        ("TYPE: TSP\n1 0 0\nDIMENSION: 2\n", 2),
+        ("TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE:\n", 3),
+        ("NAME: missing_dimension\nTYPE: TSP\nEOF\n", 3),
+    ];
+    for (text, expected_line) in cases {
+        match RoutingProblem::from_tsplib_str(text) {
+            Err(LkhError::TsplibParse { line, .. }) => assert_eq!(line, expected_line, "{text:?}"),
+            other => panic!("expected a parse error for {text:?}, got {other:?}"),
+        }
+    }
+    assert!(matches!(
+        RoutingProblem::from_tsplib_path("tests/fixtures/missing.tsp"),
+        Err(LkhError::ReadFile { .. })
+    ));
+}
+
+#[test]
+fn round_trips_search_parameters_through_parameter_text() {
+    let parameters = SearchParameters::new()
+        .with_runs(3)
+        .with_trace_level(1)
+        .with_max_trials(40)
+        .with_move_type(5)
+        .with_patching_c(3)
+        .with_patching_a(2)
+        .with_seed(7)
+        .with_time_limit(1.5)
+        .with_total_time_limit(10.0)
+        .with_optimum(1234)
+        .with_stop_at_optimum(false)
+        .with_mtsp(
+            MtspSettings::new(3)
+                .with_objective(MtspObjective::MinMaxSize)
+                .with_min_size(0)
+                .with_max_size(4),
+        )
+        .with_max_candidates(6)
+        .with_max_candidates_symmetric(true)
+        .with_lkh_parameter("SINTEF_SOLUTION_FILE", "best.sol")
+        .expect("add expert parameter");
+
+    let text = parameters
+        .to_lkh_parameter_file("problem.tsp")
+        .expect("render parameters");
+    let parsed = SearchParameters::from_lkh_parameter_str(&text).expect("parse");
+
+    assert_eq!(parsed.parameters, parameters);
+    assert_eq!(parsed.problem_file, Some(PathBuf::from("problem.tsp")));
+}
+
+#[test]
+fn separates_file_names_from_search_settings() {
+    let text = "# benchmark settings\n\
+                PROBLEM_FILE = instances/my problem.vrp\n\
+                COMMENT = nightly\n\
+                move_type 5 SPECIAL\n\
+                STOP_AT_OPTIMUM = Y\n\
+                INITIAL_TOUR_FILE = start.tour\n\
+                MERGE_TOUR_FILE = a.tour\n\
+                MERGE_TOUR_FILE = b.tour\n\
+                MERGE_TOUR_FILE = a.tour\n\
+                OUTPUT_TOUR_FILE = best.$.tour\n\
+                SUBPROBLEM_SIZE = 1000 K-M comp\n\
+                SUBPROBLEM_TOUR_FILE = start.tour\n\
+                EOF\n\
+                RUNS = not read";
+    let parsed = SearchParameters::from_lkh_parameter_str(text).expect("parse");
+
+    assert_eq!(
+        parsed.problem_file,
+        Some(PathBuf::from("instances/my problem.vrp"))
+    );
+    assert_eq!(parsed.initial_tour_file, Some(PathBuf::from("start.tour")));
+    assert_eq!(
+        parsed.merge_tour_files,
+        [PathBuf::from("a.tour"), PathBuf::from("b.tour")]
+    );
+    assert_eq!(parsed.output_tour_file, Some(PathBuf::from("best.$.tour")));
+    assert_eq!(
+        parsed.subproblem_tour_file,
+        Some(PathBuf::from("start.tour"))
+    );
+    assert_eq!(
+        parsed.parameters.subproblem,
+        Some(SubproblemSettings::new(1000, Partitioning::KMeans).with_compressed(true))
+    );
+    assert_eq!(parsed.parameters.runs, 1);
+    assert_eq!(parsed.parameters.move_type, Some(5));
+    assert!(parsed.parameters.move_type_special);
+    assert_eq!(parsed.parameters.stop_at_optimum, Some(true));
+    assert!(parsed.parameters.additional_parameters.is_empty());
+
+    let fixture = SearchParameters::from_path("tests/fixtures/tiny.par").expect("parse fixture");
+    let problem_file = fixture.problem_file.expect("fixture names a problem");
+    let problem = RoutingProblem::from_tsplib_path(problem_file).expect("parse problem");
+    solve_problem(&problem, &fixture.parameters).expect("solve migrated fixture");
+}
+
+#[test]
+fn parses_every_search_parameter_into_typed_fields() {
+    let text = "CANDIDATE_SET_TYPE = D P\n\
+                INITIAL_TOUR_ALGORITHM = GR\n\
+                RECOMBINATION = gpx\n\
+                PATCHING_C = 3 R\n\
+                MAX_CANDIDATES = 6 SYM\n\
+                SUBSEQUENT_MOVE_TYPE = 5 SPECIAL\n\
+                BWTSP = 2 3\n\
+                GAIN23 = NO\n\
+                INITIAL_TOUR_FRACTION = 0.5\n\
+                SPECIAL\n";
+    let parameters = SearchParameters::from_lkh_parameter_str(text)
+        .expect("parse")
+        .parameters;
+    assert_eq!(
+        parameters.candidate_set_type,
+        Some(CandidateSetType::Delaunay { pure: true })
+    );
+    assert_eq!(
+        parameters.initial_tour_algorithm,
+        Some(InitialTourAlgorithm::Greedy)
+    );
+    assert_eq!(parameters.recombination, Some(Recombination::Gpx2));
+    assert_eq!(parameters.patching_c_mode, Some(PatchingMode::Restricted));
+    assert_eq!(parameters.max_candidates, Some(6));
+    assert!(parameters.max_candidates_symmetric);
+    assert!(parameters.subsequent_move_type_special);
+    assert_eq!(
+        parameters.bwtsp,
+        Some(BwtspParameters {
+            black_nodes: 2,
+            max_white_nodes: 3,
+            max_length: None,
+        })
+    );
+    assert_eq!(parameters.initial_tour_fraction, Some(0.5));
+    assert_eq!(parameters.kicks, Some(1));
+    assert_eq!(parameters.gain23, Some(false));
+    assert!(parameters.move_type_special);
+    assert!(parameters.additional_parameters.is_empty());
+
+    let rendered = parameters
+        .to_lkh_parameter_file("problem.tsp")
+        .expect("render parameters");
+    let reparsed = SearchParameters::from_lkh_parameter_str(&rendered).expect("reparse");
+    assert_eq!(reparsed.parameters, parameters);
+
+    let problem = RoutingProblem::from_tsplib_path("tests/fixtures/tiny.tsp").expect("parse");
+    let parameters = SearchParameters {
+        bwtsp: None,
+        ..parameters
+    };
+    let report = solve_problem(&problem, &parameters).expect("solve with typed parameters");
+    assert_eq!(report.best_cost, 4);
+
+    for invalid in [
+        SearchParameters::new().with_kick_type(2),
+        SearchParameters::new().with_initial_tour_fraction(1.5),
+        SearchParameters::new()
+            .with_move_type(4)
+            .with_move_type_special(true),
+        SearchParameters::new().with_max_candidates_symmetric(true),
+        SearchParameters::new().with_patching_a_mode(PatchingMode::Extended),
+    ] {
+        assert!(matches!(
+            invalid.validate(),
+            Err(LkhError::InvalidSearchParameters(_))
+        ));
+    }
+    assert!(SearchParameters::from_lkh_parameter_str("RECOMBINATION = X\n").is_err());
+}
+
+#[test]
+fn reports_parameter_errors_with_line_numbers() {
+    let cases = [
+        ("RUNS = 2\nMAX_TRIALS = many\n", 2),
+        ("\n\nSTOP_AT_OPTIMUM = maybe\n", 3),
+        ("PROBLEM_FILE =\n", 1),
+        ("RUNS = 1\nBAD-KEY = 1\n", 2),
+        ("SUBPROBLEM_SIZE = 500 KARP HILBERT\n", 1),
+    ];
+    for (text, expected_line) in cases {
+        match SearchParameters::from_lkh_parameter_str(text) {
+            Err(LkhError::ParameterParse { line, .. }) => {
+                assert_eq!(line, expected_line, "{text:?}")
+            }
+            other => panic!("expected a parse error for {text:?}, got {other:?}"),
+        }
+    }
+}
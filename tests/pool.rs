@@ -0,0 +1,101 @@
+use lkh_rs::{
+    LkhError, PoolOptions, ProblemKind, RoutingProblem, SearchParameters, SolverPool,
+    SubprocessBackend,
+};
+use std::time::Duration;
+
+fn backend() -> SubprocessBackend {
+    SubprocessBackend::new(env!("CARGO_BIN_EXE_lkh"))
+}
+
+fn square() -> RoutingProblem {
+    RoutingProblem::euclidean_2d([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])
+        .expect("build problem")
+}
+
+#[test]
+fn solves_batch_across_workers() {
+    let pool = SolverPool::new(
+        backend(),
+        PoolOptions {
+            workers: 2,
+            queue_capacity: 1,
+            ..PoolOptions::default()
+        },
+    )
+    .expect("start pool");
+    let problems = vec![square(); 6];
+
+    let results = pool.solve_all(&problems, &SearchParameters::new());
+
+    assert_eq!(pool.workers(), 2);
+    assert_eq!(results.len(), 6);
+    for result in results {
+        assert_eq!(result.expect("solve in pool").best_cost, 4);
+    }
+}
+
+#[test]
+fn recycles_workers_and_survives_native_errors() {
+    let pool = SolverPool::new(
+        backend(),
+        PoolOptions {
+            workers: 1,
+            max_jobs_per_worker: Some(2),
+            ..PoolOptions::default()
+        },
+    )
+    .expect("start pool");
+    let bad = RoutingProblem::named("bad_weight_type", ProblemKind::Tsp, 3)
+        .expect("build problem")
+        .with_keyword("EDGE_WEIGHT_TYPE", "NOT_A_METRIC")
+        .expect("add keyword");
+    let parameters = SearchParameters::new();
+
+    let err = pool
+        .submit(&bad, &parameters)
+        .expect("submit")
+        .wait()
+        .expect_err("LKH rejects the weight type");
+    assert!(matches!(err, LkhError::Native(_)), "got {err:?}");
+
+    for _ in 0..3 {
+        let report = pool.submit(&square(), &parameters).expect("submit").wait();
+        assert_eq!(report.expect("solve after error").best_cost, 4);
+    }
+}
+
+#[test]
+fn kills_and_replaces_worker_after_timeout() {
+    let pool = SolverPool::new(
+        backend(),
+        PoolOptions {
+            workers: 1,
+            job_timeout: Some(Duration::from_millis(200)),
+            ..PoolOptions::default()
+        },
+    )
+    .expect("start pool");
+    let mut state = 12345_u64;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 33) as f64
+    };
+    let points = (0..5000).map(|_| (next(), next())).collect::<Vec<_>>();
+    let large = RoutingProblem::euclidean_2d(points).expect("build problem");
+    let parameters = SearchParameters::new().with_runs(100);
+
+    let err = pool
+        .submit(&large, &parameters)
+        .expect("submit")
+        .wait()
+        .expect_err("job exceeds the timeout");
+    assert!(matches!(err, LkhError::WorkerTimeout { .. }), "got {err:?}");
+
+    let report = pool
+        .submit(&square(), &SearchParameters::new())
+        .expect("submit")
+        .wait()
+        .expect("replacement worker solves");
+    assert_eq!(report.best_cost, 4);
+}
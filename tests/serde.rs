@@ -0,0 +1,85 @@
+#![cfg(feature = "serde")]
+
+use lkh_rs::{
+    solve_problem, CandidateSetType, MtspSettings, Point2d, ProblemKind, RoutingProblem,
+    SearchParameters, SolveReport,
+};
+use serde_json::json;
+
+#[test]
+fn round_trips_problems_parameters_and_reports_through_json() {
+    let problem =
+        RoutingProblem::named_euclidean_2d("square", [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0)])
+            .expect("build problem");
+    let json = serde_json::to_value(&problem).expect("serialize problem");
+    assert_eq!(json["kind"], "TSP");
+    let parsed: RoutingProblem = serde_json::from_value(json).expect("deserialize problem");
+    assert_eq!(parsed, problem);
+
+    let parameters = SearchParameters::new()
+        .with_runs(2)
+        .with_seed(3)
+        .with_candidate_set_type(CandidateSetType::Delaunay { pure: true })
+        .with_mtsp(MtspSettings::new(2));
+    let json = serde_json::to_string(&parameters).expect("serialize parameters");
+    let parsed: SearchParameters = serde_json::from_str(&json).expect("deserialize parameters");
+    assert_eq!(parsed, parameters);
+
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve");
+    let json = serde_json::to_string(&report).expect("serialize report");
+    let parsed: SolveReport = serde_json::from_str(&json).expect("deserialize report");
+    assert_eq!(parsed, report);
+
+    assert_eq!(
+        serde_json::to_value(ProblemKind::OnePdtsp).expect("serialize kind"),
+        "1-PDTSP"
+    );
+    let kind: ProblemKind = serde_json::from_value(json!("pc-tsp")).expect("deserialize kind");
+    assert_eq!(kind, ProblemKind::PcTsp);
+    let point: Point2d = serde_json::from_value(json!({ "x": 1.5, "y": -2.0 })).expect("point");
+    assert_eq!(point, Point2d::new(1.5, -2.0));
+}
+
+#[test]
+fn deserialization_runs_validation() {
+    let parameters: SearchParameters =
+        serde_json::from_value(json!({ "runs": 4 })).expect("missing fields use defaults");
+    assert_eq!(parameters, SearchParameters::new().with_runs(4));
+
+    let invalid_parameters = [
+        json!({ "runs": 0 }),
+        json!({ "time_limit": -1.0 }),
+        json!({ "initial_tour": [1, 1, 2] }),
+        json!({ "additional_parameters": [["bad key", "1"]] }),
+    ];
+    for json in invalid_parameters {
+        assert!(
+            serde_json::from_value::<SearchParameters>(json.clone()).is_err(),
+            "{json}"
+        );
+    }
+
+    let invalid_problems = [
+        json!({ "name": "empty", "kind": "TSP", "dimension": 0, "entries": [] }),
+        json!({ "name": "bad\nname", "kind": "TSP", "dimension": 3, "entries": [] }),
+        json!({ "name": "p", "kind": " ", "dimension": 3, "entries": [] }),
+        json!({
+            "name": "p",
+            "kind": "TSP",
+            "dimension": 3,
+            "entries": [{ "Keyword": { "key": "edge_weight_type", "value": "EUC_2D" } }],
+        }),
+        json!({
+            "name": "p",
+            "kind": "TSP",
+            "dimension": 3,
+            "entries": [{ "Section": { "key": "NODE_COORD_SECTION", "lines": ["1 0\n0"] } }],
+        }),
+    ];
+    for json in invalid_problems {
+        assert!(
+            serde_json::from_value::<RoutingProblem>(json.clone()).is_err(),
+            "{json}"
+        );
+    }
+}
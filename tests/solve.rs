@@ -1,4 +1,13 @@
-use lkh_rs::{solve_parameter_file, solve_problem, ProblemKind, RoutingProblem, SearchParameters};
+use lkh_rs::solver::tour_cost;
+use lkh_rs::{
+    solve_parameter_file, solve_problem, solve_problem_with_distance, solve_problem_with_observer,
+    solve_problem_with_observer_and_options, solve_problem_with_options, CancellationToken,
+    Coordinates, CvrpSpec, CvrptwSpec, Distances, EdgeWeightFormat, EdgeWeightType, GeoPoint,
+    LkhError, MtspObjective, MtspSettings, NativeOutput, Partitioning, PdptwSpec, PickupDelivery,
+    Point2d, Point3d, ProblemKind, ProgrammaticSolveOptions, RoutingProblem, Scaling,
+    SearchParameters, SolveEvent, SubproblemSettings, TerminationReason, TimeWindow, TsptwSpec,
+};
+use std::cell::Cell;
 use std::fs;
 
 #[test]
@@ -23,12 +32,82 @@ fn solves_programmatic_euclidean_tsp() {
     assert_eq!(report.best_cost, 4);
     assert_eq!(report.dimension, 4);
     assert_eq!(report.tour.len(), 4);
+    assert_eq!(report.termination, TerminationReason::RunsExhausted);
 
     let mut sorted_tour = report.tour.clone();
     sorted_tour.sort_unstable();
     assert_eq!(sorted_tour, [1, 2, 3, 4]);
 }
 
+#[test]
+fn solves_coordinates_with_each_metric() {
+    let diamond: Vec<Point2d> = [(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (1.0, -1.0)]
+        .into_iter()
+        .map(Point2d::from)
+        .collect();
+    let rectangle: Vec<Point3d> = [
+        (0.0, 0.0, 0.0),
+        (0.0, 0.0, 3.0),
+        (0.0, 4.0, 3.0),
+        (0.0, 4.0, 0.0),
+    ]
+    .into_iter()
+    .map(Point3d::from)
+    .collect();
+    let cities = vec![
+        GeoPoint::new(52.52, 13.405),
+        GeoPoint::new(48.8566, 2.3522),
+        GeoPoint::new(51.5074, -0.1278),
+    ];
+    let cases = [
+        (EdgeWeightType::Euc2d, Coordinates::from(diamond.clone()), 4),
+        (EdgeWeightType::Ceil2d, diamond.clone().into(), 8),
+        (EdgeWeightType::Man2d, diamond.clone().into(), 8),
+        (EdgeWeightType::Max2d, diamond.clone().into(), 4),
+        (EdgeWeightType::Att, diamond.clone().into(), 4),
+        (EdgeWeightType::Euc3d, rectangle.into(), 14),
+        (EdgeWeightType::Geo, cities.clone().into(), 2156),
+        (EdgeWeightType::Geom, cities.clone().into(), 2_155_086),
+    ];
+    for (metric, points, cost) in cases {
+        let problem = RoutingProblem::coordinates(metric, points).expect("build problem");
+        let report = solve_problem(&problem, &SearchParameters::new()).expect("solve");
+        assert_eq!(report.best_cost, cost, "{metric}");
+    }
+
+    let geo =
+        RoutingProblem::coordinates(EdgeWeightType::Geo, vec![GeoPoint::new(-33.5, 151.25); 2])
+            .expect("build geo problem");
+    assert!(
+        geo.to_tsplib().contains("1 -33.3 151.15"),
+        "{}",
+        geo.to_tsplib()
+    );
+
+    for (metric, points) in [
+        (EdgeWeightType::Euc3d, Coordinates::from(diamond.clone())),
+        (EdgeWeightType::Euc2d, cities.clone().into()),
+        (EdgeWeightType::Geom, diamond.into()),
+        (
+            EdgeWeightType::Geom,
+            vec![GeoPoint::new(91.0, 0.0); 2].into(),
+        ),
+        (
+            EdgeWeightType::Geo,
+            vec![GeoPoint::new(0.0, f64::NAN); 2].into(),
+        ),
+        (EdgeWeightType::Geo, cities[..1].to_vec().into()),
+    ] {
+        assert!(
+            matches!(
+                RoutingProblem::coordinates(metric, points),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{metric}"
+        );
+    }
+}
+
 #[test]
 fn solves_programmatic_symmetric_matrix_tsp() {
     let problem = RoutingProblem::distance_matrix(vec![
@@ -45,6 +124,425 @@ fn solves_programmatic_symmetric_matrix_tsp() {
     assert_eq!(report.dimension, 4);
 }
 
+#[test]
+fn solves_compact_explicit_weight_formats() {
+    // d(1,2)=3 d(1,3)=5 d(1,4)=9 d(2,3)=4 d(2,4)=7 d(3,4)=2; the best tour
+    // 1-2-4-3 costs 17.
+    let upper = [3, 5, 9, 4, 7, 2];
+    let lower = [3, 5, 4, 9, 7, 2];
+    let upper_diag = [0, 3, 5, 9, 0, 4, 7, 0, 2, 0];
+    let lower_diag = [0, 3, 0, 5, 4, 0, 9, 7, 2, 0];
+    let full = [0, 3, 5, 9, 3, 0, 4, 7, 5, 4, 0, 2, 9, 7, 2, 0];
+    let cases: [(EdgeWeightFormat, &[i64]); 9] = [
+        (EdgeWeightFormat::FullMatrix, &full),
+        (EdgeWeightFormat::UpperRow, &upper),
+        (EdgeWeightFormat::LowerRow, &lower),
+        (EdgeWeightFormat::UpperDiagRow, &upper_diag),
+        (EdgeWeightFormat::LowerDiagRow, &lower_diag),
+        (EdgeWeightFormat::UpperCol, &lower),
+        (EdgeWeightFormat::LowerCol, &upper),
+        (EdgeWeightFormat::UpperDiagCol, &lower_diag),
+        (EdgeWeightFormat::LowerDiagCol, &upper_diag),
+    ];
+    for (format, weights) in cases {
+        assert_eq!(format.value_count(4), weights.len(), "{format}");
+        let problem =
+            RoutingProblem::explicit_weights(format, 4, weights).expect("build explicit problem");
+        let report = solve_problem(&problem, &SearchParameters::new()).expect("solve");
+        assert_eq!(report.best_cost, 17, "{format}");
+    }
+    let text = RoutingProblem::explicit_weights(EdgeWeightFormat::UpperRow, 4, &upper)
+        .expect("build upper row problem")
+        .to_tsplib();
+    assert!(
+        text.contains("EDGE_WEIGHT_SECTION\n3 5 9\n4 7\n2\n"),
+        "{text}"
+    );
+
+    let invalid: [(EdgeWeightFormat, &[i64]); 4] = [
+        (EdgeWeightFormat::UpperRow, &upper[..5]),
+        (EdgeWeightFormat::UpperRow, &[3, 5, 9, 4, 7, -2]),
+        (
+            EdgeWeightFormat::LowerDiagRow,
+            &[0, 3, 1, 5, 4, 0, 9, 7, 2, 0],
+        ),
+        (
+            EdgeWeightFormat::FullMatrix,
+            &[0, 3, 5, 9, 3, 0, 4, 7, 5, 4, 0, 2, 9, 7, 1, 0],
+        ),
+    ];
+    for (format, weights) in invalid {
+        assert!(
+            matches!(
+                RoutingProblem::explicit_weights(format, 4, weights),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{format} {weights:?}"
+        );
+    }
+}
+
+#[test]
+fn solves_sparse_graphs() {
+    let ring = [(1, 2, 5), (2, 3, 5), (3, 4, 5), (4, 1, 5), (1, 3, 1)];
+    let problem = RoutingProblem::sparse_edges(4, ring).expect("build sparse problem");
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve sparse problem");
+    assert_eq!(report.best_cost, 20);
+
+    // 1-2-3-6-4-5-1 is the only Hamiltonian cycle.
+    let detour = [(1, 2), (2, 3), (3, 4), (4, 5), (5, 1), (6, 3), (6, 4)];
+    let problem = RoutingProblem::hamiltonian_cycle(6, detour).expect("build hcp");
+    assert!(problem.to_tsplib().contains("\n3 4 6 -1\n"));
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve hcp");
+    assert_eq!(report.best_cost, 0);
+
+    let star = [(1, 2), (1, 3), (1, 4)];
+    let problem = RoutingProblem::hamiltonian_cycle(4, star).expect("build star");
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve star");
+    assert!(report.best_cost > 0);
+
+    for edges in [
+        vec![(1, 1, 1)],
+        vec![(1, 5, 1)],
+        vec![(1, 2, 1), (2, 1, 1)],
+        vec![(1, 2, 10_000_000)],
+        vec![],
+    ] {
+        assert!(
+            matches!(
+                RoutingProblem::sparse_edges(4, edges.clone()),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{edges:?}"
+        );
+    }
+}
+
+#[test]
+fn solves_float_distance_matrix_with_scaling() {
+    // The best tour 1-2-4-3 is 7.1095 km long.
+    let kilometers = vec![
+        vec![0.0, 1.2345, 2.5, 3.75],
+        vec![1.2345, 0.0, 1.5, 2.25],
+        vec![2.5, 1.5, 0.0, 1.125],
+        vec![3.75, 2.25, 1.125, 0.0],
+    ];
+    let problem = RoutingProblem::float_distance_matrix(kilometers.clone(), Scaling::Auto)
+        .expect("build auto-scaled problem");
+    assert!(problem.to_tsplib().contains("SCALE: 1000000\n"));
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve auto-scaled");
+    assert_eq!(report.best_cost, 7_109_500);
+    assert!((report.best_cost_unscaled - 7.1095).abs() < 1e-9);
+
+    let problem = RoutingProblem::float_distance_matrix(kilometers.clone(), Scaling::Factor(100.0))
+        .expect("build problem scaled by 100");
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve scaled by 100");
+    assert_eq!(report.best_cost, 711);
+    assert!((report.best_cost_unscaled - 7.11).abs() < 1e-9);
+
+    // The auto scale of 1,000,000 leaves 3.75 km at 3,750,000, above the
+    // 1,073,741 that LKH handles at PRECISION 1000.
+    let problem = RoutingProblem::float_distance_matrix(kilometers.clone(), Scaling::Auto)
+        .expect("build auto-scaled problem");
+    let precise = SearchParameters::new().with_precision(1000);
+    assert!(matches!(
+        solve_problem(&problem, &precise),
+        Err(LkhError::InvalidProblem(_))
+    ));
+    let problem = RoutingProblem::float_distance_matrix(kilometers.clone(), Scaling::Factor(1e5))
+        .expect("build problem scaled by 100,000");
+    let report = solve_problem(&problem, &precise).expect("solve at precision 1000");
+    assert_eq!(report.best_cost, 710_950);
+
+    let integral =
+        RoutingProblem::distance_matrix(vec![vec![0, 1, 2], vec![1, 0, 3], vec![2, 3, 0]])
+            .expect("build integer problem");
+    let report = solve_problem(&integral, &SearchParameters::new()).expect("solve integer");
+    assert_eq!(report.best_cost_unscaled, report.best_cost as f64);
+
+    let mut asymmetric = kilometers.clone();
+    asymmetric[0][1] = 1.0;
+    let mut negative = kilometers.clone();
+    negative[2][3] = -1.0;
+    negative[3][2] = -1.0;
+    for (matrix, scaling) in [
+        (kilometers.clone(), Scaling::Factor(0.5)),
+        (kilometers.clone(), Scaling::Factor(10.5)),
+        (kilometers.clone(), Scaling::Factor(1e7)),
+        (vec![vec![0.0, 1e8], vec![1e8, 0.0]], Scaling::Auto),
+        (
+            vec![vec![0.0, f64::NAN], vec![f64::NAN, 0.0]],
+            Scaling::Auto,
+        ),
+        (asymmetric, Scaling::Auto),
+        (negative, Scaling::Auto),
+    ] {
+        assert!(
+            matches!(
+                RoutingProblem::float_distance_matrix(matrix, scaling),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{scaling:?}"
+        );
+    }
+}
+
+#[test]
+fn solves_with_a_distance_callback() {
+    let dimension = 4;
+    let flat = [0, 3, 5, 9, 3, 0, 4, 7, 5, 4, 0, 2, 9, 7, 2, 0];
+    let problem = RoutingProblem::distance_function(dimension).expect("build problem");
+    assert!(problem.to_tsplib().contains("EDGE_WEIGHT_TYPE: SPECIAL\n"));
+    let report = solve_problem_with_distance(
+        &problem,
+        &SearchParameters::new(),
+        ProgrammaticSolveOptions::default(),
+        |i, j| flat[(i - 1) * dimension + j - 1],
+    )
+    .expect("solve with callback");
+    assert_eq!(report.best_cost, 17);
+    assert_eq!(report.dimension, 4);
+
+    let points = spiral(60);
+    let rendered = RoutingProblem::euclidean_2d(points.clone()).expect("build coordinates");
+    let expected =
+        solve_problem(&rendered, &SearchParameters::new().with_seed(3)).expect("solve coordinates");
+    let problem = RoutingProblem::distance_function(points.len()).expect("build problem");
+    let report = solve_problem_with_distance(
+        &problem,
+        &SearchParameters::new().with_seed(3),
+        ProgrammaticSolveOptions::default(),
+        |i, j| {
+            let (xi, yi) = points[i - 1];
+            let (xj, yj) = points[j - 1];
+            (xi - xj).hypot(yi - yj).round() as i64
+        },
+    )
+    .expect("solve euclidean callback");
+    assert_eq!(report.best_cost, expected.best_cost);
+
+    // Only demands and the depot are rendered; no section creates the nodes.
+    let problem = RoutingProblem::named("callback-cvrp", ProblemKind::Cvrp, 4)
+        .and_then(|problem| problem.with_keyword("EDGE_WEIGHT_TYPE", "SPECIAL"))
+        .and_then(|problem| problem.with_keyword("CAPACITY", "2"))
+        .and_then(|problem| problem.with_section("DEMAND_SECTION", ["1 0", "2 1", "3 1", "4 1"]))
+        .and_then(|problem| problem.with_section("DEPOT_SECTION", ["1", "-1"]))
+        .expect("build cvrp");
+    let hops = [[0, 1, 1, 2], [1, 0, 2, 1], [1, 2, 0, 1], [2, 1, 1, 0]];
+    let report = solve_problem_with_distance(
+        &problem,
+        &SearchParameters::new(),
+        ProgrammaticSolveOptions::default(),
+        |i, j| hops[i - 1][j - 1],
+    )
+    .expect("solve cvrp with callback");
+    assert_eq!(report.best_cost, 6);
+}
+
+#[test]
+fn reports_distance_callback_failures() {
+    let problem = RoutingProblem::distance_function(5).expect("build problem");
+    let parameters = SearchParameters::new();
+
+    assert!(matches!(
+        solve_problem_with_distance(
+            &problem,
+            &parameters,
+            ProgrammaticSolveOptions::default(),
+            |i, j| if i == 2 && j == 1 { -1 } else { 1 }
+        ),
+        Err(LkhError::InvalidProblem(_))
+    ));
+    let panicked = std::panic::catch_unwind(|| {
+        solve_problem_with_distance(
+            &problem,
+            &parameters,
+            ProgrammaticSolveOptions::default(),
+            |_, _| -> i64 { panic!("no distance") },
+        )
+    });
+    assert!(panicked.is_err());
+
+    let coordinates =
+        RoutingProblem::euclidean_2d([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0)]).expect("build problem");
+    assert!(matches!(
+        solve_problem_with_distance(
+            &coordinates,
+            &parameters,
+            ProgrammaticSolveOptions::default(),
+            |_, _| 1
+        ),
+        Err(LkhError::InvalidProblem(_))
+    ));
+
+    let report = solve_problem_with_distance(
+        &problem,
+        &parameters,
+        ProgrammaticSolveOptions::default(),
+        |_, _| 1,
+    )
+    .expect("solve after failures");
+    assert_eq!(report.best_cost, 5);
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let options = ProgrammaticSolveOptions {
+        cancellation: Some(token),
+        ..ProgrammaticSolveOptions::default()
+    };
+    assert!(matches!(
+        solve_problem_with_distance(&problem, &parameters, options, |_, _| 1),
+        Err(LkhError::Cancelled)
+    ));
+    let options = ProgrammaticSolveOptions {
+        native_output: NativeOutput::Capture,
+        ..ProgrammaticSolveOptions::default()
+    };
+    let report = solve_problem_with_distance(
+        &problem,
+        &parameters.clone().with_trace_level(1),
+        options,
+        |_, _| 1,
+    )
+    .expect("solve with captured output");
+    assert!(report.native_log.is_some_and(|log| !log.is_empty()));
+}
+
+#[test]
+fn rejects_distance_function_problems_without_a_callback() {
+    let problem = RoutingProblem::distance_function(4).expect("build problem");
+    let parameters = SearchParameters::new();
+
+    assert!(matches!(
+        solve_problem(&problem, &parameters),
+        Err(LkhError::InvalidProblem(_))
+    ));
+    assert!(matches!(
+        solve_problem_with_options(&problem, &parameters, ProgrammaticSolveOptions::default()),
+        Err(LkhError::InvalidProblem(_))
+    ));
+
+    let coordinates = RoutingProblem::from_tsplib_str(
+        "NAME: special\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: SPECIAL\n\
+         NODE_COORD_TYPE: TWOD_COORDS\n\
+         NODE_COORD_SECTION\n1 0 0\n2 0 1\n3 1 1\n4 1 0\nEOF\n",
+    )
+    .expect("parse problem");
+    let report = solve_problem(&coordinates, &parameters).expect("solve with coordinates");
+    assert!(report.best_cost > 0);
+}
+
+#[test]
+fn recovers_from_errors_in_the_middle_of_a_search() {
+    // An error raised between two steps of a move leaves the tour links
+    // half updated, and freeing the structures must not follow them.
+    let points = spiral(12);
+    let problem = RoutingProblem::distance_function(points.len()).expect("build problem");
+    let parameters = SearchParameters::new().with_runs(1).with_seed(5);
+    let calls = Cell::new(0);
+    let limit = Cell::new(usize::MAX);
+    let distance = |i: usize, j: usize| {
+        calls.set(calls.get() + 1);
+        if calls.get() > limit.get() {
+            return -1;
+        }
+        let (xi, yi) = points[i - 1];
+        let (xj, yj) = points[j - 1];
+        (xi - xj).hypot(yi - yj).round() as i64
+    };
+    let expected = solve_problem_with_distance(
+        &problem,
+        &parameters,
+        ProgrammaticSolveOptions::default(),
+        distance,
+    )
+    .expect("solve without failures");
+    let total = calls.get();
+
+    for failing in 0..total {
+        calls.set(0);
+        limit.set(failing);
+        let result = solve_problem_with_distance(
+            &problem,
+            &parameters,
+            ProgrammaticSolveOptions::default(),
+            distance,
+        );
+        assert!(
+            matches!(result, Err(LkhError::InvalidProblem(_))),
+            "call {}: {result:?}",
+            failing + 1
+        );
+    }
+    limit.set(usize::MAX);
+    let report = solve_problem_with_distance(
+        &problem,
+        &parameters,
+        ProgrammaticSolveOptions::default(),
+        distance,
+    )
+    .expect("solve after failures");
+    assert_eq!(report.best_cost, expected.best_cost);
+}
+
+#[test]
+fn reports_distance_callback_failures_after_the_first_run() {
+    // Later runs merge tours and build children from a population, which
+    // also asks for distances outside FindTour. Few candidates leave edges
+    // that only the callback can price.
+    let points = spiral(10);
+    let demands: Vec<_> = (1..=points.len())
+        .map(|id| format!("{id} {}", u8::from(id > 1)))
+        .collect();
+    let problem = RoutingProblem::named("callback-cvrp", ProblemKind::Cvrp, points.len())
+        .and_then(|problem| problem.with_keyword("EDGE_WEIGHT_TYPE", "SPECIAL"))
+        .and_then(|problem| problem.with_keyword("CAPACITY", "3"))
+        .and_then(|problem| problem.with_keyword("VEHICLES", "4"))
+        .and_then(|problem| problem.with_section("DEMAND_SECTION", demands))
+        .and_then(|problem| problem.with_section("DEPOT_SECTION", ["1", "-1"]))
+        .expect("build cvrp");
+    let parameters = SearchParameters::new()
+        .with_seed(2)
+        .with_max_trials(5)
+        .with_population_size(2)
+        .with_max_candidates(3);
+    let calls = Cell::new(0);
+    let limit = Cell::new(usize::MAX);
+    let distance = |i: usize, j: usize| {
+        calls.set(calls.get() + 1);
+        if calls.get() > limit.get() {
+            return -1;
+        }
+        let (xi, yi) = points[i - 1];
+        let (xj, yj) = points[j - 1];
+        (xi - xj).hypot(yi - yj).round() as i64
+    };
+    let solve = |runs| {
+        calls.set(0);
+        solve_problem_with_distance(
+            &problem,
+            &parameters.clone().with_runs(runs),
+            ProgrammaticSolveOptions::default(),
+            distance,
+        )
+    };
+    solve(1).expect("solve one run");
+    let first_run = calls.get();
+    solve(3).expect("solve three runs");
+    let all_runs = calls.get();
+    assert!(all_runs > first_run);
+
+    for failing in first_run..all_runs {
+        limit.set(failing);
+        let result = solve(3);
+        assert!(
+            matches!(result, Err(LkhError::InvalidProblem(_))),
+            "call {}: {result:?}",
+            failing + 1
+        );
+    }
+}
+
 #[test]
 fn solves_programmatic_asymmetric_matrix_tsp() {
     let problem = RoutingProblem::asymmetric_distance_matrix(vec![
@@ -75,6 +573,194 @@ fn solves_programmatic_tsp_with_additional_lkh_parameter() {
     assert_eq!(report.dimension, 4);
 }
 
+#[test]
+fn returns_native_error_and_recovers_for_next_solve() {
+    let bad = RoutingProblem::named("bad_weight_type", ProblemKind::Tsp, 3)
+        .expect("build problem")
+        .with_keyword("EDGE_WEIGHT_TYPE", "NOT_A_METRIC")
+        .expect("add keyword");
+
+    let err =
+        solve_problem(&bad, &SearchParameters::new()).expect_err("LKH rejects the weight type");
+    match err {
+        LkhError::Native(message) => {
+            assert!(message.contains("EDGE_WEIGHT_TYPE"), "message: {message}");
+        }
+        other => panic!("expected Native, got {other:?}"),
+    }
+
+    let problem = RoutingProblem::euclidean_2d([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])
+        .expect("build problem");
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve after error");
+    assert_eq!(report.best_cost, 4);
+}
+
+#[test]
+fn observer_receives_search_events() {
+    let problem = RoutingProblem::euclidean_2d(spiral(60)).expect("build problem");
+    let mut events = Vec::new();
+    let mut observer = |event: &SolveEvent| events.push(event.clone());
+
+    let report = solve_problem_with_observer(
+        &problem,
+        &SearchParameters::new().with_runs(3),
+        &mut observer,
+    )
+    .expect("solve with observer");
+
+    assert!(matches!(events[0], SolveEvent::LowerBoundComputed { .. }));
+    assert!(matches!(events[1], SolveEvent::CandidateSetCreated { .. }));
+    let runs = events
+        .iter()
+        .filter_map(|event| match event {
+            SolveEvent::RunFinished { run, .. } => Some(*run),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(runs, [1, 2, 3]);
+    let last_best = events.iter().rev().find_map(|event| match event {
+        SolveEvent::NewBest { cost, .. } => Some(*cost),
+        _ => None,
+    });
+    assert_eq!(last_best, Some(report.best_cost));
+}
+
+#[test]
+fn observer_can_cancel_the_solve_it_watches() {
+    let problem = RoutingProblem::euclidean_2d(spiral(60)).expect("build problem");
+    let token = CancellationToken::new();
+    let options = ProgrammaticSolveOptions {
+        cancellation: Some(token.clone()),
+        ..ProgrammaticSolveOptions::default()
+    };
+    let mut finished = 0;
+    let mut observer = |event: &SolveEvent| {
+        if let SolveEvent::RunFinished { .. } = event {
+            finished += 1;
+            token.cancel();
+        }
+    };
+
+    let report = solve_problem_with_observer_and_options(
+        &problem,
+        &SearchParameters::new().with_runs(5),
+        options,
+        &mut observer,
+    )
+    .expect("solve");
+
+    assert_eq!(finished, 1);
+    assert_eq!(report.termination, TerminationReason::Cancelled);
+    assert_eq!(report.runs, 1);
+}
+
+#[test]
+fn reports_bounds_and_run_statistics() {
+    let problem = RoutingProblem::euclidean_2d(spiral(60)).expect("build problem");
+
+    let report = solve_problem(&problem, &SearchParameters::new().with_runs(3)).expect("solve");
+
+    assert_eq!(report.termination, TerminationReason::RunsExhausted);
+    assert!(report.lower_bound > 0.0);
+    assert!(report.lower_bound <= report.best_cost as f64);
+    assert!(report.lower_bound_gap.is_some());
+    assert_eq!(report.optimum, None);
+    assert_eq!(report.gap, None);
+    assert_eq!(
+        report
+            .run_results
+            .iter()
+            .map(|result| result.run)
+            .collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    let statistics = report.statistics().expect("statistics");
+    assert_eq!(statistics.cost_min, report.best_cost);
+    assert!(statistics.cost_avg >= statistics.cost_min as f64);
+    assert!(statistics.trials_max >= statistics.trials_min);
+    assert!(report.wall_time > std::time::Duration::ZERO);
+
+    let with_optimum = solve_problem(
+        &problem,
+        &SearchParameters::new()
+            .with_runs(3)
+            .with_optimum(report.best_cost),
+    )
+    .expect("solve with optimum");
+    assert_eq!(with_optimum.optimum, Some(report.best_cost));
+    assert!(with_optimum.gap.is_some());
+}
+
+#[test]
+fn cancellation_stops_trials_and_keeps_best_tour() {
+    let problem = RoutingProblem::euclidean_2d(spiral(300)).expect("build problem");
+    let parameters = SearchParameters::new()
+        .with_runs(1)
+        .with_max_trials(100_000_000);
+    let token = CancellationToken::new();
+    let options = ProgrammaticSolveOptions {
+        cancellation: Some(token.clone()),
+        ..ProgrammaticSolveOptions::default()
+    };
+
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        token.cancel();
+    });
+    let report = solve_problem_with_options(&problem, &parameters, options).expect("solve");
+    canceller.join().expect("join canceller");
+
+    assert_eq!(report.termination, TerminationReason::Cancelled);
+    assert_eq!(report.runs, 1);
+    let mut sorted_tour = report.tour.clone();
+    sorted_tour.sort_unstable();
+    assert_eq!(sorted_tour, (1..=300).collect::<Vec<_>>());
+}
+
+#[test]
+fn reports_runs_cut_short_by_time_limits() {
+    let problem = RoutingProblem::euclidean_2d(spiral(300)).expect("build problem");
+    let endless = SearchParameters::new().with_max_trials(100_000_000);
+
+    // TimeLimit reports any run cut short. The first one was, and the search
+    // still went on to the last.
+    let report = solve_problem(&problem, &endless.clone().with_runs(2).with_time_limit(0.2))
+        .expect("solve with run time limit");
+    assert_eq!(report.termination, TerminationReason::TimeLimit);
+    assert_eq!(report.runs, 2);
+    assert_eq!(report.run_results.len(), 2);
+    assert!(report.run_results[0].time >= std::time::Duration::from_millis(200));
+
+    let report = solve_problem(&problem, &endless.with_runs(5).with_total_time_limit(1.0))
+        .expect("solve with total time limit");
+    assert_eq!(report.termination, TerminationReason::TimeLimit);
+    assert!(report.runs < 5);
+    assert_eq!(report.runs as usize, report.run_results.len());
+}
+
+#[test]
+fn captures_native_output_in_report() {
+    let problem = RoutingProblem::euclidean_2d(spiral(30)).expect("build problem");
+    let parameters = SearchParameters::new().with_trace_level(1);
+    let options = ProgrammaticSolveOptions {
+        native_output: NativeOutput::Capture,
+        ..ProgrammaticSolveOptions::default()
+    };
+    let report = solve_problem_with_options(&problem, &parameters, options).expect("solve");
+    let log = report.native_log.expect("captured output");
+    assert!(log.contains("Lower bound = "), "log:\n{log}");
+    assert!(log.contains("Run 1: Cost = "), "log:\n{log}");
+
+    let options = ProgrammaticSolveOptions {
+        native_output: NativeOutput::Log(log::Level::Debug),
+        ..ProgrammaticSolveOptions::default()
+    };
+    let report = solve_problem_with_options(&problem, &parameters, options).expect("solve");
+    assert_eq!(report.native_log, None);
+    let report = solve_problem(&problem, &parameters).expect("solve");
+    assert_eq!(report.native_log, None);
+}
+
 #[test]
 fn solves_programmatic_cvrp_from_generic_problem() {
     let problem = RoutingProblem::named("tiny_cvrp", ProblemKind::Cvrp, 4)
@@ -106,6 +792,457 @@ fn solves_programmatic_cvrp_from_generic_problem() {
     assert_eq!(report.tour.len(), 4);
 }
 
+#[test]
+fn solves_cvrp_after_a_larger_one() {
+    // Penalty_CVRP remembers a route start in a static pointer, which must
+    // not outlive the node set of the previous solve.
+    let cvrp = |customers: usize| {
+        let coordinates = (1..=customers + 1).map(|id| {
+            let angle = id as f64 * 0.7;
+            let radius = id as f64;
+            format!(
+                "{id} {} {}",
+                (radius * angle.cos()).round(),
+                (radius * angle.sin()).round()
+            )
+        });
+        let demands = (1..=customers + 1).map(|id| format!("{id} {}", u8::from(id > 1)));
+        RoutingProblem::named("spiral_cvrp", ProblemKind::Cvrp, customers + 1)
+            .expect("build base problem")
+            .with_keyword("CAPACITY", "5")
+            .expect("add capacity")
+            .with_keyword("VEHICLES", (customers.div_ceil(5) + 1).to_string())
+            .expect("add vehicles")
+            .with_keyword("EDGE_WEIGHT_TYPE", "EUC_2D")
+            .expect("add weight type")
+            .with_section("NODE_COORD_SECTION", coordinates)
+            .expect("add coordinates")
+            .with_section("DEMAND_SECTION", demands)
+            .expect("add demands")
+            .with_section("DEPOT_SECTION", ["1", "-1"])
+            .expect("add depot")
+    };
+    let parameters = SearchParameters::new().with_runs(1).with_max_trials(5);
+
+    for customers in [30, 6, 20, 4] {
+        let report = solve_problem(&cvrp(customers), &parameters).expect("solve cvrp");
+        assert_eq!(report.best_penalty, 0);
+        assert!(report.best_cost > 0);
+    }
+}
+
+#[test]
+fn validates_generic_problems_before_solving() {
+    let base = |kind| RoutingProblem::named("checked", kind, 3).expect("build base problem");
+    let explicit = |format: &str, lines: &[&str]| {
+        base(ProblemKind::Tsp)
+            .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")
+            .and_then(|problem| problem.with_keyword("EDGE_WEIGHT_FORMAT", format))
+            .and_then(|problem| problem.with_section("EDGE_WEIGHT_SECTION", lines.iter().copied()))
+            .expect("build explicit problem")
+    };
+    let coords = |lines: &[&str]| {
+        base(ProblemKind::Tsp)
+            .with_keyword("EDGE_WEIGHT_TYPE", "EUC_2D")
+            .and_then(|problem| problem.with_section("NODE_COORD_SECTION", lines.iter().copied()))
+            .expect("build coordinate problem")
+    };
+
+    let valid = [
+        explicit("FULL_MATRIX", &["0 1 2", "1 0 3", "2 3 0"]),
+        explicit("UPPER_ROW", &["1 2", "3"]),
+        explicit("LOWER_DIAG_ROW", &["0", "1 0", "2 3 0"]),
+        coords(&["1 0 0", "2 0 1", "3 1 1"]),
+    ];
+    for problem in &valid {
+        problem.validate().expect("valid problem");
+    }
+    let report = solve_problem(&valid[1], &SearchParameters::new()).expect("solve upper row");
+    assert_eq!(report.best_cost, 6);
+
+    let invalid = [
+        base(ProblemKind::Cvrp),
+        base(ProblemKind::Cvrp)
+            .with_keyword("CAPACITY", "0")
+            .expect("add capacity"),
+        base(ProblemKind::Cvrp)
+            .with_keyword("CAPACITY", "5")
+            .expect("add capacity"),
+        base(ProblemKind::Cvrp)
+            .with_keyword("CAPACITY", "5")
+            .and_then(|problem| problem.with_section("DEMAND_SECTION", ["1 0", "2 1", "4 1"]))
+            .expect("add demands"),
+        base(ProblemKind::Tsp)
+            .with_keyword("EDGE_WEIGHT_TYPE", "EXPLICIT")
+            .expect("add weight type"),
+        explicit("FULL_MATRIX", &["0 1 2", "1 0 3"]),
+        explicit("UPPER_ROW", &["1 2 3 4"]),
+        explicit("FUNCTION", &["1 2 3"]),
+        explicit("FULL_MATRIX", &["0 1 2", "1 0 x", "2 3 0"]),
+        coords(&["1 0 0", "2 0 1"]),
+        coords(&["1 0 0", "2 0 1", "2 1 1"]),
+        coords(&["1 0 0", "2 0 1", "4 1 1"]),
+        coords(&["1 0 0", "2 0 1", "3 1"]),
+        base(ProblemKind::Tsp)
+            .with_section("DEPOT_SECTION", ["4", "-1"])
+            .expect("add depot"),
+        base(ProblemKind::Hcp)
+            .with_section("EDGE_DATA_SECTION", ["1 2", "2 3", "-1"])
+            .expect("add edges"),
+    ];
+    for problem in &invalid {
+        assert!(
+            matches!(problem.validate(), Err(LkhError::InvalidProblem(_))),
+            "{problem:?}"
+        );
+        assert!(
+            matches!(
+                solve_problem(problem, &SearchParameters::new()),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{problem:?}"
+        );
+    }
+}
+
+#[test]
+fn decodes_cvrp_routes_per_vehicle() {
+    let problem = RoutingProblem::named("two_vehicle_cvrp", ProblemKind::Cvrp, 5)
+        .expect("build base problem")
+        .with_keyword("CAPACITY", "2")
+        .expect("add capacity")
+        .with_keyword("VEHICLES", "2")
+        .expect("add vehicles")
+        .with_keyword("EDGE_WEIGHT_TYPE", "EUC_2D")
+        .expect("add weight type")
+        .with_section(
+            "NODE_COORD_SECTION",
+            ["1 0 0", "2 10 0", "3 11 0", "4 -10 0", "5 -11 0"],
+        )
+        .expect("add coordinates")
+        .with_section("DEMAND_SECTION", ["1 0", "2 1", "3 1", "4 1", "5 1"])
+        .expect("add demands")
+        .with_section("DEPOT_SECTION", ["1", "-1"])
+        .expect("add depot");
+
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve cvrp");
+
+    assert_eq!(report.best_penalty, 0);
+    assert_eq!(report.best_cost, 44);
+    let routes = report.routes();
+    assert_eq!(routes.len(), 2);
+    let mut vehicles: Vec<_> = routes.iter().map(|route| route.vehicle).collect();
+    vehicles.sort_unstable();
+    assert_eq!(vehicles, [1, 2]);
+    let mut customers = Vec::new();
+    for route in routes {
+        assert_eq!(route.nodes.first(), Some(&1));
+        assert_eq!(route.nodes.last(), Some(&1));
+        assert_eq!(route.nodes.len(), 4);
+        assert_eq!(route.cost, 22);
+        assert_eq!(route.load, 2);
+        assert_eq!(route.duration, 22.0);
+        customers.extend_from_slice(&route.nodes[1..3]);
+    }
+    customers.sort_unstable();
+    assert_eq!(customers, [2, 3, 4, 5]);
+}
+
+#[test]
+fn solves_typed_cvrp_spec() {
+    let spec = CvrpSpec {
+        distances: Distances::Euclidean2d(
+            [
+                (0.0, 0.0),
+                (10.0, 0.0),
+                (11.0, 0.0),
+                (-10.0, 0.0),
+                (-11.0, 0.0),
+            ]
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        ),
+        demands: vec![0, 1, 1, 1, 1],
+        capacity: 2,
+        depot: 1,
+        vehicles: Some(2),
+    };
+    let problem = RoutingProblem::cvrp(spec.clone()).expect("build cvrp");
+    assert!(problem.to_tsplib().contains("DEMAND_SECTION\n1 0\n2 1\n"));
+
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve cvrp");
+    assert_eq!(report.best_penalty, 0);
+    assert_eq!(report.best_cost, 44);
+    assert_eq!(report.routes().len(), 2);
+
+    let matrix = RoutingProblem::cvrp(CvrpSpec {
+        distances: Distances::Matrix(vec![vec![0, 1, 1], vec![1, 0, 1], vec![1, 1, 0]]),
+        demands: vec![0, 1, 1],
+        capacity: 2,
+        depot: 1,
+        vehicles: None,
+    })
+    .expect("build matrix cvrp");
+    assert!(matrix
+        .to_tsplib()
+        .contains("EDGE_WEIGHT_FORMAT: FULL_MATRIX"));
+    assert!(!matrix.to_tsplib().contains("VEHICLES"));
+}
+
+#[test]
+fn rejects_invalid_cvrp_specs() {
+    let valid = CvrpSpec {
+        distances: Distances::Euclidean2d(vec![
+            (0.0, 0.0).into(),
+            (1.0, 0.0).into(),
+            (0.0, 1.0).into(),
+        ]),
+        demands: vec![0, 2, 2],
+        capacity: 3,
+        depot: 1,
+        vehicles: Some(2),
+    };
+    RoutingProblem::cvrp(valid.clone()).expect("valid spec");
+
+    let invalid = [
+        CvrpSpec {
+            demands: vec![0, 2],
+            ..valid.clone()
+        },
+        CvrpSpec {
+            depot: 4,
+            ..valid.clone()
+        },
+        CvrpSpec {
+            demands: vec![1, 2, 2],
+            ..valid.clone()
+        },
+        CvrpSpec {
+            demands: vec![0, 4, 2],
+            ..valid.clone()
+        },
+        CvrpSpec {
+            vehicles: Some(1),
+            ..valid.clone()
+        },
+        CvrpSpec {
+            capacity: 0,
+            ..valid
+        },
+    ];
+    for spec in invalid {
+        assert!(
+            matches!(
+                RoutingProblem::cvrp(spec.clone()),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{spec:?}"
+        );
+    }
+}
+
+fn line_points(xs: &[f64]) -> Distances {
+    Distances::Euclidean2d(xs.iter().map(|&x| (x, 0.0).into()).collect())
+}
+
+#[test]
+fn solves_time_window_specs() {
+    let tsptw = RoutingProblem::tsptw(TsptwSpec {
+        distances: line_points(&[0.0, 10.0, 20.0]),
+        time_windows: vec![
+            TimeWindow::new(0.0, 100.0),
+            TimeWindow::new(30.0, 40.0),
+            TimeWindow::new(10.0, 20.0),
+        ],
+        service_times: vec![0.0, 2.0, 0.0],
+        depot: 1,
+    })
+    .expect("build tsptw");
+    let text = tsptw.to_tsplib();
+    assert!(text.contains("TIME_WINDOW_SECTION\n1 0 100\n2 30 40\n3 10 20\n"));
+    assert!(text.contains("SERVICE_TIME_SECTION\n1 0\n2 2\n3 0\n"));
+    let report = solve_problem(&tsptw, &SearchParameters::new()).expect("solve tsptw");
+    assert_eq!(report.best_penalty, 0);
+    let mut tour = report.tour.clone();
+    let depot = tour.iter().position(|&id| id == 1).unwrap();
+    tour.rotate_left(depot);
+    assert_eq!(tour, [1, 3, 2]);
+
+    let cvrptw = RoutingProblem::cvrptw(CvrptwSpec {
+        cvrp: CvrpSpec {
+            distances: line_points(&[0.0, 10.0, 11.0, -10.0, -11.0]),
+            demands: vec![0, 1, 1, 1, 1],
+            capacity: 2,
+            depot: 1,
+            vehicles: Some(2),
+        },
+        time_windows: vec![(0.0, 100.0).into(); 5],
+        service_times: Vec::new(),
+    })
+    .expect("build cvrptw");
+    assert!(!cvrptw.to_tsplib().contains("SERVICE_TIME_SECTION"));
+    let report = solve_problem(&cvrptw, &SearchParameters::new()).expect("solve cvrptw");
+    assert_eq!(report.best_penalty, 0);
+    assert_eq!(report.routes().len(), 2);
+
+    let pdptw = RoutingProblem::pdptw(PdptwSpec {
+        distances: line_points(&[0.0, -10.0, 10.0, 20.0]),
+        requests: vec![PickupDelivery {
+            pickup: 3,
+            delivery: 2,
+            quantity: 1,
+        }],
+        time_windows: vec![(0.0, 100.0).into(); 4],
+        service_times: Vec::new(),
+        capacity: 1,
+        depot: 1,
+        vehicles: None,
+    })
+    .expect("build pdptw");
+    assert!(pdptw.to_tsplib().contains(
+        "PICKUP_AND_DELIVERY_SECTION\n1 0 0 100 0 0 0\n2 -1 0 100 0 3 0\n3 1 0 100 0 0 2\n"
+    ));
+    let report = solve_problem(&pdptw, &SearchParameters::new()).expect("solve pdptw");
+    assert_eq!(report.best_penalty, 0);
+    let position = |node| report.tour.iter().position(|&id| id == node).unwrap();
+    assert!(position(3) < position(2));
+}
+
+#[test]
+fn solves_mtsp_with_each_objective() {
+    let problem = RoutingProblem::euclidean_2d([
+        (0.0, 0.0),
+        (10.0, 0.0),
+        (11.0, 0.0),
+        (12.0, 0.0),
+        (13.0, 0.0),
+    ])
+    .expect("build problem");
+    let solve = |mtsp: MtspSettings| {
+        let parameters = SearchParameters::new().with_runs(2).with_mtsp(mtsp);
+        let report = solve_problem(&problem, &parameters).expect("solve mtsp");
+        report.mtsp_solution().expect("two salesmen")
+    };
+
+    let min_sum = solve(MtspSettings::new(2).with_objective(MtspObjective::MinSum));
+    assert_eq!(min_sum.tours.len(), 2);
+    assert_eq!(min_sum.total_length(), 46);
+    assert_eq!(min_sum.max_size(), 3);
+    for tour in &min_sum.tours {
+        assert_eq!(tour.nodes.first(), Some(&1));
+        assert_eq!(tour.nodes.last(), Some(&1));
+    }
+
+    let bounded = solve(
+        MtspSettings::new(2)
+            .with_objective(MtspObjective::MinSum)
+            .with_max_size(2),
+    );
+    assert_eq!(bounded.total_length(), 48);
+    assert_eq!(bounded.max_size(), 2);
+
+    let min_max_size = solve(MtspSettings::new(2).with_objective(MtspObjective::MinMaxSize));
+    assert_eq!(min_max_size.max_size(), 2);
+
+    let min_max = solve(MtspSettings::new(2).with_objective(MtspObjective::MinMax));
+    assert_eq!(min_max.max_length(), 26);
+
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve tsp");
+    assert!(report.mtsp_solution().is_none());
+    assert!(matches!(
+        solve_problem(
+            &problem,
+            &SearchParameters::new()
+                .with_mtsp(MtspSettings::new(2).with_min_size(3).with_max_size(2))
+        ),
+        Err(LkhError::InvalidSearchParameters(_))
+    ));
+}
+
+#[test]
+fn rejects_inconsistent_time_window_specs() {
+    let valid = TsptwSpec {
+        distances: line_points(&[0.0, 1.0, 2.0]),
+        time_windows: vec![(0.0, 10.0).into(); 3],
+        service_times: Vec::new(),
+        depot: 1,
+    };
+    RoutingProblem::tsptw(valid.clone()).expect("valid spec");
+
+    let invalid = [
+        TsptwSpec {
+            time_windows: vec![(0.0, 10.0).into(), (5.0, 4.0).into(), (0.0, 10.0).into()],
+            ..valid.clone()
+        },
+        TsptwSpec {
+            time_windows: vec![(0.0, 10.0).into(), (0.0, 11.0).into(), (0.0, 10.0).into()],
+            ..valid.clone()
+        },
+        TsptwSpec {
+            time_windows: vec![(0.0, 10.0).into(); 2],
+            ..valid.clone()
+        },
+        TsptwSpec {
+            service_times: vec![0.0, -1.0, 0.0],
+            ..valid.clone()
+        },
+        TsptwSpec {
+            time_windows: vec![(0.0, f64::NAN).into(); 3],
+            ..valid.clone()
+        },
+    ];
+    for spec in invalid {
+        assert!(
+            matches!(
+                RoutingProblem::tsptw(spec.clone()),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{spec:?}"
+        );
+    }
+
+    let pdptw = PdptwSpec {
+        distances: line_points(&[0.0, 1.0, 2.0, 3.0, 4.0]),
+        requests: vec![PickupDelivery {
+            pickup: 2,
+            delivery: 3,
+            quantity: 1,
+        }],
+        time_windows: vec![(0.0, 10.0).into(); 5],
+        service_times: Vec::new(),
+        capacity: 2,
+        depot: 1,
+        vehicles: Some(1),
+    };
+    RoutingProblem::pdptw(pdptw.clone()).expect("valid pdptw");
+    let pair = |pickup, delivery, quantity| PickupDelivery {
+        pickup,
+        delivery,
+        quantity,
+    };
+    let invalid_requests = [
+        vec![pair(2, 2, 1)],
+        vec![pair(1, 3, 1)],
+        vec![pair(2, 6, 1)],
+        vec![pair(2, 3, 1), pair(4, 3, 1)],
+        vec![pair(2, 3, 3)],
+    ];
+    for requests in invalid_requests {
+        let spec = PdptwSpec {
+            requests,
+            ..pdptw.clone()
+        };
+        assert!(
+            matches!(
+                RoutingProblem::pdptw(spec.clone()),
+                Err(LkhError::InvalidProblem(_))
+            ),
+            "{spec:?}"
+        );
+    }
+}
+
 #[test]
 fn renders_programmatic_problem_and_parameters_without_solving() {
     let problem = RoutingProblem::euclidean_2d([(0.0, 0.0), (1.0, 0.0)]).expect("build problem");
@@ -166,3 +1303,187 @@ fn explicitly_exports_programmatic_problem_and_parameters() {
     let _ = fs::remove_file(parameter_path);
     let _ = fs::remove_dir(directory);
 }
+
+/// Points on a sunflower spiral, which LKH cannot solve by the ascent alone.
+#[test]
+fn warm_starts_from_previous_tour() {
+    let problem = RoutingProblem::euclidean_2d(spiral(60)).expect("build problem");
+    let cold = solve_problem(&problem, &SearchParameters::new().with_seed(1)).expect("cold solve");
+    let previous: Vec<usize> = cold.tour.iter().map(|&node| node as usize).collect();
+
+    let parameters = SearchParameters::new()
+        .with_seed(2)
+        .with_max_trials(1)
+        .with_initial_tour(previous.clone())
+        .with_input_tour(previous.clone())
+        .with_merge_tours(vec![previous]);
+    let warm = solve_problem(&problem, &parameters).expect("warm solve");
+
+    assert!(warm.best_cost <= cold.best_cost);
+    assert_eq!(warm.tour.len(), 60);
+}
+
+#[test]
+fn measures_tour_cost_without_searching() {
+    let problem = RoutingProblem::euclidean_2d(spiral(40)).expect("build problem");
+    let parameters = SearchParameters::new().with_seed(5);
+    let report = solve_problem(&problem, &parameters).expect("solve");
+    let best: Vec<usize> = report.tour.iter().map(|&node| node as usize).collect();
+    assert_eq!(
+        tour_cost(&problem, &parameters, &best).expect("cost of best tour"),
+        report.best_cost
+    );
+    let ordered: Vec<usize> = (1..=40).collect();
+    assert!(tour_cost(&problem, &parameters, &ordered).expect("cost") > report.best_cost);
+
+    let asymmetric = RoutingProblem::asymmetric_distance_matrix(vec![
+        vec![0, 1, 9, 9],
+        vec![9, 0, 1, 9],
+        vec![9, 9, 0, 1],
+        vec![1, 9, 9, 0],
+    ])
+    .expect("build atsp");
+    assert_eq!(
+        tour_cost(&asymmetric, &parameters, &[1, 2, 3, 4]).expect("forward"),
+        4
+    );
+    assert_eq!(
+        tour_cost(&asymmetric, &parameters, &[4, 3, 2, 1]).expect("backward"),
+        36
+    );
+
+    for tour in [vec![1, 2, 3], vec![1, 2, 2, 3], vec![0, 1, 2, 3]] {
+        assert!(matches!(
+            tour_cost(&asymmetric, &parameters, &tour),
+            Err(LkhError::InvalidProblem(_))
+        ));
+    }
+
+    // Tours of multi-vehicle problems visit LKH's depot copies too, and
+    // CVRPTW is read into a doubled node set like an asymmetric problem.
+    let cvrp = CvrpSpec {
+        distances: line_points(&[0.0, 10.0, 11.0, -10.0, -11.0]),
+        demands: vec![0, 1, 1, 1, 1],
+        capacity: 2,
+        depot: 1,
+        vehicles: Some(2),
+    };
+    let cvrptw = RoutingProblem::cvrptw(CvrptwSpec {
+        cvrp: cvrp.clone(),
+        time_windows: vec![(0.0, 100.0).into(); 5],
+        service_times: Vec::new(),
+    })
+    .expect("build cvrptw");
+    let cvrp = RoutingProblem::cvrp(cvrp).expect("build cvrp");
+    for problem in [cvrp, cvrptw] {
+        let report = solve_problem(&problem, &parameters).expect("solve");
+        assert_eq!(report.tour.len(), 6);
+        let tour: Vec<usize> = report.tour.iter().map(|&node| node as usize).collect();
+        assert_eq!(
+            tour_cost(&problem, &parameters, &tour).expect("cost with depot copies"),
+            report.best_cost
+        );
+    }
+}
+
+#[test]
+fn rejects_warm_start_tours_that_do_not_fit_the_problem() {
+    let problem = RoutingProblem::euclidean_2d(spiral(10)).expect("build problem");
+    let duplicate = SearchParameters::new().with_initial_tour(vec![1, 2, 2]);
+    assert!(matches!(
+        solve_problem(&problem, &duplicate),
+        Err(LkhError::InvalidSearchParameters(_))
+    ));
+
+    let too_short = SearchParameters::new().with_initial_tour(vec![1, 2, 3]);
+    assert!(matches!(
+        solve_problem(&problem, &too_short),
+        Err(LkhError::Native(_))
+    ));
+
+    let report = solve_problem(&problem, &SearchParameters::new()).expect("solve after error");
+    assert_eq!(report.tour.len(), 10);
+}
+
+#[test]
+fn improves_a_tour_through_subproblem_partitioning() {
+    let points = spiral(300);
+    let problem = RoutingProblem::euclidean_2d(points.clone()).expect("build problem");
+    let length = |tour: &[usize]| -> i64 {
+        (0..tour.len())
+            .map(|i| {
+                let (x1, y1) = points[tour[i] - 1];
+                let (x2, y2) = points[tour[(i + 1) % tour.len()] - 1];
+                ((x1 - x2).hypot(y1 - y2) + 0.5) as i64
+            })
+            .sum()
+    };
+    // A tour sweeping the points from left to right, which subproblems of
+    // nearby nodes can improve.
+    let mut start: Vec<usize> = (1..=300).collect();
+    start.sort_by(|&a, &b| points[a - 1].0.total_cmp(&points[b - 1].0));
+    let mut nodes = start.clone();
+    nodes.sort_unstable();
+
+    for (partitioning, size) in [
+        (Partitioning::Karp, 60),
+        (Partitioning::KCenter, 60),
+        (Partitioning::KMeans, 60),
+        (Partitioning::Rohe, 60),
+        (Partitioning::Delaunay, 60),
+        (Partitioning::Moore, 60),
+        (Partitioning::Sierpinski, 60),
+        (Partitioning::TourSegment, 100),
+    ] {
+        let parameters = SearchParameters::new()
+            .with_seed(1)
+            .with_max_trials(30)
+            .with_subproblem(SubproblemSettings::new(size, partitioning))
+            .with_subproblem_tour(start.clone());
+        let report = solve_problem(&problem, &parameters).expect("solve subproblems");
+        let mut tour: Vec<usize> = report.tour.iter().map(|&node| node as usize).collect();
+        assert_eq!(report.best_cost, length(&tour), "{partitioning:?}");
+        assert!(report.best_cost < length(&start), "{partitioning:?}");
+        tour.sort_unstable();
+        assert_eq!(tour, nodes, "{partitioning:?}");
+    }
+
+    let parameters = SearchParameters::new().with_max_trials(30).with_subproblem(
+        SubproblemSettings::new(60, Partitioning::KMeans)
+            .with_borders(true)
+            .with_compressed(true),
+    );
+    assert!(matches!(
+        solve_problem(&problem, &parameters),
+        Err(LkhError::InvalidSearchParameters(_))
+    ));
+    let report = solve_problem(&problem, &parameters.with_subproblem_tour(start.clone()))
+        .expect("solve with borders");
+    assert_eq!(report.tour.len(), 300);
+
+    // Partitioning output at trace level 0 is kept when it is captured.
+    let parameters = SearchParameters::new()
+        .with_seed(1)
+        .with_max_trials(30)
+        .with_trace_level(0)
+        .with_subproblem(SubproblemSettings::new(60, Partitioning::Karp))
+        .with_subproblem_tour(start);
+    let options = ProgrammaticSolveOptions {
+        native_output: NativeOutput::Capture,
+        ..ProgrammaticSolveOptions::default()
+    };
+    let report =
+        solve_problem_with_options(&problem, &parameters, options).expect("solve with capture");
+    let log = report.native_log.expect("captured output");
+    assert!(log.contains("Cost = "), "log:\n{log}");
+}
+
+fn spiral(count: i32) -> Vec<(f64, f64)> {
+    (0..count)
+        .map(|i| {
+            let angle = f64::from(i) * 2.399_963;
+            let radius = f64::from(i).sqrt() * 100.0;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect()
+}
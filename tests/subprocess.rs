@@ -0,0 +1,118 @@
+use lkh_rs::{
+    LkhError, NativeOutput, ProgrammaticSolveOptions, RoutingProblem, SearchParameters,
+    SubprocessBackend,
+};
+
+fn backend() -> SubprocessBackend {
+    SubprocessBackend::new(env!("CARGO_BIN_EXE_lkh"))
+}
+
+#[test]
+fn solves_programmatic_problem_in_worker() {
+    let problem = RoutingProblem::euclidean_2d([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])
+        .expect("build problem");
+    let report = backend()
+        .solve_problem(&problem, &SearchParameters::new())
+        .expect("solve in worker");
+
+    assert_eq!(report.best_cost, 4);
+    assert_eq!(report.best_cost_unscaled, 4.0);
+    assert_eq!(report.dimension, 4);
+    assert_eq!(report.termination, lkh_rs::TerminationReason::RunsExhausted);
+    assert_eq!(report.run_results.len(), report.runs as usize);
+
+    let mut sorted_tour = report.tour.clone();
+    sorted_tour.sort_unstable();
+    assert_eq!(sorted_tour, [1, 2, 3, 4]);
+}
+
+#[test]
+fn sends_warm_start_tours_to_worker() {
+    let problem = RoutingProblem::euclidean_2d([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])
+        .expect("build problem");
+    let parameters = SearchParameters::new()
+        .with_initial_tour(vec![1, 2, 3, 4])
+        .with_merge_tours(vec![vec![1, 4, 3, 2], vec![2, 3, 4, 1]]);
+    let report = backend()
+        .solve_problem(&problem, &parameters)
+        .expect("solve in worker");
+
+    assert_eq!(report.best_cost, 4);
+}
+
+#[test]
+fn returns_captured_native_output_from_worker() {
+    let problem = RoutingProblem::euclidean_2d([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])
+        .expect("build problem");
+    let options = ProgrammaticSolveOptions {
+        native_output: NativeOutput::Capture,
+        ..ProgrammaticSolveOptions::default()
+    };
+    let report = backend()
+        .solve_problem_with_options(
+            &problem,
+            &SearchParameters::new().with_trace_level(1),
+            options,
+        )
+        .expect("solve in worker");
+
+    let log = report.native_log.expect("captured output");
+    assert!(log.contains("Run 1: Cost = 4"), "log:\n{log}");
+}
+
+#[test]
+fn solves_parameter_file_in_worker() {
+    let report = backend()
+        .solve_parameter_file("tests/fixtures/tiny.par")
+        .expect("solve tiny fixture in worker");
+
+    assert!(report.best_cost > 0);
+    assert_eq!(report.tour.len(), 4);
+}
+
+#[test]
+fn returns_native_errors_from_worker() {
+    let problem = RoutingProblem::named("bad_weight_type", lkh_rs::ProblemKind::Tsp, 3)
+        .expect("build problem")
+        .with_keyword("EDGE_WEIGHT_TYPE", "NOT_A_METRIC")
+        .expect("add keyword");
+
+    let err = backend()
+        .solve_problem(&problem, &SearchParameters::new())
+        .expect_err("LKH rejects the weight type");
+
+    match err {
+        LkhError::Native(message) => {
+            assert!(message.contains("EDGE_WEIGHT_TYPE"), "message: {message}");
+        }
+        other => panic!("expected Native, got {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn rejects_distance_function_problems_before_starting_a_worker() {
+    let problem = RoutingProblem::distance_function(4).expect("build problem");
+
+    assert!(matches!(
+        backend().solve_problem(&problem, &SearchParameters::new()),
+        Err(LkhError::InvalidProblem(_))
+    ));
+}
+
+#[test]
+fn reports_native_abort_when_worker_exits() {
+    let backend = SubprocessBackend::with_args("sh", ["-c", "echo boom >&2; exit 3"]);
+
+    let err = backend
+        .solve_parameter_file("tests/fixtures/tiny.par")
+        .expect_err("worker exits without answering");
+
+    match err {
+        LkhError::NativeAbort { exit_code, stderr } => {
+            assert_eq!(exit_code, Some(3));
+            assert!(stderr.contains("boom"), "stderr:\n{stderr}");
+        }
+        other => panic!("expected NativeAbort, got {other:?}"),
+    }
+}